@@ -0,0 +1,2 @@
+//! The C and WebAssembly libraries of `plutofilter-rs`, built with its `capi` and `wasm` features.
+pub use plutofilter_rs::*;
@@ -0,0 +1,262 @@
+#ifndef PLUTOFILTER_H
+#define PLUTOFILTER_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit by hand. */
+
+#include <stdint.h>
+
+/*
+ Blend modes for combining source and backdrop surfaces, see [BlendMode].
+
+ Passed to [plutofilter_blend] as an `int`, so values from C are checked before they are read.
+ */
+typedef enum {
+  PLUTOFILTER_BLEND_MODE_NORMAL,
+  PLUTOFILTER_BLEND_MODE_MULTIPLY,
+  PLUTOFILTER_BLEND_MODE_SCREEN,
+  PLUTOFILTER_BLEND_MODE_OVERLAY,
+  PLUTOFILTER_BLEND_MODE_DARKEN,
+  PLUTOFILTER_BLEND_MODE_LIGHTEN,
+  PLUTOFILTER_BLEND_MODE_COLOR_DODGE,
+  PLUTOFILTER_BLEND_MODE_COLOR_BURN,
+  PLUTOFILTER_BLEND_MODE_HARD_LIGHT,
+  PLUTOFILTER_BLEND_MODE_SOFT_LIGHT,
+  PLUTOFILTER_BLEND_MODE_DIFFERENCE,
+  PLUTOFILTER_BLEND_MODE_EXCLUSION,
+} plutofilter_blend_mode_t;
+
+/*
+ Compositing operators for combining source and backdrop surfaces, see [CompositeOperator].
+
+ Passed to [plutofilter_composite] as an `int`, so values from C are checked before they are
+ read.
+ */
+typedef enum {
+  PLUTOFILTER_COMPOSITE_OPERATOR_OVER,
+  PLUTOFILTER_COMPOSITE_OPERATOR_IN,
+  PLUTOFILTER_COMPOSITE_OPERATOR_OUT,
+  PLUTOFILTER_COMPOSITE_OPERATOR_ATOP,
+  PLUTOFILTER_COMPOSITE_OPERATOR_XOR,
+} plutofilter_composite_operator_t;
+
+/*
+ A 2D image surface in ARGB32 premultiplied format.
+ */
+typedef struct {
+  /*
+   Pointer to at least `stride * (height - 1) + width` pixels.
+   */
+  uint32_t *pixels;
+  /*
+   The width of the surface in pixels.
+   */
+  uint16_t width;
+  /*
+   The height of the surface in pixels.
+   */
+  uint16_t height;
+  /*
+   The number of pixels per row.
+   */
+  uint16_t stride;
+} plutofilter_surface_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Creates a surface from a raw pixel buffer.
+
+ # Safety
+ `pixels` must point to at least `stride * (height - 1) + width` pixels that stay valid, and
+ are not accessed from elsewhere, while the surface is passed to a filter.
+ */
+plutofilter_surface_t plutofilter_surface_make(uint32_t *pixels,
+                                               uint16_t width,
+                                               uint16_t height,
+                                               uint16_t stride);
+
+/*
+ Creates a subregion of an existing surface, clipped to its bounds.
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+plutofilter_surface_t plutofilter_surface_make_sub(plutofilter_surface_t surface,
+                                                   uint16_t x,
+                                                   uint16_t y,
+                                                   uint16_t width,
+                                                   uint16_t height);
+
+/*
+ See [Surface::color_transform]. A null `matrix` leaves `output` unchanged.
+
+ # Safety
+ `matrix` must be null or point to 20 floats. See [plutofilter_surface_make] for the surfaces.
+ */
+void plutofilter_color_transform(plutofilter_surface_t input,
+                                 plutofilter_surface_t output,
+                                 const float *matrix);
+
+/*
+ See [Surface::color_transform_opacity].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_opacity(plutofilter_surface_t input,
+                                         plutofilter_surface_t output,
+                                         float amount);
+
+/*
+ See [Surface::color_transform_brightness].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_brightness(plutofilter_surface_t input,
+                                            plutofilter_surface_t output,
+                                            float amount);
+
+/*
+ See [Surface::color_transform_invert].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_invert(plutofilter_surface_t input,
+                                        plutofilter_surface_t output,
+                                        float amount);
+
+/*
+ See [Surface::color_transform_contrast].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_contrast(plutofilter_surface_t input,
+                                          plutofilter_surface_t output,
+                                          float amount);
+
+/*
+ See [Surface::color_transform_saturate].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_saturate(plutofilter_surface_t input,
+                                          plutofilter_surface_t output,
+                                          float amount);
+
+/*
+ See [Surface::color_transform_grayscale].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_grayscale(plutofilter_surface_t input,
+                                           plutofilter_surface_t output,
+                                           float amount);
+
+/*
+ See [Surface::color_transform_sepia].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_sepia(plutofilter_surface_t input,
+                                       plutofilter_surface_t output,
+                                       float amount);
+
+/*
+ See [Surface::color_transform_hue_rotate].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_hue_rotate(plutofilter_surface_t input,
+                                            plutofilter_surface_t output,
+                                            float angle);
+
+/*
+ See [Surface::color_transform_luminance_to_alpha].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_luminance_to_alpha(plutofilter_surface_t input,
+                                                    plutofilter_surface_t output);
+
+/*
+ See [Surface::color_transform_srgb_to_linear_rgb].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_srgb_to_linear_rgb(plutofilter_surface_t input,
+                                                    plutofilter_surface_t output);
+
+/*
+ See [Surface::color_transform_linear_rgb_to_srgb].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_color_transform_linear_rgb_to_srgb(plutofilter_surface_t input,
+                                                    plutofilter_surface_t output);
+
+/*
+ See [Surface::gaussian_blur]. Negative standard deviations leave `output` unchanged.
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_gaussian_blur(plutofilter_surface_t input,
+                               plutofilter_surface_t output,
+                               float std_deviation_x,
+                               float std_deviation_y);
+
+/*
+ See [Surface::blend]. `mode` is a [plutofilter_blend_mode_t], other values leave `out`
+ unchanged.
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_blend(plutofilter_surface_t in1,
+                       plutofilter_surface_t in2,
+                       plutofilter_surface_t out,
+                       int mode);
+
+/*
+ See [Surface::composite]. `op` is a [plutofilter_composite_operator_t], other values leave
+ `out` unchanged.
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_composite(plutofilter_surface_t in1,
+                           plutofilter_surface_t in2,
+                           plutofilter_surface_t out,
+                           int op);
+
+/*
+ See [Surface::composite_arithmetic].
+
+ # Safety
+ See [plutofilter_surface_make].
+ */
+void plutofilter_composite_arithmetic(plutofilter_surface_t in1,
+                                      plutofilter_surface_t in2,
+                                      plutofilter_surface_t out,
+                                      float k1,
+                                      float k2,
+                                      float k3,
+                                      float k4);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PLUTOFILTER_H */
@@ -0,0 +1,22 @@
+// Canvas helpers over the `wasm` bindings, generated into `pkg` with
+// `wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/plutofilter.wasm`.
+import init, { cssFilter } from "../pkg/plutofilter.js";
+
+export { init };
+
+/**
+ * Applies a CSS filter string such as "blur(2px) grayscale(50%)" to the pixels of an
+ * `ImageData`, or any `{ data, width, height }` object holding RGBA bytes, in place.
+ */
+export function filterImageData(image, filter) {
+  const { buffer, byteOffset, byteLength } = image.data;
+  cssFilter(new Uint8Array(buffer, byteOffset, byteLength), image.width, image.height, filter);
+  return image;
+}
+
+/** Applies a CSS filter string to the current pixels of a canvas. */
+export function filterCanvas(canvas, filter) {
+  const context = canvas.getContext("2d");
+  const image = context.getImageData(0, 0, canvas.width, canvas.height);
+  context.putImageData(filterImageData(image, filter), 0, 0);
+}
@@ -0,0 +1,305 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{
+    ColorSpace, HslAdjustment, HueRange, Surface, SurfaceView, color_space::invert_matrix3,
+    utils::*,
+};
+use alloc::{vec, vec::Vec};
+
+/// Largest shift of the Lab `a` and `b` axes applied by a temperature or tint of 1.
+const WHITE_BALANCE_RANGE: f32 = 40.0;
+
+/// Chroma treated as fully saturated by vibrance, close to the most saturated sRGB colors.
+const VIBRANCE_CHROMA: f32 = 128.0;
+
+/// Largest change of lightness applied by shadows or highlights of 1, as a fraction of the range.
+/// Small enough that the tone curve stays monotonic.
+const TONE_RANGE: f32 = 0.125;
+
+/// Hue in degrees at the center of every [HueRange], in the order of its variants.
+const HUE_CENTERS: [f32; 8] = [0.0, 30.0, 60.0, 120.0, 180.0, 240.0, 270.0, 300.0];
+
+/// Applies `adjust` to the unpremultiplied 8-bit color of every pixel, keeping alpha unchanged.
+fn map_pixels(mut input: SurfaceView, output: &mut Surface, adjust: impl Fn([u32; 3]) -> [u32; 3]) {
+    overlap_surface(&mut input, output);
+    for y in 0..output.height {
+        for x in 0..output.width {
+            let [mut r, mut g, mut b, mut a] = init_load_pixel(&input, x, y, input.channel);
+            if a > 0 {
+                unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+                [r, g, b] = adjust([r, g, b]);
+                premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+            }
+            store_pixel(output, x, y, r, g, b, a);
+        }
+    }
+}
+
+/// Applies `adjust` to the unpremultiplied color of every pixel, normalized to [0, 1].
+pub(crate) fn adjust_pixels(
+    input: SurfaceView,
+    output: &mut Surface,
+    adjust: impl Fn([f32; 3]) -> [f32; 3],
+) {
+    map_pixels(input, output, |rgb| {
+        adjust(rgb.map(|c| c as f32 / 255.0)).map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u32)
+    });
+}
+
+/// Applies one lookup table per color channel.
+pub(crate) fn apply_tables(input: SurfaceView, output: &mut Surface, tables: &[[u8; 256]; 3]) {
+    map_pixels(input, output, |[r, g, b]| {
+        [
+            tables[0][r as usize] as u32,
+            tables[1][g as usize] as u32,
+            tables[2][b as usize] as u32,
+        ]
+    });
+}
+
+/// Builds a lookup table from a function of the normalized channel value.
+fn table(curve: impl Fn(f32) -> f32) -> [u8; 256] {
+    core::array::from_fn(|i| (curve(i as f32 / 255.0).clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+pub(crate) fn levels_table(black: f32, white: f32, gamma: f32) -> [u8; 256] {
+    table(|v| {
+        ((v - black) / (white - black))
+            .clamp(0.0, 1.0)
+            .powf(1.0 / gamma)
+    })
+}
+
+/// Builds a lookup table from a monotone cubic spline through the control points.
+///
+/// Tangents follow Fritsch and Carlson, so the curve never overshoots between two points and
+/// stays monotonic wherever the points are. Values outside the first and last points are held
+/// constant.
+pub(crate) fn curve_table(points: &[[f32; 2]]) -> [u8; 256] {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    if points.windows(2).any(|pair| pair[0][0] == pair[1][0]) {
+        panic!("curve control points must have distinct x coordinates.")
+    }
+
+    let secants: Vec<f32> = points
+        .windows(2)
+        .map(|pair| (pair[1][1] - pair[0][1]) / (pair[1][0] - pair[0][0]))
+        .collect();
+    let mut tangents = vec![0.0; points.len()];
+    tangents[0] = secants[0];
+    tangents[points.len() - 1] = secants[secants.len() - 1];
+    for i in 1..points.len() - 1 {
+        if secants[i - 1] * secants[i] > 0.0 {
+            tangents[i] = (secants[i - 1] + secants[i]) / 2.0;
+        }
+    }
+    for (i, &secant) in secants.iter().enumerate() {
+        if secant == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        let (alpha, beta) = (tangents[i] / secant, tangents[i + 1] / secant);
+        let length = alpha.hypot(beta);
+        if length > 3.0 {
+            tangents[i] = 3.0 * alpha / length * secant;
+            tangents[i + 1] = 3.0 * beta / length * secant;
+        }
+    }
+
+    table(|v| {
+        let (first, last) = (points[0], points[points.len() - 1]);
+        if v <= first[0] {
+            return first[1];
+        }
+        if v >= last[0] {
+            return last[1];
+        }
+        let i = points.partition_point(|point| point[0] <= v) - 1;
+        let ([x0, y0], [x1, y1]) = (points[i], points[i + 1]);
+        let h = x1 - x0;
+        let t = (v - x0) / h;
+        let (t2, t3) = (t * t, t * t * t);
+        (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+            + (t3 - 2.0 * t2 + t) * h * tangents[i]
+            + (-2.0 * t3 + 3.0 * t2) * y1
+            + (t3 - t2) * h * tangents[i + 1]
+    })
+}
+
+/// Converts between the RGB values of a color space and CIE L*a*b* relative to its D65 white.
+struct LabSpace {
+    space: ColorSpace,
+    to_xyz: [[f32; 3]; 3],
+    from_xyz: [[f32; 3]; 3],
+    white: [f32; 3],
+}
+
+impl LabSpace {
+    fn new(space: ColorSpace) -> Self {
+        let to_xyz = space.rgb_to_xyz();
+        let from_xyz = invert_matrix3(&to_xyz).expect("primaries are linearly independent");
+        Self {
+            space,
+            to_xyz: to_xyz.map(|row| row.map(|c| c as f32)),
+            from_xyz: from_xyz.map(|row| row.map(|c| c as f32)),
+            white: to_xyz.map(|row| (row[0] + row[1] + row[2]) as f32),
+        }
+    }
+
+    fn lab(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let linear = rgb.map(|c| self.space.decode(c));
+        let xyz = multiply(&self.to_xyz, linear);
+        let [fx, fy, fz] = [0, 1, 2].map(|i| lab_f(xyz[i] / self.white[i]));
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    fn rgb(&self, [l, a, b]: [f32; 3]) -> [f32; 3] {
+        let fy = (l + 16.0) / 116.0;
+        let f = [fy + a / 500.0, fy, fy - b / 200.0];
+        let xyz = [0, 1, 2].map(|i| lab_f_inverse(f[i]) * self.white[i]);
+        multiply(&self.from_xyz, xyz).map(|c| self.space.encode(c.clamp(0.0, 1.0)))
+    }
+}
+
+#[inline(always)]
+fn multiply(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+#[inline(always)]
+fn lab_f(t: f32) -> f32 {
+    const DELTA: f32 = 6.0 / 29.0;
+    if t > DELTA * DELTA * DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+    }
+}
+
+#[inline(always)]
+fn lab_f_inverse(t: f32) -> f32 {
+    const DELTA: f32 = 6.0 / 29.0;
+    if t > DELTA {
+        t * t * t
+    } else {
+        3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+pub(crate) fn vibrance(input: SurfaceView, output: &mut Surface, amount: f32) {
+    let lab_space = LabSpace::new(input.color_space);
+    adjust_pixels(input, output, |rgb| {
+        let [l, a, b] = lab_space.lab(rgb);
+        let saturation = (a.hypot(b) / VIBRANCE_CHROMA).min(1.0);
+        let scale = (1.0 + amount * (1.0 - saturation)).max(0.0);
+        lab_space.rgb([l, a * scale, b * scale])
+    });
+}
+
+pub(crate) fn temperature_tint(
+    input: SurfaceView,
+    output: &mut Surface,
+    temperature: f32,
+    tint: f32,
+) {
+    let lab_space = LabSpace::new(input.color_space);
+    adjust_pixels(input, output, |rgb| {
+        let [l, a, b] = lab_space.lab(rgb);
+        // Scaling the shift with lightness keeps black neutral, like a white balance gain.
+        let shift = WHITE_BALANCE_RANGE * l / 100.0;
+        lab_space.rgb([l, a + tint * shift, b + temperature * shift])
+    });
+}
+
+pub(crate) fn shadows_highlights(
+    input: SurfaceView,
+    output: &mut Surface,
+    shadows: f32,
+    highlights: f32,
+) {
+    let lab_space = LabSpace::new(input.color_space);
+    adjust_pixels(input, output, |rgb| {
+        let [l, a, b] = lab_space.lab(rgb);
+        let t = (l / 100.0).clamp(0.0, 1.0);
+        // Weights peak at a third and two thirds of the lightness range and vanish at black and
+        // white, so the end points stay fixed.
+        let shadow_weight = 6.75 * t * (1.0 - t) * (1.0 - t);
+        let highlight_weight = 6.75 * t * t * (1.0 - t);
+        let t = t + TONE_RANGE * (shadows * shadow_weight + highlights * highlight_weight);
+        lab_space.rgb([t * 100.0, a, b])
+    });
+}
+
+pub(crate) fn hsl_adjust(
+    input: SurfaceView,
+    output: &mut Surface,
+    adjustments: &[(HueRange, HslAdjustment)],
+) {
+    let mut bands = [HslAdjustment::default(); 8];
+    for &(range, adjustment) in adjustments {
+        bands[range as usize] = adjustment;
+    }
+    adjust_pixels(input, output, |rgb| {
+        let [h, s, l] = rgb_to_hsl(rgb);
+        if s == 0.0 {
+            return rgb;
+        }
+        // Blend the adjustments of the two hue ranges around `h` linearly.
+        let next = HUE_CENTERS.partition_point(|&center| center <= h) % HUE_CENTERS.len();
+        let previous = (next + HUE_CENTERS.len() - 1) % HUE_CENTERS.len();
+        let span = (HUE_CENTERS[next] - HUE_CENTERS[previous]).rem_euclid(360.0);
+        let t = (h - HUE_CENTERS[previous]).rem_euclid(360.0) / span;
+        let mix =
+            |f: fn(&HslAdjustment) -> f32| f(&bands[previous]) * (1.0 - t) + f(&bands[next]) * t;
+
+        let h = (h + mix(|band| band.hue)).rem_euclid(360.0);
+        let l = {
+            let lightness = mix(|band| band.lightness) * s;
+            if lightness > 0.0 {
+                l + (1.0 - l) * lightness
+            } else {
+                l + l * lightness
+            }
+        };
+        let s = (s * (1.0 + mix(|band| band.saturation))).clamp(0.0, 1.0);
+        hsl_to_rgb([h, s, l.clamp(0.0, 1.0)])
+    });
+}
+
+/// Converts a normalized RGB color to hue in degrees, saturation and lightness.
+fn rgb_to_hsl([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return [0.0, 0.0, l];
+    }
+    let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    [h * 60.0, s.min(1.0), l]
+}
+
+fn hsl_to_rgb([h, s, l]: [f32; 3]) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h / 60.0;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let [r, g, b] = match h as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    let m = l - chroma / 2.0;
+    [r + m, g + m, b + m]
+}
@@ -0,0 +1,176 @@
+use crate::{Surface, SurfaceView, utils::*};
+use alloc::{vec, vec::Vec};
+
+/// Number of values stored per grid cell: premultiplied red, green, blue, alpha and the weight.
+const CELL_SIZE: usize = 5;
+
+/// Extra cells on each side of every grid axis so the blur kernel never reads outside the grid.
+const PADDING: usize = 2;
+
+/// Smallest width of a grid cell in pixels. Smaller spatial sigmas would allocate several cells per
+/// pixel, so they are approximated with cells of this size.
+const MIN_CELL_SPATIAL: f32 = 2.0;
+
+/// Smallest height of a grid cell in luminance levels, which keeps the intensity axis to at most
+/// 32 cells however small the range sigma is.
+const MIN_CELL_RANGE: f32 = 8.0;
+
+/// Returns the width in pixels of the grid cells used for `sigma_spatial`.
+pub(crate) fn cell_spatial(sigma_spatial: f32) -> f32 {
+    sigma_spatial.max(MIN_CELL_SPATIAL)
+}
+
+/// A coarse 3D grid (x, y, intensity) used to approximate a bilateral filter.
+///
+/// Pixels are splatted into the cell matching their position and luminance, the grid is blurred
+/// with a small separable kernel, and the result is sliced back with trilinear interpolation.
+/// The cost is proportional to the number of pixels plus the number of cells, so it does not grow
+/// with the spatial radius of the filter.
+struct BilateralGrid {
+    cells: Vec<f32>,
+    width: usize,
+    height: usize,
+    depth: usize,
+    cell_spatial: f32,
+    cell_range: f32,
+}
+
+impl BilateralGrid {
+    fn new(width: u32, height: u32, cell_spatial: f32, cell_range: f32) -> Self {
+        let cells = |extent: f32, cell: f32| (extent / cell + 0.5) as usize + 1 + 2 * PADDING;
+        let width = cells(width.saturating_sub(1) as f32, cell_spatial);
+        let height = cells(height.saturating_sub(1) as f32, cell_spatial);
+        let depth = cells(255.0, cell_range);
+        Self {
+            cells: vec![0.0; width * height * depth * CELL_SIZE],
+            width,
+            height,
+            depth,
+            cell_spatial,
+            cell_range,
+        }
+    }
+
+    #[inline(always)]
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        ((z * self.height + y) * self.width + x) * CELL_SIZE
+    }
+
+    fn splat(&mut self, input: &SurfaceView) {
+        for y in 0..input.height {
+            for x in 0..input.width {
+                let pixel = init_load_pixel(input, x, y, input.channel);
+                let gx = (x as f32 / self.cell_spatial + 0.5) as usize + PADDING;
+                let gy = (y as f32 / self.cell_spatial + 0.5) as usize + PADDING;
+                let gz = (luminance(pixel) / self.cell_range + 0.5) as usize + PADDING;
+                let index = self.index(gx, gy, gz);
+                let cell = &mut self.cells[index..index + CELL_SIZE];
+                cell[0] += pixel[0] as f32;
+                cell[1] += pixel[1] as f32;
+                cell[2] += pixel[2] as f32;
+                cell[3] += pixel[3] as f32;
+                cell[4] += 1.0;
+            }
+        }
+    }
+
+    /// Blurs the grid along all three axes with a `[1, 4, 6, 4, 1] / 16` kernel.
+    fn blur(&mut self) {
+        let mut scratch = vec![0.0; self.cells.len()];
+        let strides = [
+            CELL_SIZE,
+            self.width * CELL_SIZE,
+            self.width * self.height * CELL_SIZE,
+        ];
+        for stride in strides {
+            let cells = &self.cells;
+            let interior = cells.len() - 4 * stride;
+            for (index, value) in scratch
+                .iter_mut()
+                .enumerate()
+                .skip(2 * stride)
+                .take(interior)
+            {
+                *value = (cells[index - 2 * stride]
+                    + 4.0 * cells[index - stride]
+                    + 6.0 * cells[index]
+                    + 4.0 * cells[index + stride]
+                    + cells[index + 2 * stride])
+                    / 16.0;
+            }
+            core::mem::swap(&mut self.cells, &mut scratch);
+        }
+    }
+
+    fn slice(&self, input: &SurfaceView, output: &mut Surface) {
+        for y in 0..output.height {
+            for x in 0..output.width {
+                let pixel = init_load_pixel(input, x, y, input.channel);
+                let gx = x as f32 / self.cell_spatial + PADDING as f32;
+                let gy = y as f32 / self.cell_spatial + PADDING as f32;
+                let gz = luminance(pixel) / self.cell_range + PADDING as f32;
+                let cell = self.trilinear(gx, gy, gz);
+                if cell[4] <= f32::EPSILON {
+                    store_pixel(output, x, y, pixel[0], pixel[1], pixel[2], pixel[3]);
+                    continue;
+                }
+                let a = clamp_pixel((cell[3] / cell[4] + 0.5) as u32);
+                let r = clamp_pixel((cell[0] / cell[4] + 0.5) as u32).min(a);
+                let g = clamp_pixel((cell[1] / cell[4] + 0.5) as u32).min(a);
+                let b = clamp_pixel((cell[2] / cell[4] + 0.5) as u32).min(a);
+                store_pixel(output, x, y, r, g, b, a);
+            }
+        }
+    }
+
+    fn trilinear(&self, x: f32, y: f32, z: f32) -> [f32; CELL_SIZE] {
+        let (x0, y0, z0) = (x as usize, y as usize, z as usize);
+        let (x1, y1, z1) = (
+            (x0 + 1).min(self.width - 1),
+            (y0 + 1).min(self.height - 1),
+            (z0 + 1).min(self.depth - 1),
+        );
+        let (fx, fy, fz) = (x - x0 as f32, y - y0 as f32, z - z0 as f32);
+
+        let mut result = [0.0; CELL_SIZE];
+        for (zi, wz) in [(z0, 1.0 - fz), (z1, fz)] {
+            for (yi, wy) in [(y0, 1.0 - fy), (y1, fy)] {
+                for (xi, wx) in [(x0, 1.0 - fx), (x1, fx)] {
+                    let weight = wx * wy * wz;
+                    let index = self.index(xi, yi, zi);
+                    for (value, cell) in
+                        result.iter_mut().zip(&self.cells[index..index + CELL_SIZE])
+                    {
+                        *value += weight * cell;
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Luminance of a premultiplied pixel in the range [0, 255], used as the range guide.
+#[inline(always)]
+fn luminance([r, g, b, a]: [u32; 4]) -> f32 {
+    let (mut r, mut g, mut b, mut a) = (r, g, b, a);
+    unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+    r as f32 * 0.2126 + g as f32 * 0.7152 + b as f32 * 0.0722
+}
+
+pub(crate) fn bilateral_grid(
+    input: &SurfaceView,
+    output: &mut Surface,
+    sigma_spatial: f32,
+    sigma_range: f32,
+) {
+    let mut grid = BilateralGrid::new(
+        output.width,
+        output.height,
+        cell_spatial(sigma_spatial),
+        (sigma_range * 255.0).max(MIN_CELL_RANGE),
+    );
+    grid.splat(input);
+    grid.blur();
+    grid.slice(input, output);
+}
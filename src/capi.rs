@@ -0,0 +1,597 @@
+//! C ABI mirroring `plutofilter.h` of the original library.
+//!
+//! Surfaces hold native `0xAARRGGBB` premultiplied pixels, and every primitive accepts inputs
+//! and outputs that refer to the same buffer. Inputs that overlap the output are copied first.
+//! Arguments the filters reject leave the output unchanged, so no panic reaches the caller.
+#![allow(non_camel_case_types)]
+
+use crate::{
+    BlendMode, ColorChannel, ColorInterpolation, ColorSpace, CompositeOperator, Surface,
+    SurfaceView,
+};
+use std::{borrow::Cow, ffi::c_int, ops::Range};
+
+/// The byte order of a native `0xAARRGGBB` integer.
+const NATIVE_ARGB: ColorChannel = if cfg!(target_endian = "little") {
+    ColorChannel::BGRA32
+} else {
+    ColorChannel::ARGB32
+};
+
+/// A 2D image surface in ARGB32 premultiplied format.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct plutofilter_surface_t {
+    /// Pointer to at least `stride * (height - 1) + width` pixels.
+    pub pixels: *mut u32,
+    /// The width of the surface in pixels.
+    pub width: u16,
+    /// The height of the surface in pixels.
+    pub height: u16,
+    /// The number of pixels per row.
+    pub stride: u16,
+}
+
+/// Blend modes for combining source and backdrop surfaces, see [BlendMode].
+///
+/// Passed to [plutofilter_blend] as an `int`, so values from C are checked before they are read.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum plutofilter_blend_mode_t {
+    PLUTOFILTER_BLEND_MODE_NORMAL,
+    PLUTOFILTER_BLEND_MODE_MULTIPLY,
+    PLUTOFILTER_BLEND_MODE_SCREEN,
+    PLUTOFILTER_BLEND_MODE_OVERLAY,
+    PLUTOFILTER_BLEND_MODE_DARKEN,
+    PLUTOFILTER_BLEND_MODE_LIGHTEN,
+    PLUTOFILTER_BLEND_MODE_COLOR_DODGE,
+    PLUTOFILTER_BLEND_MODE_COLOR_BURN,
+    PLUTOFILTER_BLEND_MODE_HARD_LIGHT,
+    PLUTOFILTER_BLEND_MODE_SOFT_LIGHT,
+    PLUTOFILTER_BLEND_MODE_DIFFERENCE,
+    PLUTOFILTER_BLEND_MODE_EXCLUSION,
+}
+
+/// Compositing operators for combining source and backdrop surfaces, see [CompositeOperator].
+///
+/// Passed to [plutofilter_composite] as an `int`, so values from C are checked before they are
+/// read.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum plutofilter_composite_operator_t {
+    PLUTOFILTER_COMPOSITE_OPERATOR_OVER,
+    PLUTOFILTER_COMPOSITE_OPERATOR_IN,
+    PLUTOFILTER_COMPOSITE_OPERATOR_OUT,
+    PLUTOFILTER_COMPOSITE_OPERATOR_ATOP,
+    PLUTOFILTER_COMPOSITE_OPERATOR_XOR,
+}
+
+impl plutofilter_blend_mode_t {
+    const ALL: [Self; 12] = {
+        use plutofilter_blend_mode_t::*;
+        [
+            PLUTOFILTER_BLEND_MODE_NORMAL,
+            PLUTOFILTER_BLEND_MODE_MULTIPLY,
+            PLUTOFILTER_BLEND_MODE_SCREEN,
+            PLUTOFILTER_BLEND_MODE_OVERLAY,
+            PLUTOFILTER_BLEND_MODE_DARKEN,
+            PLUTOFILTER_BLEND_MODE_LIGHTEN,
+            PLUTOFILTER_BLEND_MODE_COLOR_DODGE,
+            PLUTOFILTER_BLEND_MODE_COLOR_BURN,
+            PLUTOFILTER_BLEND_MODE_HARD_LIGHT,
+            PLUTOFILTER_BLEND_MODE_SOFT_LIGHT,
+            PLUTOFILTER_BLEND_MODE_DIFFERENCE,
+            PLUTOFILTER_BLEND_MODE_EXCLUSION,
+        ]
+    };
+
+    /// Returns the blend mode with the value `mode`, or `None` if there is none.
+    fn from_c(mode: c_int) -> Option<Self> {
+        usize::try_from(mode)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+}
+
+impl plutofilter_composite_operator_t {
+    const ALL: [Self; 5] = {
+        use plutofilter_composite_operator_t::*;
+        [
+            PLUTOFILTER_COMPOSITE_OPERATOR_OVER,
+            PLUTOFILTER_COMPOSITE_OPERATOR_IN,
+            PLUTOFILTER_COMPOSITE_OPERATOR_OUT,
+            PLUTOFILTER_COMPOSITE_OPERATOR_ATOP,
+            PLUTOFILTER_COMPOSITE_OPERATOR_XOR,
+        ]
+    };
+
+    /// Returns the compositing operator with the value `op`, or `None` if there is none.
+    fn from_c(op: c_int) -> Option<Self> {
+        usize::try_from(op)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+}
+
+impl From<plutofilter_blend_mode_t> for BlendMode {
+    fn from(mode: plutofilter_blend_mode_t) -> Self {
+        use plutofilter_blend_mode_t::*;
+        match mode {
+            PLUTOFILTER_BLEND_MODE_NORMAL => BlendMode::Normal,
+            PLUTOFILTER_BLEND_MODE_MULTIPLY => BlendMode::Multiply,
+            PLUTOFILTER_BLEND_MODE_SCREEN => BlendMode::Screen,
+            PLUTOFILTER_BLEND_MODE_OVERLAY => BlendMode::Overlay,
+            PLUTOFILTER_BLEND_MODE_DARKEN => BlendMode::Darken,
+            PLUTOFILTER_BLEND_MODE_LIGHTEN => BlendMode::Lighten,
+            PLUTOFILTER_BLEND_MODE_COLOR_DODGE => BlendMode::ColorDodge,
+            PLUTOFILTER_BLEND_MODE_COLOR_BURN => BlendMode::ColorBurn,
+            PLUTOFILTER_BLEND_MODE_HARD_LIGHT => BlendMode::HardLight,
+            PLUTOFILTER_BLEND_MODE_SOFT_LIGHT => BlendMode::SoftLight,
+            PLUTOFILTER_BLEND_MODE_DIFFERENCE => BlendMode::Difference,
+            PLUTOFILTER_BLEND_MODE_EXCLUSION => BlendMode::Exclusion,
+        }
+    }
+}
+
+impl From<plutofilter_composite_operator_t> for CompositeOperator {
+    fn from(op: plutofilter_composite_operator_t) -> Self {
+        use plutofilter_composite_operator_t::*;
+        match op {
+            PLUTOFILTER_COMPOSITE_OPERATOR_OVER => CompositeOperator::Over,
+            PLUTOFILTER_COMPOSITE_OPERATOR_IN => CompositeOperator::In,
+            PLUTOFILTER_COMPOSITE_OPERATOR_OUT => CompositeOperator::Out,
+            PLUTOFILTER_COMPOSITE_OPERATOR_ATOP => CompositeOperator::Atop,
+            PLUTOFILTER_COMPOSITE_OPERATOR_XOR => CompositeOperator::Xor,
+        }
+    }
+}
+
+impl plutofilter_surface_t {
+    /// The number of pixels from the first to the last pixel of the surface.
+    fn len(&self) -> usize {
+        match (self.width, self.height) {
+            (0, _) | (_, 0) => 0,
+            (width, height) => self.stride as usize * (height as usize - 1) + width as usize,
+        }
+    }
+
+    /// The addresses covered by the pixels of the surface.
+    fn span(&self) -> Range<usize> {
+        let start = self.pixels as usize;
+        start..start + self.len() * size_of::<u32>()
+    }
+
+    /// Borrows the pixels, or copies them if they overlap `output`.
+    ///
+    /// # Safety
+    /// See [plutofilter_surface_make].
+    unsafe fn input(&self, output: &Self) -> Cow<'_, [u32]> {
+        let pixels = match self.len() {
+            0 => &[],
+            // SAFETY: The caller guarantees that the surface covers `len` valid pixels.
+            len => unsafe { std::slice::from_raw_parts(self.pixels, len) },
+        };
+        let (span, other) = (self.span(), output.span());
+        if span.start < other.end && other.start < span.end {
+            Cow::Owned(pixels.to_vec())
+        } else {
+            Cow::Borrowed(pixels)
+        }
+    }
+
+    fn view<'a>(&self, pixels: &'a [u32]) -> SurfaceView<'a> {
+        SurfaceView {
+            pixels,
+            width: self.width as u32,
+            height: self.height as u32,
+            stride: self.stride as u32,
+            channel: NATIVE_ARGB,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// # Safety
+    /// See [plutofilter_surface_make].
+    unsafe fn output(&mut self) -> Surface<'_> {
+        let pixels = match self.len() {
+            0 => &mut [],
+            // SAFETY: The caller guarantees that the surface covers `len` valid pixels, and inputs
+            // overlapping them were copied.
+            len => unsafe { std::slice::from_raw_parts_mut(self.pixels, len) },
+        };
+        Surface {
+            pixels: pixels.into(),
+            width: self.width as u32,
+            height: self.height as u32,
+            stride: self.stride as u32,
+            channel: NATIVE_ARGB,
+            color_interpolation: ColorInterpolation::SRGB,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+}
+
+/// Runs `filter` with the input and output surfaces borrowed from C.
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+unsafe fn run(
+    input: plutofilter_surface_t,
+    mut output: plutofilter_surface_t,
+    filter: impl FnOnce(SurfaceView, &mut Surface),
+) {
+    // SAFETY: Forwarded from the caller.
+    let pixels = unsafe { input.input(&output) };
+    let view = input.view(&pixels);
+    // SAFETY: Forwarded from the caller, and the input no longer borrows overlapping pixels.
+    filter(view, &mut unsafe { output.output() });
+}
+
+/// Runs `filter` with two input surfaces and an output surface borrowed from C.
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+unsafe fn run2(
+    in1: plutofilter_surface_t,
+    in2: plutofilter_surface_t,
+    mut out: plutofilter_surface_t,
+    filter: impl FnOnce(SurfaceView, SurfaceView, &mut Surface),
+) {
+    // SAFETY: Forwarded from the caller.
+    let (pixels1, pixels2) = unsafe { (in1.input(&out), in2.input(&out)) };
+    let (view1, view2) = (in1.view(&pixels1), in2.view(&pixels2));
+    // SAFETY: Forwarded from the caller, and the inputs no longer borrow overlapping pixels.
+    filter(view1, view2, &mut unsafe { out.output() });
+}
+
+/// Creates a surface from a raw pixel buffer.
+///
+/// # Safety
+/// `pixels` must point to at least `stride * (height - 1) + width` pixels that stay valid, and
+/// are not accessed from elsewhere, while the surface is passed to a filter.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_surface_make(
+    pixels: *mut u32,
+    width: u16,
+    height: u16,
+    stride: u16,
+) -> plutofilter_surface_t {
+    plutofilter_surface_t {
+        pixels,
+        width,
+        height,
+        stride,
+    }
+}
+
+/// Creates a subregion of an existing surface, clipped to its bounds.
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_surface_make_sub(
+    surface: plutofilter_surface_t,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> plutofilter_surface_t {
+    let (x, y) = (x.min(surface.width), y.min(surface.height));
+    let offset = y as usize * surface.stride as usize + x as usize;
+    plutofilter_surface_t {
+        pixels: surface.pixels.wrapping_add(offset),
+        width: width.min(surface.width - x),
+        height: height.min(surface.height - y),
+        stride: surface.stride,
+    }
+}
+
+/// See [Surface::color_transform]. A null `matrix` leaves `output` unchanged.
+///
+/// # Safety
+/// `matrix` must be null or point to 20 floats. See [plutofilter_surface_make] for the surfaces.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    matrix: *const f32,
+) {
+    if matrix.is_null() {
+        return;
+    }
+    // SAFETY: The caller guarantees that `matrix` points to 20 floats.
+    let matrix: [f32; 20] = unsafe { std::slice::from_raw_parts(matrix, 20) }
+        .try_into()
+        .expect("slice of 20 floats");
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform(input, output, matrix)
+        })
+    }
+}
+
+/// See [Surface::color_transform_opacity].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_opacity(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_opacity(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_brightness].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_brightness(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_brightness(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_invert].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_invert(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_invert(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_contrast].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_contrast(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_contrast(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_saturate].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_saturate(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_saturate(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_grayscale].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_grayscale(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_grayscale(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_sepia].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_sepia(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    amount: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_sepia(input, output, amount)
+        })
+    }
+}
+
+/// See [Surface::color_transform_hue_rotate].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_hue_rotate(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    angle: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_hue_rotate(input, output, angle)
+        })
+    }
+}
+
+/// See [Surface::color_transform_luminance_to_alpha].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_luminance_to_alpha(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_luminance_to_alpha(input, output)
+        })
+    }
+}
+
+/// See [Surface::color_transform_srgb_to_linear_rgb].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_srgb_to_linear_rgb(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_srgb_to_linear_rgb(input, output)
+        })
+    }
+}
+
+/// See [Surface::color_transform_linear_rgb_to_srgb].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_color_transform_linear_rgb_to_srgb(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::color_transform_linear_rgb_to_srgb(input, output)
+        })
+    }
+}
+
+/// See [Surface::gaussian_blur]. Negative standard deviations leave `output` unchanged.
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_gaussian_blur(
+    input: plutofilter_surface_t,
+    output: plutofilter_surface_t,
+    std_deviation_x: f32,
+    std_deviation_y: f32,
+) {
+    if std_deviation_x < 0.0 || std_deviation_y < 0.0 {
+        return;
+    }
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run(input, output, |input, output| {
+            Surface::gaussian_blur(input, output, std_deviation_x, std_deviation_y)
+        })
+    }
+}
+
+/// See [Surface::blend]. `mode` is a [plutofilter_blend_mode_t], other values leave `out`
+/// unchanged.
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_blend(
+    in1: plutofilter_surface_t,
+    in2: plutofilter_surface_t,
+    out: plutofilter_surface_t,
+    mode: c_int,
+) {
+    let Some(mode) = plutofilter_blend_mode_t::from_c(mode) else {
+        return;
+    };
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run2(in1, in2, out, |in1, in2, out| {
+            Surface::blend(in1, in2, out, mode.into())
+        })
+    }
+}
+
+/// See [Surface::composite]. `op` is a [plutofilter_composite_operator_t], other values leave
+/// `out` unchanged.
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_composite(
+    in1: plutofilter_surface_t,
+    in2: plutofilter_surface_t,
+    out: plutofilter_surface_t,
+    op: c_int,
+) {
+    let Some(op) = plutofilter_composite_operator_t::from_c(op) else {
+        return;
+    };
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run2(in1, in2, out, |in1, in2, out| {
+            Surface::composite(in1, in2, out, op.into())
+        })
+    }
+}
+
+/// See [Surface::composite_arithmetic].
+///
+/// # Safety
+/// See [plutofilter_surface_make].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plutofilter_composite_arithmetic(
+    in1: plutofilter_surface_t,
+    in2: plutofilter_surface_t,
+    out: plutofilter_surface_t,
+    k1: f32,
+    k2: f32,
+    k3: f32,
+    k4: f32,
+) {
+    // SAFETY: Forwarded from the caller.
+    unsafe {
+        run2(in1, in2, out, |in1, in2, out| {
+            Surface::composite_arithmetic(in1, in2, out, k1, k2, k3, k4)
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{ColorSpace, utils::*};
+use core::ops::Mul;
+
+/// A 5x4 color matrix applied to unpremultiplied `[r, g, b, a]` colors.
+///
+/// Values are stored in row-major order, with one row per output channel. The first four columns
+/// weight the input channels and the fifth column is an offset, in normalized units where 1 is
+/// full intensity. The matrix is treated as a 5x5 affine transform whose last row is
+/// `[0, 0, 0, 0, 1]`, so matrices can be multiplied, inverted and interpolated.
+///
+/// Chaining presets into one matrix applies them in a single pass over the pixels. Separate passes
+/// clamp every channel to `[0, 1]` and round to 8 bits after every step, while the chained matrix
+/// only clamps at the end. When an earlier matrix pushes colors out of range the results differ
+/// a lot: `brightness(2.0).then(brightness(0.5))` is the identity, while the two passes clip
+/// highlights first. When every earlier matrix keeps colors in range, they only differ by rounding.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::ColorMatrix;
+///
+/// let matrix = ColorMatrix::contrast(0.97)
+///     .then(ColorMatrix::hue_rotate(330.0))
+///     .then(ColorMatrix::saturate(1.11));
+/// let inverse = matrix.inverse().unwrap();
+/// let [r, g, b, a] = inverse.transform(matrix.transform([0.2, 0.4, 0.6, 1.0]));
+/// assert!((r - 0.2).abs() < 1e-5 && (g - 0.4).abs() < 1e-5 && (b - 0.6).abs() < 1e-5);
+/// assert!((a - 1.0).abs() < 1e-5);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorMatrix([f32; 20]);
+
+impl ColorMatrix {
+    /// The matrix that leaves every color unchanged.
+    pub const IDENTITY: Self = Self([
+        1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        1.0, 0.0,
+    ]);
+
+    /// Creates a matrix from 20 values in row-major order.
+    pub fn new(values: [f32; 20]) -> Self {
+        Self(values)
+    }
+
+    /// Returns the matrix that leaves every color unchanged.
+    pub fn identity() -> Self {
+        Self::IDENTITY
+    }
+
+    /// Returns the 20 values of the matrix in row-major order.
+    pub fn to_array(self) -> [f32; 20] {
+        self.0
+    }
+
+    /// See [crate::Surface::color_transform_opacity].
+    pub fn opacity(amount: f32) -> Self {
+        Self(opacity_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_brightness].
+    pub fn brightness(amount: f32) -> Self {
+        Self(brightness_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_invert].
+    pub fn invert(amount: f32) -> Self {
+        Self(invert_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_contrast].
+    pub fn contrast(amount: f32) -> Self {
+        Self(contrast_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_saturate]. Uses the sRGB luminance coefficients.
+    pub fn saturate(amount: f32) -> Self {
+        Self::saturate_in(amount, ColorSpace::Srgb)
+    }
+
+    /// Saturate matrix using the luminance coefficients of `color_space`.
+    pub fn saturate_in(amount: f32, color_space: ColorSpace) -> Self {
+        Self(color_space.saturate_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_grayscale]. Uses the sRGB luminance coefficients.
+    pub fn grayscale(amount: f32) -> Self {
+        Self::grayscale_in(amount, ColorSpace::Srgb)
+    }
+
+    /// Grayscale matrix using the luminance coefficients of `color_space`.
+    pub fn grayscale_in(amount: f32, color_space: ColorSpace) -> Self {
+        Self(color_space.grayscale_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_sepia].
+    pub fn sepia(amount: f32) -> Self {
+        Self(sepia_matrix(amount))
+    }
+
+    /// See [crate::Surface::color_transform_hue_rotate]. Uses the sRGB luminance coefficients.
+    pub fn hue_rotate(angle: f32) -> Self {
+        Self::hue_rotate_in(angle, ColorSpace::Srgb)
+    }
+
+    /// Hue rotate matrix using the luminance coefficients of `color_space`.
+    pub fn hue_rotate_in(angle: f32, color_space: ColorSpace) -> Self {
+        Self(color_space.hue_rotate_matrix(angle))
+    }
+
+    /// Whether every value is within rounding error of [Self::IDENTITY].
+    #[cfg(feature = "image")]
+    pub(crate) fn is_identity(&self) -> bool {
+        self.0
+            .iter()
+            .zip(Self::IDENTITY.0)
+            .all(|(value, identity)| (value - identity).abs() < 1e-5)
+    }
+
+    #[inline(always)]
+    fn at(&self, row: usize, column: usize) -> f32 {
+        self.0[row * 5 + column]
+    }
+
+    /// Returns the product `self * other`, which applies `other` first and then `self`.
+    pub fn multiply(self, other: Self) -> Self {
+        let mut result = [0.0; 20];
+        for (i, value) in result.iter_mut().enumerate() {
+            let (row, column) = (i / 5, i % 5);
+            *value = (0..4).map(|k| self.at(row, k) * other.at(k, column)).sum();
+            if column == 4 {
+                *value += self.at(row, 4);
+            }
+        }
+        Self(result)
+    }
+
+    /// Whether every unpremultiplied color in `[0, 1]` stays in `[0, 1]` through the matrix, so
+    /// clamping after it has no effect.
+    #[cfg(feature = "image")]
+    pub(crate) fn keeps_unit_range(&self) -> bool {
+        const TOLERANCE: f32 = 1e-6;
+        (0..4).all(|row| {
+            let weights = (0..4).map(|column| self.at(row, column));
+            let low: f32 = weights.clone().map(|w| w.min(0.0)).sum::<f32>() + self.at(row, 4);
+            let high: f32 = weights.map(|w| w.max(0.0)).sum::<f32>() + self.at(row, 4);
+            low >= -TOLERANCE && high <= 1.0 + TOLERANCE
+        })
+    }
+
+    /// Returns the matrix that applies `self` first and then `next`.
+    pub fn then(self, next: Self) -> Self {
+        next.multiply(self)
+    }
+
+    /// Returns the matrix that undoes `self`, or `None` if it is not invertible.
+    ///
+    /// Matrices that drop information, such as a full grayscale or zero opacity, have no inverse.
+    pub fn inverse(self) -> Option<Self> {
+        // Gauss-Jordan elimination of the 4x4 linear part augmented with the identity.
+        let mut rows = [[0.0f64; 8]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().take(4).enumerate() {
+                *value = self.at(i, j) as f64;
+            }
+            row[4 + i] = 1.0;
+        }
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&a, &b| rows[a][column].abs().total_cmp(&rows[b][column].abs()))
+                .unwrap_or(column);
+            if rows[pivot][column].abs() < 1e-9 {
+                return None;
+            }
+            rows.swap(column, pivot);
+            let scale = rows[column][column];
+            rows[column].iter_mut().for_each(|value| *value /= scale);
+            let pivot_row = rows[column];
+            for (i, row) in rows.iter_mut().enumerate() {
+                if i != column {
+                    let factor = row[column];
+                    for (value, pivot_value) in row.iter_mut().zip(pivot_row) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+
+        let mut result = [0.0; 20];
+        for (i, row) in rows.iter().enumerate() {
+            for j in 0..4 {
+                result[i * 5 + j] = row[4 + j] as f32;
+            }
+            let offset: f64 = (0..4).map(|k| row[4 + k] * self.at(k, 4) as f64).sum();
+            result[i * 5 + 4] = -offset as f32;
+        }
+        Some(Self(result))
+    }
+
+    /// Linearly interpolates every value from `self` (`t = 0`) to `other` (`t = 1`).
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self(core::array::from_fn(|i| {
+            self.0[i] + (other.0[i] - self.0[i]) * t
+        }))
+    }
+
+    /// Applies the matrix to an unpremultiplied, normalized `[r, g, b, a]` color without clamping.
+    pub fn transform(self, rgba: [f32; 4]) -> [f32; 4] {
+        core::array::from_fn(|row| {
+            (0..4).map(|k| self.at(row, k) * rgba[k]).sum::<f32>() + self.at(row, 4)
+        })
+    }
+}
+
+impl Default for ColorMatrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for ColorMatrix {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.multiply(other)
+    }
+}
+
+impl From<[f32; 20]> for ColorMatrix {
+    fn from(values: [f32; 20]) -> Self {
+        Self(values)
+    }
+}
+
+impl From<ColorMatrix> for [f32; 20] {
+    fn from(matrix: ColorMatrix) -> Self {
+        matrix.0
+    }
+}
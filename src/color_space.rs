@@ -0,0 +1,176 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{
+    ColorSpace,
+    utils::*,
+    wide::{linear_to_srgb, srgb_to_linear},
+};
+
+pub(crate) type Matrix3 = [[f64; 3]; 3];
+
+/// CIE xy chromaticity of the D65 white point shared by every supported color space.
+const D65: [f64; 2] = [0.3127, 0.3290];
+
+impl ColorSpace {
+    /// CIE xy chromaticities of the red, green and blue primaries.
+    fn primaries(self) -> [[f64; 2]; 3] {
+        match self {
+            ColorSpace::Srgb => [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]],
+            ColorSpace::DisplayP3 => [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060]],
+            ColorSpace::Rec2020 => [[0.708, 0.292], [0.170, 0.797], [0.131, 0.046]],
+            ColorSpace::AdobeRgb => [[0.64, 0.33], [0.21, 0.71], [0.15, 0.06]],
+        }
+    }
+
+    /// Parameters `[g, a, b, c, d]` of the transfer function, as in the ICC parametric curve
+    /// type 3: `linear = (a * v + b) ^ g` for `v >= d`, otherwise `c * v`.
+    pub(crate) fn transfer_parameters(self) -> [f32; 5] {
+        match self {
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => {
+                [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045]
+            }
+            ColorSpace::Rec2020 => [
+                1.0 / 0.45,
+                1.0 / 1.0993,
+                0.0993 / 1.0993,
+                1.0 / 4.5,
+                0.081243,
+            ],
+            ColorSpace::AdobeRgb => [563.0 / 256.0, 1.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// Decodes a normalized, non-linear channel value to linear light.
+    pub(crate) fn decode(self, value: f32) -> f32 {
+        match self {
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => srgb_to_linear(value),
+            _ => {
+                let [g, a, b, c, d] = self.transfer_parameters();
+                if value >= d {
+                    f32::powf(a * value + b, g)
+                } else {
+                    c * value
+                }
+            }
+        }
+    }
+
+    /// Encodes a linear light channel value with the transfer function of the color space.
+    pub(crate) fn encode(self, value: f32) -> f32 {
+        match self {
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => linear_to_srgb(value),
+            _ => {
+                let [g, a, b, c, d] = self.transfer_parameters();
+                let value = value.max(0.0);
+                if value >= c * d {
+                    (f32::powf(value, 1.0 / g) - b) / a
+                } else {
+                    value / c
+                }
+            }
+        }
+    }
+
+    /// Returns the matrix converting linear RGB in this color space to CIE XYZ, relative to D65.
+    pub(crate) fn rgb_to_xyz(self) -> Matrix3 {
+        let xyz = |[x, y]: [f64; 2]| [x / y, 1.0, (1.0 - x - y) / y];
+        let [r, g, b] = self.primaries().map(xyz);
+        let primaries = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+        let scale = multiply_vector(
+            &invert_matrix3(&primaries).expect("primaries are linearly independent"),
+            xyz(D65),
+        );
+        primaries.map(|row| [row[0] * scale[0], row[1] * scale[1], row[2] * scale[2]])
+    }
+
+    /// Returns the contribution of the linear red, green and blue channels to luminance (CIE Y).
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::ColorSpace;
+    /// let [r, g, b] = ColorSpace::Rec2020.luminance();
+    /// assert!((r - 0.2627).abs() < 1e-3 && (g - 0.6780).abs() < 1e-3 && (b - 0.0593).abs() < 1e-3);
+    /// ```
+    ///
+    pub fn luminance(self) -> [f32; 3] {
+        self.rgb_to_xyz()[1].map(|c| c as f32)
+    }
+
+    // sRGB keeps the coefficients of the filter effects specification, other spaces derive them
+    // from their primaries.
+    pub(crate) fn saturate_matrix(self, amount: f32) -> [f32; 20] {
+        match self {
+            ColorSpace::Srgb => saturate_matrix(amount),
+            _ => luminance_saturate_matrix(amount, self.luminance()),
+        }
+    }
+
+    pub(crate) fn grayscale_matrix(self, amount: f32) -> [f32; 20] {
+        match self {
+            ColorSpace::Srgb => grayscale_matrix(amount),
+            _ => luminance_grayscale_matrix(amount, self.luminance()),
+        }
+    }
+
+    pub(crate) fn hue_rotate_matrix(self, angle: f32) -> [f32; 20] {
+        match self {
+            ColorSpace::Srgb => hue_rotate_matrix(angle),
+            _ => luminance_hue_rotate_matrix(angle, self.luminance()),
+        }
+    }
+
+    /// Luminance coefficients used by `color_transform_luminance_to_alpha`.
+    pub(crate) fn luminance_to_alpha_coefficients(self) -> [f32; 3] {
+        match self {
+            ColorSpace::Srgb => [0.2125, 0.7154, 0.0721],
+            _ => self.luminance(),
+        }
+    }
+}
+
+/// Returns the matrix converting linear RGB from one color space to another.
+pub(crate) fn conversion_matrix(from: ColorSpace, to: ColorSpace) -> [[f32; 3]; 3] {
+    let xyz_to_rgb = invert_matrix3(&to.rgb_to_xyz()).expect("primaries are linearly independent");
+    multiply_matrix3(&xyz_to_rgb, &from.rgb_to_xyz()).map(|row| row.map(|c| c as f32))
+}
+
+pub(crate) fn multiply_matrix3(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut result = [[0.0; 3]; 3];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+pub(crate) fn multiply_vector(m: &Matrix3, v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+pub(crate) fn invert_matrix3(m: &Matrix3) -> Option<Matrix3> {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let det = m[0][0] * cofactor(1, 2, 1, 2) - m[0][1] * cofactor(1, 2, 0, 2)
+        + m[0][2] * cofactor(1, 2, 0, 1);
+    if det.abs() <= f64::EPSILON {
+        return None;
+    }
+    Some([
+        [
+            cofactor(1, 2, 1, 2) / det,
+            -cofactor(0, 2, 1, 2) / det,
+            cofactor(0, 1, 1, 2) / det,
+        ],
+        [
+            -cofactor(1, 2, 0, 2) / det,
+            cofactor(0, 2, 0, 2) / det,
+            -cofactor(0, 1, 0, 2) / det,
+        ],
+        [
+            cofactor(1, 2, 0, 1) / det,
+            -cofactor(0, 2, 0, 1) / det,
+            cofactor(0, 1, 0, 1) / det,
+        ],
+    ])
+}
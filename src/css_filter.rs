@@ -0,0 +1,186 @@
+use crate::{Surface, SurfaceView, error::CssFilterError};
+use alloc::{string::ToString, vec::Vec};
+use core::str::FromStr;
+
+/// A single function of a CSS `filter` property.
+///
+/// Amounts are normalized, so percentages are already divided by 100 and angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CssFunction {
+    /// `blur(<length>)`, the standard deviation in pixels
+    Blur(f32),
+    /// `brightness(<number-percentage>)`
+    Brightness(f32),
+    /// `contrast(<number-percentage>)`
+    Contrast(f32),
+    /// `grayscale(<number-percentage>)`, clamped to 1
+    Grayscale(f32),
+    /// `hue-rotate(<angle>)`, in degrees
+    HueRotate(f32),
+    /// `invert(<number-percentage>)`, clamped to 1
+    Invert(f32),
+    /// `opacity(<number-percentage>)`, clamped to 1
+    Opacity(f32),
+    /// `saturate(<number-percentage>)`
+    Saturate(f32),
+    /// `sepia(<number-percentage>)`, clamped to 1
+    Sepia(f32),
+}
+
+impl CssFunction {
+    /// Applies the function from `input` into `output`.
+    pub fn apply<'i>(self, input: impl Into<SurfaceView<'i>>, output: &mut Surface) {
+        match self {
+            CssFunction::Blur(radius) => Surface::gaussian_blur(input, output, radius, radius),
+            CssFunction::Brightness(amount) => {
+                Surface::color_transform_brightness(input, output, amount)
+            }
+            CssFunction::Contrast(amount) => {
+                Surface::color_transform_contrast(input, output, amount)
+            }
+            CssFunction::Grayscale(amount) => {
+                Surface::color_transform_grayscale(input, output, amount)
+            }
+            CssFunction::HueRotate(angle) => {
+                Surface::color_transform_hue_rotate(input, output, angle)
+            }
+            CssFunction::Invert(amount) => Surface::color_transform_invert(input, output, amount),
+            CssFunction::Opacity(amount) => Surface::color_transform_opacity(input, output, amount),
+            CssFunction::Saturate(amount) => {
+                Surface::color_transform_saturate(input, output, amount)
+            }
+            CssFunction::Sepia(amount) => Surface::color_transform_sepia(input, output, amount),
+        }
+    }
+
+    fn parse(name: &str, argument: &str) -> Result<Self, CssFilterError> {
+        let invalid = || CssFilterError::InvalidArgument(name.to_string());
+        let amount = |clamp: bool| -> Result<f32, CssFilterError> {
+            let value = match argument.strip_suffix('%') {
+                _ if argument.is_empty() => 1.0,
+                Some(percent) => number(percent).ok_or_else(invalid)? / 100.0,
+                None => number(argument).ok_or_else(invalid)?,
+            };
+            match value < 0.0 {
+                true => Err(invalid()),
+                false if clamp => Ok(value.min(1.0)),
+                false => Ok(value),
+            }
+        };
+        Ok(match name {
+            "blur" => {
+                let radius = match argument.strip_suffix("px") {
+                    _ if argument.is_empty() => 0.0,
+                    Some(length) => number(length).ok_or_else(invalid)?,
+                    None => number(argument).filter(|&n| n == 0.0).ok_or_else(invalid)?,
+                };
+                if radius < 0.0 {
+                    return Err(invalid());
+                }
+                CssFunction::Blur(radius)
+            }
+            "brightness" => CssFunction::Brightness(amount(false)?),
+            "contrast" => CssFunction::Contrast(amount(false)?),
+            "grayscale" => CssFunction::Grayscale(amount(true)?),
+            "hue-rotate" => {
+                let angle = [
+                    ("deg", 1.0),
+                    ("grad", 0.9),
+                    ("rad", 180.0 / PI),
+                    ("turn", 360.0),
+                ]
+                .into_iter()
+                .find_map(|(unit, scale)| Some(number(argument.strip_suffix(unit)?)? * scale));
+                let angle = match angle {
+                    _ if argument.is_empty() => 0.0,
+                    Some(angle) => angle,
+                    None => number(argument).filter(|&n| n == 0.0).ok_or_else(invalid)?,
+                };
+                CssFunction::HueRotate(angle)
+            }
+            "invert" => CssFunction::Invert(amount(true)?),
+            "opacity" => CssFunction::Opacity(amount(true)?),
+            "saturate" => CssFunction::Saturate(amount(false)?),
+            "sepia" => CssFunction::Sepia(amount(true)?),
+            "drop-shadow" | "url" => return Err(CssFilterError::Unsupported(name.to_string())),
+            _ => return Err(CssFilterError::UnknownFunction(name.to_string())),
+        })
+    }
+}
+
+const PI: f32 = core::f32::consts::PI;
+
+/// Parses a finite CSS number, rejecting the `inf` and `NaN` spellings Rust accepts.
+fn number(text: &str) -> Option<f32> {
+    text.parse::<f32>().ok().filter(|n| n.is_finite())
+}
+
+/// A parsed CSS `filter` property, a list of [CssFunction]s applied in order.
+///
+/// Runs the same [Surface] filters as the rest of the crate, so a filter string applied on the
+/// server matches the one previewed in the browser through the `wasm` bindings.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{ColorChannel, CssFilter, CssFunction, SurfaceBuf};
+///
+/// let filter = CssFilter::parse("contrast(97%) hue-rotate(330deg) saturate(1.11) blur(1.5px)")?;
+/// assert_eq!(filter.functions()[1], CssFunction::HueRotate(330.0));
+///
+/// let mut image = SurfaceBuf::new(64, 64, ColorChannel::RGBA32);
+/// image.fill([255, 128, 0, 255]);
+/// filter.apply(&mut image.as_surface());
+/// # Ok::<(), plutofilter_rs::CssFilterError>(())
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CssFilter {
+    functions: Vec<CssFunction>,
+}
+
+impl CssFilter {
+    /// Parses the value of a CSS `filter` property, such as `"blur(2px) grayscale(50%)"`.
+    ///
+    ///`none` parses to an empty filter. `drop-shadow()` and `url()` are not supported.
+    pub fn parse(text: &str) -> Result<Self, CssFilterError> {
+        let mut rest = text.trim();
+        let mut functions = Vec::new();
+        if rest.eq_ignore_ascii_case("none") {
+            return Ok(Self { functions });
+        }
+        while !rest.is_empty() {
+            let (name, tail) = rest.split_once('(').ok_or(CssFilterError::Syntax)?;
+            let (argument, tail) = tail.split_once(')').ok_or(CssFilterError::Syntax)?;
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(CssFilterError::Syntax);
+            }
+            let name = name.to_ascii_lowercase();
+            functions.push(CssFunction::parse(
+                &name,
+                &argument.trim().to_ascii_lowercase(),
+            )?);
+            rest = tail.trim_start();
+        }
+        Ok(Self { functions })
+    }
+
+    /// Returns the functions in the order they are applied.
+    pub fn functions(&self) -> &[CssFunction] {
+        &self.functions
+    }
+
+    /// Applies every function in order, writing the result back into `surface`.
+    pub fn apply(&self, surface: &mut Surface) {
+        for function in &self.functions {
+            Surface::in_place(surface, |input, output| function.apply(input, output));
+        }
+    }
+}
+
+impl FromStr for CssFilter {
+    type Err = CssFilterError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
@@ -1,4 +1,5 @@
-use std::fmt::Display;
+use alloc::string::String;
+use core::fmt::Display;
 
 #[derive(Debug)]
 pub enum SurfaceError {
@@ -7,19 +8,95 @@ pub enum SurfaceError {
     InvalidStride,
     InvalidWidth,
     InvalidHeight,
+    MisalignedPixels,
+    UnalignedStride,
+    BufferTooShort { required: usize, found: usize },
+    TooManyPixels,
 }
 
-impl std::error::Error for SurfaceError {}
+impl core::error::Error for SurfaceError {}
 
 impl Display for SurfaceError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         use SurfaceError::*;
         match self {
             StrideLessThanWidth => write!(f, "Stride should be greater than the width"),
             InvalidPixelLength => write!(f, "Number of pixels should be more than stride * height"),
             InvalidHeight => write!(f, "Invalid Height"),
-            InvalidStride => write!(f, "Invalid width"),
-            InvalidWidth => write!(f, "Invalid height"),
+            InvalidStride => write!(f, "Invalid stride"),
+            InvalidWidth => write!(f, "Invalid width"),
+            MisalignedPixels => write!(f, "Pixel buffer is not aligned for u32 pixels"),
+            UnalignedStride => write!(f, "Stride in bytes should be a multiple of 4"),
+            BufferTooShort { required, found } => {
+                write!(f, "Expected at least {required} bytes, found {found}")
+            }
+            TooManyPixels => write!(f, "Surfaces hold at most u32::MAX pixels"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LutError {
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    MissingSize,
+    InvalidSize,
+    InvalidDomain,
+    InvalidLine(usize),
+    Unsupported1d,
+    EntryCount {
+        expected: usize,
+        found: usize,
+    },
+    InvalidHaldSize,
+}
+
+impl core::error::Error for LutError {}
+
+impl Display for LutError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use LutError::*;
+        match self {
+            #[cfg(feature = "std")]
+            Io(err) => write!(f, "Unable to read LUT: {err}"),
+            MissingSize => write!(f, "LUT_3D_SIZE is missing"),
+            InvalidSize => write!(f, "LUT size should be between 2 and 256"),
+            InvalidDomain => write!(f, "LUT domain maximum should be greater than the minimum"),
+            InvalidLine(line) => write!(f, "Invalid LUT entry on line {line}"),
+            Unsupported1d => write!(f, "1D LUTs are not supported"),
+            EntryCount { expected, found } => {
+                write!(f, "Expected {expected} LUT entries, found {found}")
+            }
+            InvalidHaldSize => write!(f, "Hald CLUT image should be a square of level^3 pixels"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for LutError {
+    fn from(err: std::io::Error) -> Self {
+        LutError::Io(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum CssFilterError {
+    Syntax,
+    UnknownFunction(String),
+    Unsupported(String),
+    InvalidArgument(String),
+}
+
+impl core::error::Error for CssFilterError {}
+
+impl Display for CssFilterError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use CssFilterError::*;
+        match self {
+            Syntax => write!(f, "Filter should be a list of functions such as blur(2px)"),
+            UnknownFunction(name) => write!(f, "Unknown filter function {name}()"),
+            Unsupported(name) => write!(f, "Filter function {name}() is not supported"),
+            InvalidArgument(name) => write!(f, "Invalid argument for {name}()"),
         }
     }
 }
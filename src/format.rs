@@ -0,0 +1,548 @@
+use crate::{Surface, SurfaceView, error::SurfaceError, utils::*};
+use core::marker::PhantomData;
+
+/// Describes how a pixel is laid out in memory.
+///
+/// Filters are monomorphized over the format, so loading and storing a pixel compiles down to
+/// the byte shuffles of that format without a per-pixel `match`.
+///
+/// Pixels are loaded as premultiplied `[r, g, b, a]` channels in the range [0, 255].
+/// Formats without an alpha channel load as opaque and drop alpha on store, and gray formats
+/// store the luminance of the color channels.
+pub trait PixelFormat {
+    /// The number of bytes used by one pixel.
+    const BYTES: usize;
+
+    /// Reads one pixel from the first [PixelFormat::BYTES] bytes of `bytes`.
+    fn load(bytes: &[u8]) -> [u32; 4];
+
+    /// Writes one pixel to the first [PixelFormat::BYTES] bytes of `bytes`.
+    fn store(bytes: &mut [u8], rgba: [u32; 4]);
+}
+
+/// 32-bit pixels with bytes ordered red, green, blue, alpha
+#[derive(Debug, Clone, Copy)]
+pub struct Rgba8;
+
+/// 32-bit pixels with bytes ordered blue, green, red, alpha
+#[derive(Debug, Clone, Copy)]
+pub struct Bgra8;
+
+/// 32-bit pixels with bytes ordered alpha, red, green, blue
+#[derive(Debug, Clone, Copy)]
+pub struct Argb8;
+
+/// 24-bit pixels with bytes ordered red, green, blue and no alpha
+#[derive(Debug, Clone, Copy)]
+pub struct Rgb8;
+
+/// 8-bit gray pixels with no alpha
+#[derive(Debug, Clone, Copy)]
+pub struct Gray8;
+
+/// 16-bit pixels with a gray byte followed by an alpha byte
+#[derive(Debug, Clone, Copy)]
+pub struct GrayAlpha8;
+
+/// 16-bit little-endian pixels with 5 bits red, 6 bits green, 5 bits blue and no alpha
+#[derive(Debug, Clone, Copy)]
+pub struct Rgb565;
+
+#[inline(always)]
+fn gray(r: u32, g: u32, b: u32) -> u32 {
+    (r * 54 + g * 183 + b * 19) >> 8
+}
+
+impl PixelFormat for Rgba8 {
+    const BYTES: usize = 4;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        [bytes[0], bytes[1], bytes[2], bytes[3]].map(u32::from)
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, a]: [u32; 4]) {
+        bytes[..4].copy_from_slice(&[r as u8, g as u8, b as u8, a as u8]);
+    }
+}
+
+impl PixelFormat for Bgra8 {
+    const BYTES: usize = 4;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        [bytes[2], bytes[1], bytes[0], bytes[3]].map(u32::from)
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, a]: [u32; 4]) {
+        bytes[..4].copy_from_slice(&[b as u8, g as u8, r as u8, a as u8]);
+    }
+}
+
+impl PixelFormat for Argb8 {
+    const BYTES: usize = 4;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        [bytes[1], bytes[2], bytes[3], bytes[0]].map(u32::from)
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, a]: [u32; 4]) {
+        bytes[..4].copy_from_slice(&[a as u8, r as u8, g as u8, b as u8]);
+    }
+}
+
+impl PixelFormat for Rgb8 {
+    const BYTES: usize = 3;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        [bytes[0], bytes[1], bytes[2], 255].map(u32::from)
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, _]: [u32; 4]) {
+        bytes[..3].copy_from_slice(&[r as u8, g as u8, b as u8]);
+    }
+}
+
+impl PixelFormat for Gray8 {
+    const BYTES: usize = 1;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        let v = bytes[0] as u32;
+        [v, v, v, 255]
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, _]: [u32; 4]) {
+        bytes[0] = gray(r, g, b) as u8;
+    }
+}
+
+impl PixelFormat for GrayAlpha8 {
+    const BYTES: usize = 2;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        let (v, a) = (bytes[0] as u32, bytes[1] as u32);
+        [v, v, v, a]
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, a]: [u32; 4]) {
+        bytes[..2].copy_from_slice(&[gray(r, g, b) as u8, a as u8]);
+    }
+}
+
+impl PixelFormat for Rgb565 {
+    const BYTES: usize = 2;
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> [u32; 4] {
+        let pixel = u16::from_le_bytes([bytes[0], bytes[1]]) as u32;
+        let (r, g, b) = ((pixel >> 11) & 0x1F, (pixel >> 5) & 0x3F, pixel & 0x1F);
+        [
+            (r << 3) | (r >> 2),
+            (g << 2) | (g >> 4),
+            (b << 3) | (b >> 2),
+            255,
+        ]
+    }
+
+    #[inline(always)]
+    fn store(bytes: &mut [u8], [r, g, b, _]: [u32; 4]) {
+        let (r, g, b) = (
+            (r * 31 + 127) / 255,
+            (g * 63 + 127) / 255,
+            (b * 31 + 127) / 255,
+        );
+        let pixel = ((r << 11) | (g << 5) | b) as u16;
+        bytes[..2].copy_from_slice(&pixel.to_le_bytes());
+    }
+}
+
+/// Random read access to the premultiplied channels of a surface, independent of its storage.
+pub(crate) trait PixelSource {
+    fn width(&self) -> u32;
+
+    fn height(&self) -> u32;
+
+    fn load(&self, x: u32, y: u32) -> [u32; 4];
+}
+
+/// A [PixelSource] that can also be written to.
+pub(crate) trait PixelAccess: PixelSource {
+    fn store(&mut self, x: u32, y: u32, rgba: [u32; 4]);
+}
+
+/// A [Surface] whose [crate::ColorChannel] has been resolved to the pixel format `F`, so loops
+/// over it are monomorphized instead of matching on the channel for every pixel.
+pub(crate) struct ChannelSurface<'s, 'a, F: PixelFormat> {
+    surface: &'s mut Surface<'a>,
+    format: PhantomData<F>,
+}
+
+impl<'s, 'a, F: PixelFormat> ChannelSurface<'s, 'a, F> {
+    pub(crate) fn new(surface: &'s mut Surface<'a>) -> Self {
+        Self {
+            surface,
+            format: PhantomData,
+        }
+    }
+}
+
+impl<F: PixelFormat> PixelSource for ChannelSurface<'_, '_, F> {
+    #[inline(always)]
+    fn width(&self) -> u32 {
+        self.surface.width
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u32 {
+        self.surface.height
+    }
+
+    #[inline(always)]
+    fn load(&self, x: u32, y: u32) -> [u32; 4] {
+        let pixel = self.surface.pixels[(y * self.surface.stride + x) as usize];
+        F::load(&pixel.to_le_bytes())
+    }
+}
+
+impl<F: PixelFormat> PixelAccess for ChannelSurface<'_, '_, F> {
+    #[inline(always)]
+    fn store(&mut self, x: u32, y: u32, rgba: [u32; 4]) {
+        let mut bytes = [0; 4];
+        F::store(&mut bytes, rgba);
+        *get_pixel_mut(self.surface, x, y) = u32::from_le_bytes(bytes);
+    }
+}
+
+/// A [SurfaceView] whose [crate::ColorChannel] has been resolved to the pixel format `F`.
+pub(crate) struct ChannelView<'v, F: PixelFormat> {
+    view: SurfaceView<'v>,
+    format: PhantomData<F>,
+}
+
+impl<'v, F: PixelFormat> ChannelView<'v, F> {
+    pub(crate) fn new(view: &SurfaceView<'v>) -> Self {
+        Self {
+            view: *view,
+            format: PhantomData,
+        }
+    }
+}
+
+impl<F: PixelFormat> PixelSource for ChannelView<'_, F> {
+    #[inline(always)]
+    fn width(&self) -> u32 {
+        self.view.width
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u32 {
+        self.view.height
+    }
+
+    #[inline(always)]
+    fn load(&self, x: u32, y: u32) -> [u32; 4] {
+        F::load(&get_pixel(&self.view, x, y).to_le_bytes())
+    }
+}
+
+/// Represents a 2D image surface over raw bytes in the pixel format `F`.
+///
+/// Unlike [crate::Surface], the buffer is addressed in bytes, so any packed format can be wrapped
+/// in place, such as a framebuffer, a camera frame or a GPU readback.
+/// The pixel data is stored in row-major order. Each row begins at a multiple of `stride` bytes.
+#[derive(Debug)]
+pub struct FormatSurface<'a, F: PixelFormat> {
+    /// The pixel bytes.
+    ///
+    /// Must hold at least `stride * (height - 1) + width * F::BYTES` bytes.
+    pub(crate) bytes: &'a mut [u8],
+
+    /// The width of the surface in pixels.
+    pub(crate) width: u32,
+
+    /// The height of the surface in pixels.
+    pub(crate) height: u32,
+
+    /// The number of bytes per row.
+    ///
+    /// Must be greater than or equal to `width * F::BYTES`.
+    pub(crate) stride: usize,
+
+    pub(crate) format: PhantomData<F>,
+}
+
+impl<F: PixelFormat> PixelSource for FormatSurface<'_, F> {
+    #[inline(always)]
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline(always)]
+    fn load(&self, x: u32, y: u32) -> [u32; 4] {
+        let offset = y as usize * self.stride + x as usize * F::BYTES;
+        F::load(&self.bytes[offset..offset + F::BYTES])
+    }
+}
+
+impl<F: PixelFormat> PixelAccess for FormatSurface<'_, F> {
+    #[inline(always)]
+    fn store(&mut self, x: u32, y: u32, rgba: [u32; 4]) {
+        let offset = y as usize * self.stride + x as usize * F::BYTES;
+        F::store(&mut self.bytes[offset..offset + F::BYTES], rgba);
+    }
+}
+
+impl<'a, F: PixelFormat> FormatSurface<'a, F> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Creates a surface from a raw byte buffer.
+    ///
+    /// # Arguments
+    /// * `bytes`  - The pixel bytes in the format `F`, premultiplied if the format has alpha.
+    /// * `width`  - The width of the surface in pixels.
+    /// * `height` - The height of the surface in pixels.
+    /// * `stride` - The number of bytes per row (must be greater than or equal to `width * F::BYTES`).
+    /// # Returns [FormatSurface] representing the given pixel buffer.
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::{FormatSurface, Rgb565};
+    /// let mut framebuffer = vec![0u8; 320 * 2 * 240];
+    /// let surface = FormatSurface::<Rgb565>::make(&mut framebuffer, 320, 240, 640).expect("ERROR: Failed to make surface struct.");
+    /// ```
+    ///
+    pub fn make(
+        bytes: &'a mut [u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<Self, SurfaceError> {
+        let row = width as usize * F::BYTES;
+        if stride < row {
+            Err(SurfaceError::StrideLessThanWidth)
+        } else if height > 0 && bytes.len() < stride * (height as usize - 1) + row {
+            Err(SurfaceError::InvalidPixelLength)
+        } else {
+            Ok(Self {
+                bytes,
+                width,
+                height,
+                stride,
+                format: PhantomData,
+            })
+        }
+    }
+
+    /// Copies the input surface into an output surface of another format.
+    ///
+    /// # Arguments
+    /// * `input`  - The input surface.
+    /// * `output` - The output surface.
+    ///
+    pub fn convert<G: PixelFormat>(input: &Self, output: &mut FormatSurface<G>) {
+        let input = overlap_format_surface(input, output);
+        for y in 0..output.height {
+            for x in 0..output.width {
+                output.store(x, y, input.load(x, y));
+            }
+        }
+    }
+
+    /// Applies a 5x4 color transformation matrix to each pixel in the input surface.
+    ///
+    /// See [crate::Surface::color_transform]. The output may use a different pixel format.
+    pub fn color_transform<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        matrix: impl Into<crate::ColorMatrix>,
+    ) {
+        let input = overlap_format_surface(input, output);
+        color_transform_pixels(&input, output, &matrix.into().to_array());
+    }
+
+    /// Adjusts the opacity of each pixel by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_opacity].
+    pub fn color_transform_opacity<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, opacity_matrix(amount));
+    }
+
+    /// Adjusts the brightness of each pixel by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_brightness].
+    pub fn color_transform_brightness<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, brightness_matrix(amount));
+    }
+
+    /// Inverts the color channels of each pixel by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_invert].
+    pub fn color_transform_invert<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, invert_matrix(amount));
+    }
+
+    /// Adjusts the contrast of each pixel by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_contrast].
+    pub fn color_transform_contrast<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, contrast_matrix(amount));
+    }
+
+    /// Adjusts the saturation of each pixel by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_saturate].
+    pub fn color_transform_saturate<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, saturate_matrix(amount));
+    }
+
+    /// Converts each pixel toward grayscale by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_grayscale].
+    pub fn color_transform_grayscale<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, grayscale_matrix(amount));
+    }
+
+    /// Applies a sepia tone to each pixel by a uniform amount.
+    ///
+    /// See [crate::Surface::color_transform_sepia].
+    pub fn color_transform_sepia<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, sepia_matrix(amount));
+    }
+
+    /// Rotates the hue of each pixel by a given angle.
+    ///
+    /// See [crate::Surface::color_transform_hue_rotate].
+    pub fn color_transform_hue_rotate<G: PixelFormat>(
+        input: &Self,
+        output: &mut FormatSurface<G>,
+        angle: f32,
+    ) {
+        Self::color_transform(input, output, hue_rotate_matrix(angle));
+    }
+
+    /// Applies a Gaussian blur to the input surface.
+    ///
+    /// See [crate::Surface::gaussian_blur].
+    pub fn gaussian_blur(
+        input: &Self,
+        output: &mut Self,
+        std_deviation_x: f32,
+        std_deviation_y: f32,
+    ) {
+        if std_deviation_x < 0.0 || std_deviation_y < 0.0 {
+            panic!("standard deviation can not be less than zero.")
+        }
+        let clipped = overlap_format_surface(input, output);
+        let (kernel_width, kernel_height) = (
+            calc_kernel_size(std_deviation_x).min(MAX_KERNEL_SIZE),
+            calc_kernel_size(std_deviation_y).min(MAX_KERNEL_SIZE),
+        );
+        if kernel_width == 0 && kernel_height == 0 {
+            Self::convert(input, output);
+            return;
+        }
+
+        gaussian_blur_pixels(&clipped, output, kernel_width, kernel_height);
+    }
+
+    /// Applies a Gaussian blur along the X axis only.
+    ///
+    /// See [crate::Surface::blur_horizontal].
+    pub fn blur_horizontal(input: &Self, output: &mut Self, std_deviation: f32) {
+        Self::gaussian_blur(input, output, std_deviation, 0.0);
+    }
+
+    /// Applies a Gaussian blur along the Y axis only.
+    ///
+    /// See [crate::Surface::blur_vertical].
+    pub fn blur_vertical(input: &Self, output: &mut Self, std_deviation: f32) {
+        Self::gaussian_blur(input, output, 0.0, std_deviation);
+    }
+}
+
+/// The top-left `width * height` pixels of a [PixelSource].
+struct Clipped<'s, S: PixelSource> {
+    source: &'s S,
+    width: u32,
+    height: u32,
+}
+
+impl<S: PixelSource> PixelSource for Clipped<'_, S> {
+    #[inline(always)]
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline(always)]
+    fn load(&self, x: u32, y: u32) -> [u32; 4] {
+        self.source.load(x, y)
+    }
+}
+
+/// Clips `output` and the returned view of `input` to the size they have in common.
+fn overlap_format_surface<'s, F: PixelFormat, G: PixelFormat>(
+    input: &'s FormatSurface<F>,
+    output: &mut FormatSurface<G>,
+) -> Clipped<'s, FormatSurface<'s, F>> {
+    output.width = output.width.min(input.width);
+    output.height = output.height.min(input.height);
+    Clipped {
+        source: input,
+        width: output.width,
+        height: output.height,
+    }
+}
@@ -0,0 +1,206 @@
+//! Minimal ICC profile support: identifying the color space of a profile and writing matrix/TRC
+//! profiles for the supported color spaces.
+
+use crate::{
+    ColorSpace,
+    color_space::{Matrix3, invert_matrix3, multiply_matrix3, multiply_vector},
+};
+
+const HEADER_SIZE: usize = 128;
+
+/// CIE XYZ of the D65 white point and of the D50 profile connection space illuminant.
+const D65_XYZ: [f64; 3] = [0.3127 / 0.3290, 1.0, (1.0 - 0.3127 - 0.3290) / 0.3290];
+const D50_XYZ: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+const BRADFORD: Matrix3 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+impl ColorSpace {
+    fn profile_description(self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "sRGB",
+            ColorSpace::DisplayP3 => "Display P3",
+            ColorSpace::Rec2020 => "Rec. 2020",
+            ColorSpace::AdobeRgb => "Adobe RGB (1998)",
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Returns the text of the profile description tag, for both `desc` (v2) and `mluc` (v4) types.
+fn description(profile: &[u8]) -> Option<String> {
+    let count = read_u32(profile, HEADER_SIZE)? as usize;
+    let (offset, size) = (0..count).find_map(|i| {
+        let entry = HEADER_SIZE + 4 + i * 12;
+        if profile.get(entry..entry + 4)? != b"desc" {
+            return None;
+        }
+        Some((
+            read_u32(profile, entry + 4)? as usize,
+            read_u32(profile, entry + 8)? as usize,
+        ))
+    })?;
+    let tag = profile.get(offset..offset.checked_add(size)?)?;
+
+    match tag.get(0..4)? {
+        b"desc" => {
+            let length = read_u32(tag, 8)? as usize;
+            let text = tag.get(12..12 + length)?;
+            Some(
+                String::from_utf8_lossy(text)
+                    .trim_end_matches('\0')
+                    .to_owned(),
+            )
+        }
+        b"mluc" => {
+            let length = read_u32(tag, 20)? as usize;
+            let start = read_u32(tag, 24)? as usize;
+            let units: Vec<u16> = tag
+                .get(start..start + length)?
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        _ => None,
+    }
+}
+
+/// Identifies the color space of an ICC profile from its description.
+///
+/// Returns `None` for profiles that do not describe one of the supported RGB spaces.
+pub(crate) fn color_space_from_icc(profile: &[u8]) -> Option<ColorSpace> {
+    if profile.get(16..20)? != b"RGB " {
+        return None;
+    }
+    let description = description(profile)?.to_lowercase();
+    if description.contains("p3") {
+        Some(ColorSpace::DisplayP3)
+    } else if description.contains("2020") {
+        Some(ColorSpace::Rec2020)
+    } else if description.contains("adobe") {
+        Some(ColorSpace::AdobeRgb)
+    } else if description.contains("srgb") {
+        Some(ColorSpace::Srgb)
+    } else {
+        None
+    }
+}
+
+fn s15_fixed16(value: f64) -> [u8; 4] {
+    ((value * 65536.0).round() as i32).to_be_bytes()
+}
+
+fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    xyz.iter().for_each(|&v| tag.extend(s15_fixed16(v)));
+    tag
+}
+
+fn mluc_tag(text: &str) -> Vec<u8> {
+    let units: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    let mut tag = b"mluc\0\0\0\0".to_vec();
+    tag.extend(1u32.to_be_bytes());
+    tag.extend(12u32.to_be_bytes());
+    tag.extend(b"enUS");
+    tag.extend((units.len() as u32).to_be_bytes());
+    tag.extend(28u32.to_be_bytes());
+    tag.extend(units);
+    tag
+}
+
+fn parametric_curve_tag(space: ColorSpace) -> Vec<u8> {
+    let [g, a, b, c, d] = space.transfer_parameters().map(f64::from);
+    let mut tag = b"para\0\0\0\0".to_vec();
+    if let ColorSpace::AdobeRgb = space {
+        tag.extend([0, 0, 0, 0]);
+        tag.extend(s15_fixed16(g));
+    } else {
+        tag.extend([0, 3, 0, 0]);
+        [g, a, b, c, d]
+            .iter()
+            .for_each(|&v| tag.extend(s15_fixed16(v)));
+    }
+    tag
+}
+
+/// Returns the Bradford chromatic adaptation matrix from D65 to D50.
+fn d65_to_d50() -> Matrix3 {
+    let source = multiply_vector(&BRADFORD, D65_XYZ);
+    let target = multiply_vector(&BRADFORD, D50_XYZ);
+    let scale = [
+        [target[0] / source[0], 0.0, 0.0],
+        [0.0, target[1] / source[1], 0.0],
+        [0.0, 0.0, target[2] / source[2]],
+    ];
+    let inverse = invert_matrix3(&BRADFORD).expect("Bradford matrix is invertible");
+    multiply_matrix3(&inverse, &multiply_matrix3(&scale, &BRADFORD))
+}
+
+/// Builds an ICC v4 display profile describing `space` with its primaries and transfer function.
+pub(crate) fn icc_profile(space: ColorSpace) -> Vec<u8> {
+    let adaptation = d65_to_d50();
+    let colorants = multiply_matrix3(&adaptation, &space.rgb_to_xyz());
+    let column = |i: usize| [colorants[0][i], colorants[1][i], colorants[2][i]];
+    let mut chad = b"sf32\0\0\0\0".to_vec();
+    adaptation
+        .iter()
+        .flatten()
+        .for_each(|&v| chad.extend(s15_fixed16(v)));
+    let curve = parametric_curve_tag(space);
+
+    let tags: [(&[u8; 4], Vec<u8>); 9] = [
+        (b"desc", mluc_tag(space.profile_description())),
+        (b"cprt", mluc_tag("No copyright, use freely")),
+        (b"wtpt", xyz_tag(D50_XYZ)),
+        (b"chad", chad),
+        (b"rXYZ", xyz_tag(column(0))),
+        (b"gXYZ", xyz_tag(column(1))),
+        (b"bXYZ", xyz_tag(column(2))),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+    ];
+
+    let table_size = 4 + (tags.len() + 1) * 12;
+    let mut table = ((tags.len() + 1) as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+    let mut curve_offset = 0;
+    for (signature, tag) in &tags {
+        let offset = HEADER_SIZE + table_size + data.len();
+        if *signature == b"rTRC" {
+            curve_offset = offset;
+        }
+        table.extend(*signature);
+        table.extend((offset as u32).to_be_bytes());
+        table.extend((tag.len() as u32).to_be_bytes());
+        data.extend(tag);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+    // The blue curve shares the data of the red curve.
+    table.extend(b"bTRC");
+    table.extend((curve_offset as u32).to_be_bytes());
+    table.extend((curve.len() as u32).to_be_bytes());
+
+    let size = HEADER_SIZE + table.len() + data.len();
+    let mut profile = Vec::with_capacity(size);
+    profile.extend((size as u32).to_be_bytes());
+    profile.extend([0; 4]);
+    profile.extend([4, 0x30, 0, 0]);
+    profile.extend(b"mntrRGB XYZ ");
+    profile.extend([0x07, 0xE8, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0]);
+    profile.extend(b"acsp");
+    profile.resize(64, 0);
+    profile.extend([0; 4]);
+    D50_XYZ.iter().for_each(|&v| profile.extend(s15_fixed16(v)));
+    profile.resize(HEADER_SIZE, 0);
+    profile.extend(table);
+    profile.extend(data);
+    profile
+}
@@ -1,31 +1,177 @@
 //! PlutoFilter implementation
+#![cfg_attr(not(feature = "std"), no_std)]
 
-// TODO: input output issue ?
-// TODO: inplace functions are incorrect. Maybe push inplace code to surface level??
+extern crate alloc;
 
+mod adjust;
+mod bilateral;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod color_matrix;
+mod color_space;
+mod css_filter;
 mod error;
+mod format;
+#[cfg(feature = "image")]
+mod icc;
+mod linear;
+mod lut;
+#[cfg(not(any(feature = "std", test)))]
+mod math;
+#[cfg(feature = "image")]
+mod operation;
+mod rank;
+mod region;
+mod scanline;
 mod surface;
+mod surface_buf;
+mod surface_view;
+mod tiled;
+mod transform;
 mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod wide;
+#[cfg(feature = "image")]
+pub use arena::{FilterInput, ImageEditor};
+pub use color_matrix::ColorMatrix;
+pub use css_filter::{CssFilter, CssFunction};
+pub use error::{CssFilterError, LutError, SurfaceError};
+pub use format::{
+    Argb8, Bgra8, FormatSurface, Gray8, GrayAlpha8, PixelFormat, Rgb8, Rgb565, Rgba8,
+};
+pub use lut::{Lut3d, LutInterpolation};
+pub use region::{Bounds, FilterPrimitive, Rect};
+pub use scanline::{BlendRows, BlurRows, ColorMatrixRows, CompositeRows, FilteredRows, RowFilter};
+#[cfg(feature = "image")]
+pub use surface::ImageSurface;
+pub use surface::{
+    BlendMode, ColorChannel, ColorInterpolation, ColorSpace, CompositeOperator, EdgeMode,
+    HslAdjustment, HueRange, ResampleFilter, Surface,
+};
+pub use surface_buf::SurfaceBuf;
+pub use surface_view::SurfaceView;
 #[cfg(feature = "image")]
-pub use arena::ImageEditor;
-pub use error::SurfaceError;
-pub use surface::{BlendMode, ColorChannel, CompositeOperator, Surface};
+pub use tiled::{PngRowReader, PngRowWriter};
+pub use tiled::{RowReader, RowWriter, SurfaceReader, SurfaceWriter, TileError, TiledPipeline};
+#[cfg(feature = "std")]
 pub use utils::get_resource_path;
+pub use wide::{Surface16, SurfaceF32, WidePixel, WideSurface};
 
 /// `arena` provides API using image crate to expose easy to use methods for surface API
 #[cfg(feature = "image")]
 pub mod arena {
     use std::{
-        panic,
+        fs::File,
+        io::{self, BufRead, BufWriter, Cursor, Seek, Write},
+        mem, panic,
         path::{Path, PathBuf},
     };
 
-    use image::{DynamicImage, ImageBuffer, ImageReader, ImageResult};
+    use image::{
+        DynamicImage, ImageDecoder, ImageEncoder, ImageError, ImageFormat, ImageReader,
+        ImageResult, RgbaImage,
+        codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+        error::{ParameterError, ParameterErrorKind},
+    };
+
+    /// JPEG quality used by [ImageEditor::save_to] and [ImageEditor::save].
+    const DEFAULT_JPEG_QUALITY: u8 = 75;
+
+    use crate::{
+        BlendMode, ColorInterpolation, ColorMatrix, ColorSpace, CompositeOperator, EdgeMode,
+        HslAdjustment, HueRange, Lut3d, LutInterpolation, ResampleFilter,
+        icc::{color_space_from_icc, icc_profile},
+        operation::{Buffers, Layer, Operation, Step, convert_image, optimize},
+    };
+
+    /// The second input of a blend or composite operation, like the `in2` attribute of SVG
+    /// filter primitives.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum FilterInput {
+        /// The image as it was opened
+        SourceGraphic,
+        /// An image stored with [ImageEditor::result]
+        Result(String),
+        /// An image file, converted into the color space of the editor
+        File(PathBuf),
+        /// An encoded image in memory, decoded by `FilterInput::try_from(bytes)` along with its
+        /// embedded ICC profile, and converted into the color space of the editor
+        Decoded(DynamicImage, Option<Vec<u8>>),
+        /// A decoded sRGB image, converted into the color space of the editor
+        Image(DynamicImage),
+    }
 
-    use crate::{BlendMode, CompositeOperator, Surface};
+    impl FilterInput {
+        /// Refers to the image stored with [ImageEditor::result] under `name`.
+        pub fn result(name: impl Into<String>) -> Self {
+            Self::Result(name.into())
+        }
+    }
+
+    impl From<&Path> for FilterInput {
+        fn from(path: &Path) -> Self {
+            Self::File(path.to_owned())
+        }
+    }
+
+    impl From<PathBuf> for FilterInput {
+        fn from(path: PathBuf) -> Self {
+            Self::File(path)
+        }
+    }
+
+    impl From<&PathBuf> for FilterInput {
+        fn from(path: &PathBuf) -> Self {
+            Self::File(path.clone())
+        }
+    }
+
+    impl From<&str> for FilterInput {
+        fn from(path: &str) -> Self {
+            Self::File(path.into())
+        }
+    }
+
+    impl From<String> for FilterInput {
+        fn from(path: String) -> Self {
+            Self::File(path.into())
+        }
+    }
 
-    /// Provide a unified interface for storing input and output and implementing operations on an
-    /// image
+    /// Decodes an encoded image in memory, so bytes that are not a valid image return an error
+    /// instead of failing when the operation runs.
+    impl TryFrom<&[u8]> for FilterInput {
+        type Error = ImageError;
+
+        fn try_from(bytes: &[u8]) -> ImageResult<Self> {
+            let (image, icc_profile) = ImageEditor::decode_bytes(bytes)?;
+            Ok(Self::Decoded(image, icc_profile))
+        }
+    }
+
+    impl TryFrom<Vec<u8>> for FilterInput {
+        type Error = ImageError;
+
+        fn try_from(bytes: Vec<u8>) -> ImageResult<Self> {
+            Self::try_from(bytes.as_slice())
+        }
+    }
+
+    impl From<DynamicImage> for FilterInput {
+        fn from(image: DynamicImage) -> Self {
+            Self::Image(image)
+        }
+    }
+
+    /// Provide a unified interface for opening an image, running a chain of operations on it and
+    /// saving the result.
+    ///
+    ///Every operation reads the current image and its result becomes the current image, so
+    ///operations run in the order they are chained. The original image stays available as
+    ///[FilterInput::SourceGraphic], and [ImageEditor::result] stores the current image under a
+    ///name for later blend and composite operations or for [ImageEditor::input].
+    ///
     /// Example usage:
     /// ```rust
     /// use image::ImageResult;
@@ -37,388 +183,774 @@ pub mod arena {
     ///     let editor = ImageEditor::open(&base_file);
     ///     let output_path = get_resource_path(&["test_output_images", "example"], "test-image.jpg");
     ///     editor
-    ///         .color_transform_contrast_inplace(0.97)
-    ///         .color_transform_hue_rotate_inplace(330.0)
-    ///         .color_transform_saturate_inplace(1.11)
+    ///         .color_transform_contrast(0.97)
+    ///         .color_transform_hue_rotate(330.0)
+    ///         .color_transform_saturate(1.11)
+    ///         .save_to(&output_path)
+    /// }
+    /// ```
+    ///
+    /// Branching, a soft glow that screens a blurred copy over the original:
+    /// ```rust
+    /// use image::ImageResult;
+    /// #[cfg(feature = "image")]
+    /// fn main() -> ImageResult<()> {
+    ///     use plutofilter_rs::{BlendMode, FilterInput, ImageEditor, get_resource_path};
+    ///
+    ///     let base_file = get_resource_path(&["original_images"], "test-image.jpg");
+    ///     let output_path = get_resource_path(&["test_output_images", "example"], "glow.jpg");
+    ///     ImageEditor::open(&base_file)
+    ///         .gaussian_blur(8.0, 8.0)
+    ///         .color_transform_brightness(0.6)
+    ///         .blend(FilterInput::SourceGraphic, BlendMode::Screen)
     ///         .save_to(&output_path)
     /// }
     /// ```
     #[derive(Debug)]
     pub struct ImageEditor {
-        input_image_path: PathBuf,
-        input_image: DynamicImage,
-        output_image: DynamicImage,
+        input_image_path: Option<PathBuf>,
+        buffers: Buffers,
+        color_interpolation: ColorInterpolation,
+        color_space: ColorSpace,
+        icc_profile: Option<Vec<u8>>,
+        lazy: bool,
+        steps: Vec<Step>,
     }
 
     // TODO: Split this impl using typesafe builder pattern
     impl ImageEditor {
         // TODO: change to result type
-        /// Open an image and generate input and output buffers
+        /// Open an image and make it the current image
         pub fn open(input_image_path: impl AsRef<Path>) -> Self {
             let input_image_path = input_image_path.as_ref().to_owned();
-            let input_image = Self::open_image(&input_image_path);
-            let output_image = Self::temp_output_image(input_image.width(), input_image.height());
+            let (input_image, icc_profile) = Self::read_image(&input_image_path);
+            Self::new(Some(input_image_path), input_image, icc_profile)
+        }
+
+        /// Decodes an encoded image, detecting its format from its contents.
+        ///
+        ///The color space is read from the embedded ICC profile, as with [ImageEditor::open].
+        ///[ImageEditor::save] fails for editors created from memory, use
+        ///[ImageEditor::save_to] or [ImageEditor::encode_to] instead.
+        pub fn from_bytes(bytes: &[u8]) -> ImageResult<Self> {
+            let (image, icc_profile) = Self::decode_bytes(bytes)?;
+            Ok(Self::new(None, image, icc_profile))
+        }
+
+        /// Creates an editor from a decoded image, which is assumed to be sRGB.
+        pub fn from_dynamic_image(image: DynamicImage) -> Self {
+            Self::new(None, DynamicImage::ImageRgba8(image.into_rgba8()), None)
+        }
+
+        /// Creates an editor from `width * height` RGBA pixels, which are assumed to be sRGB.
+        pub fn from_rgba8(width: u32, height: u32, pixels: Vec<u8>) -> ImageResult<Self> {
+            let image = RgbaImage::from_vec(width, height, pixels).ok_or_else(|| {
+                ImageError::Parameter(ParameterError::from_kind(
+                    ParameterErrorKind::DimensionMismatch,
+                ))
+            })?;
+            Ok(Self::from_dynamic_image(DynamicImage::ImageRgba8(image)))
+        }
+
+        fn new(
+            input_image_path: Option<PathBuf>,
+            input_image: DynamicImage,
+            icc_profile: Option<Vec<u8>>,
+        ) -> Self {
+            let color_space = icc_profile
+                .as_deref()
+                .and_then(color_space_from_icc)
+                .unwrap_or_default();
             Self {
                 input_image_path,
-                input_image,
-                output_image,
+                buffers: Buffers::new(input_image, color_space),
+                color_interpolation: ColorInterpolation::default(),
+                color_space,
+                icc_profile,
+                lazy: false,
+                steps: Vec::new(),
             }
         }
 
-        fn temp_output_image(width: u32, height: u32) -> DynamicImage {
-            let output_pixels =
-                ImageBuffer::from_vec(width, height, vec![0u8; (4 * width * height) as usize])
-                    .unwrap();
-            DynamicImage::ImageRgba8(output_pixels)
+        /// Records the operations that follow instead of running them, and runs them when the
+        /// image is saved.
+        ///
+        ///Before running, operations that leave the image unchanged such as
+        ///`gaussian_blur(0.0, 0.0)` or `color_transform_hue_rotate(360.0)` are skipped, and
+        ///consecutive color matrices are multiplied into a single pass when the earlier ones keep
+        ///every color in range. Matrices that can push colors out of range, such as
+        ///`color_transform_brightness(2.0)`, run on their own, since separate passes clamp between
+        ///them. Merged matrices only differ from separate passes by 8-bit rounding.
+        pub fn lazy(mut self) -> Self {
+            self.lazy = true;
+            self
+        }
+
+        /// Runs an operation on the current image, or records it in lazy mode.
+        fn push(mut self, operation: Operation) -> Self {
+            let step = Step {
+                operation,
+                color_interpolation: self.color_interpolation,
+                color_space: self.color_space,
+            };
+            if self.lazy {
+                self.steps.push(step);
+            } else {
+                self.buffers.run(step);
+            }
+            self
+        }
+
+        /// Runs the operations recorded in lazy mode.
+        fn run_pending(&mut self) {
+            for step in optimize(mem::take(&mut self.steps)) {
+                self.buffers.run(step);
+            }
         }
 
         // TODO: remove panics
-        fn open_image(image_path: &Path) -> DynamicImage {
+        /// Decodes an image file along with its embedded ICC profile, if any.
+        fn read_image(image_path: &Path) -> (DynamicImage, Option<Vec<u8>>) {
             if !image_path.exists() {
                 panic!("Unable to find image path.");
             }
-            let image_buffer = match ImageReader::open(image_path) {
-                Ok(image) => match image.decode() {
-                    Ok(image) => image,
-                    Err(err) => panic!("Unable to decode image: {err}"),
-                },
+            let reader = match ImageReader::open(image_path) {
+                Ok(reader) => reader,
                 Err(err) => panic!("Unable to open Image: {err}"),
             };
+            match Self::decode(reader) {
+                Ok(decoded) => decoded,
+                Err(err) => panic!("Unable to decode image: {err}"),
+            }
+        }
+
+        /// Decodes an encoded image in memory along with its embedded ICC profile, if any.
+        fn decode_bytes(bytes: &[u8]) -> ImageResult<(DynamicImage, Option<Vec<u8>>)> {
+            Self::decode(ImageReader::new(Cursor::new(bytes)).with_guessed_format()?)
+        }
+
+        fn decode<R: BufRead + Seek>(
+            reader: ImageReader<R>,
+        ) -> ImageResult<(DynamicImage, Option<Vec<u8>>)> {
+            let mut decoder = reader.into_decoder()?;
+            let icc_profile = decoder.icc_profile().ok().flatten();
+            let image_buffer = DynamicImage::from_decoder(decoder)?;
             let rgba8_image_buffer = image_buffer.into_rgba8();
-            DynamicImage::ImageRgba8(rgba8_image_buffer)
+            Ok((DynamicImage::ImageRgba8(rgba8_image_buffer), icc_profile))
+        }
+
+        /// Converts a decoded image into `color_space`, using its ICC profile to identify the
+        /// color space it is encoded in.
+        fn decoded_in(
+            (mut image, icc_profile): (DynamicImage, Option<Vec<u8>>),
+            color_space: ColorSpace,
+        ) -> DynamicImage {
+            let source = icc_profile
+                .as_deref()
+                .and_then(color_space_from_icc)
+                .unwrap_or_default();
+            if source == color_space {
+                return image;
+            }
+            convert_image(&mut image, source, color_space)
         }
 
-        /// Saves output image to provided path
-        pub fn save_to(self, output_path: impl AsRef<Path>) -> ImageResult<()> {
-            let output_path = output_path.as_ref();
-            self.output_image.save(output_path)
+        /// Resolves the second input of a blend or composite operation.
+        fn layer(&self, input: FilterInput) -> Layer {
+            let decoded = match input {
+                FilterInput::SourceGraphic => return Layer::Source,
+                FilterInput::Result(name) => return Layer::Result(name),
+                FilterInput::File(path) => Self::read_image(&path),
+                FilterInput::Decoded(image, icc_profile) => (image, icc_profile),
+                FilterInput::Image(image) => (DynamicImage::ImageRgba8(image.into_rgba8()), None),
+            };
+            Layer::Image(Self::decoded_in(decoded, self.color_space))
         }
 
-        /// saves the output in the input image path
-        pub fn save(self) -> ImageResult<()> {
-            self.output_image.save(&self.input_image_path)
+        /// Writes an image, embedding `icc_profile` when the format supports it.
+        fn write_image(
+            image: &DynamicImage,
+            output_path: &Path,
+            icc_profile: Option<Vec<u8>>,
+        ) -> ImageResult<()> {
+            let Some(icc_profile) = icc_profile else {
+                return image.save(output_path);
+            };
+            let format = ImageFormat::from_path(output_path)?;
+            if !matches!(
+                format,
+                ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP
+            ) {
+                return image.save(output_path);
+            }
+            let writer = BufWriter::new(File::create(output_path)?);
+            Self::encode(
+                image,
+                writer,
+                format,
+                DEFAULT_JPEG_QUALITY,
+                Some(icc_profile),
+            )
+        }
+
+        /// Encodes an image, embedding `icc_profile` when the format supports it.
+        fn encode(
+            image: &DynamicImage,
+            mut writer: impl Write,
+            format: ImageFormat,
+            quality: u8,
+            icc_profile: Option<Vec<u8>>,
+        ) -> ImageResult<()> {
+            match format {
+                ImageFormat::Png => {
+                    let mut encoder = PngEncoder::new(writer);
+                    if let Some(icc_profile) = icc_profile {
+                        let _ = encoder.set_icc_profile(icc_profile);
+                    }
+                    image.write_with_encoder(encoder)
+                }
+                ImageFormat::Jpeg => {
+                    let mut encoder = JpegEncoder::new_with_quality(writer, quality);
+                    if let Some(icc_profile) = icc_profile {
+                        let _ = encoder.set_icc_profile(icc_profile);
+                    }
+                    image.write_with_encoder(encoder)
+                }
+                ImageFormat::WebP => {
+                    let mut encoder = WebPEncoder::new_lossless(writer);
+                    if let Some(icc_profile) = icc_profile {
+                        let _ = encoder.set_icc_profile(icc_profile);
+                    }
+                    image.write_with_encoder(encoder)
+                }
+                _ => {
+                    // Other encoders need a seekable writer.
+                    let mut buffer = Cursor::new(Vec::new());
+                    image.write_to(&mut buffer, format)?;
+                    writer.write_all(buffer.get_ref())?;
+                    Ok(())
+                }
+            }
         }
 
-        pub fn color_transform_inplace(mut self, matrix: [f32; 20]) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform(&mut input_surface, &mut output_surface, matrix);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        /// Returns the color space of the image, read from its ICC profile on load.
+        pub fn color_space(&self) -> ColorSpace {
+            self.color_space
         }
 
-        pub fn color_transform(mut self, matrix: [f32; 20]) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform(&mut input_surface, &mut output_surface, matrix);
-            self
+        /// Converts the image into another color space.
+        ///
+        ///Later operations run in the new color space. A matching ICC profile is embedded when
+        ///saving.
+        pub fn convert_color_space(self, color_space: ColorSpace) -> Self {
+            let mut editor = self.push(Operation::ConvertColorSpace(color_space));
+            editor.color_space = color_space;
+            editor.icc_profile = None;
+            editor
         }
 
-        pub fn color_transform_opacity_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_opacity(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        /// The ICC profile embedded on save: the one read on load, or a generated profile when
+        /// the image is not sRGB.
+        fn output_icc_profile(&self) -> Option<Vec<u8>> {
+            self.icc_profile.clone().or_else(|| {
+                (self.color_space != ColorSpace::Srgb).then(|| icc_profile(self.color_space))
+            })
         }
 
-        pub fn color_transform_opacity(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_opacity(&mut input_surface, &mut output_surface, amount);
+        /// Sets the color space used by the color transform, blur, blend and composite operations
+        /// that follow in the pipeline.
+        ///
+        ///Browsers apply SVG filters in [ColorInterpolation::LinearRGB] by default, the editor
+        ///starts in [ColorInterpolation::SRGB].
+        pub fn color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+            self.color_interpolation = color_interpolation;
             self
         }
 
-        pub fn color_transform_brightness_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_brightness(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        /// Stores the current image under `name`, for use as [FilterInput::Result] by later
+        /// operations. Storing another image under the same name replaces it.
+        pub fn result(self, name: impl Into<String>) -> Self {
+            self.push(Operation::SaveResult(name.into()))
         }
 
-        pub fn color_transform_brightness(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_brightness(&mut input_surface, &mut output_surface, amount);
-            self
+        /// Makes `input` the current image, so later operations continue from it.
+        ///
+        ///Use [FilterInput::SourceGraphic] to start a new branch from the original image.
+        pub fn input(self, input: impl Into<FilterInput>) -> Self {
+            let operation = Operation::Input(self.layer(input.into()));
+            self.push(operation)
         }
 
-        pub fn color_transform_invert_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_invert(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        /// Saves the current image to provided path
+        pub fn save_to(mut self, output_path: impl AsRef<Path>) -> ImageResult<()> {
+            self.run_pending();
+            let output_path = output_path.as_ref();
+            Self::write_image(
+                &self.buffers.current,
+                output_path,
+                self.output_icc_profile(),
+            )
+        }
+
+        /// saves the current image in the input image path
+        ///
+        ///Fails with [std::io::ErrorKind::NotFound] for editors that were not opened from a path.
+        pub fn save(mut self) -> ImageResult<()> {
+            self.run_pending();
+            let Some(input_image_path) = &self.input_image_path else {
+                return Err(ImageError::IoError(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "the image was not opened from a path",
+                )));
+            };
+            Self::write_image(
+                &self.buffers.current,
+                input_image_path,
+                self.output_icc_profile(),
+            )
         }
 
-        pub fn color_transform_invert(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_invert(&mut input_surface, &mut output_surface, amount);
-            self
+        /// Encodes the current image into `writer`.
+        ///
+        ///`quality` in the range [1, 100] applies to JPEG, PNG and WebP are written lossless.
+        ///The ICC profile is embedded as with [ImageEditor::save_to].
+        pub fn encode_to(
+            mut self,
+            writer: impl Write,
+            format: ImageFormat,
+            quality: u8,
+        ) -> ImageResult<()> {
+            self.run_pending();
+            let icc_profile = self.output_icc_profile();
+            Self::encode(&self.buffers.current, writer, format, quality, icc_profile)
         }
 
-        pub fn color_transform_contrast_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_contrast(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        /// Runs the pending operations and returns the current image.
+        pub fn into_dynamic_image(mut self) -> DynamicImage {
+            self.run_pending();
+            self.buffers.current
         }
 
-        pub fn color_transform_contrast(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_contrast(&mut input_surface, &mut output_surface, amount);
-            self
+        pub fn color_transform(self, matrix: impl Into<ColorMatrix>) -> Self {
+            self.push(Operation::ColorTransform(matrix.into()))
         }
 
-        pub fn color_transform_saturate_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_saturate(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform`"
+        )]
+        pub fn color_transform_inplace(self, matrix: impl Into<ColorMatrix>) -> Self {
+            self.color_transform(matrix)
         }
 
-        pub fn color_transform_saturate(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_saturate(&mut input_surface, &mut output_surface, amount);
-            self
+        pub fn color_transform_opacity(self, amount: f32) -> Self {
+            self.push(Operation::ColorTransform(ColorMatrix::opacity(amount)))
         }
 
-        pub fn color_transform_grayscale_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_grayscale(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_opacity`"
+        )]
+        pub fn color_transform_opacity_inplace(self, amount: f32) -> Self {
+            self.color_transform_opacity(amount)
         }
 
-        pub fn color_transform_grayscale(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_grayscale(&mut input_surface, &mut output_surface, amount);
-            self
+        pub fn color_transform_brightness(self, amount: f32) -> Self {
+            self.push(Operation::ColorTransform(ColorMatrix::brightness(amount)))
         }
 
-        pub fn color_transform_sepia_inplace(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_sepia(&mut input_surface, &mut output_surface, amount);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_brightness`"
+        )]
+        pub fn color_transform_brightness_inplace(self, amount: f32) -> Self {
+            self.color_transform_brightness(amount)
         }
 
-        pub fn color_transform_sepia(mut self, amount: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_sepia(&mut input_surface, &mut output_surface, amount);
-            self
+        pub fn color_transform_invert(self, amount: f32) -> Self {
+            self.push(Operation::ColorTransform(ColorMatrix::invert(amount)))
         }
 
-        pub fn color_transform_hue_rotate_inplace(mut self, angle: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_hue_rotate(&mut input_surface, &mut output_surface, angle);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_invert`"
+        )]
+        pub fn color_transform_invert_inplace(self, amount: f32) -> Self {
+            self.color_transform_invert(amount)
         }
 
-        pub fn color_transform_hue_rotate(mut self, angle: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_hue_rotate(&mut input_surface, &mut output_surface, angle);
-            self
+        pub fn color_transform_contrast(self, amount: f32) -> Self {
+            self.push(Operation::ColorTransform(ColorMatrix::contrast(amount)))
         }
 
-        pub fn color_transform_luminance_to_alpha_inplace(mut self) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_luminance_to_alpha(&mut input_surface, &mut output_surface);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_contrast`"
+        )]
+        pub fn color_transform_contrast_inplace(self, amount: f32) -> Self {
+            self.color_transform_contrast(amount)
         }
 
-        pub fn color_transform_luminance_to_alpha(mut self) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_luminance_to_alpha(&mut input_surface, &mut output_surface);
-            self
+        pub fn color_transform_saturate(self, amount: f32) -> Self {
+            let operation =
+                Operation::ColorTransform(ColorMatrix::saturate_in(amount, self.color_space));
+            self.push(operation)
         }
 
-        pub fn color_transform_srgb_to_linear_rgb_inplace(mut self) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_srgb_to_linear_rgb(&mut input_surface, &mut output_surface);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_saturate`"
+        )]
+        pub fn color_transform_saturate_inplace(self, amount: f32) -> Self {
+            self.color_transform_saturate(amount)
         }
 
-        pub fn color_transform_srgb_to_linear_rgb(mut self) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_srgb_to_linear_rgb(&mut input_surface, &mut output_surface);
-            self
+        pub fn color_transform_grayscale(self, amount: f32) -> Self {
+            let operation =
+                Operation::ColorTransform(ColorMatrix::grayscale_in(amount, self.color_space));
+            self.push(operation)
         }
 
-        pub fn color_transform_linear_rgb_to_srgb_inplace(mut self) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_linear_rgb_to_srgb(&mut input_surface, &mut output_surface);
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_grayscale`"
+        )]
+        pub fn color_transform_grayscale_inplace(self, amount: f32) -> Self {
+            self.color_transform_grayscale(amount)
         }
 
-        pub fn color_transform_linear_rgb_to_srgb(mut self) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::color_transform_linear_rgb_to_srgb(&mut input_surface, &mut output_surface);
-            self
+        pub fn color_transform_sepia(self, amount: f32) -> Self {
+            self.push(Operation::ColorTransform(ColorMatrix::sepia(amount)))
         }
 
-        pub fn gaussian_blur_inplace(mut self, std_deviation_x: f32, std_deviation_y: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::gaussian_blur(
-                &mut input_surface,
-                &mut output_surface,
-                std_deviation_x,
-                std_deviation_y,
-            );
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_sepia`"
+        )]
+        pub fn color_transform_sepia_inplace(self, amount: f32) -> Self {
+            self.color_transform_sepia(amount)
         }
-        pub fn gaussian_blur(mut self, std_deviation_x: f32, std_deviation_y: f32) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-            Surface::gaussian_blur(
-                &mut input_surface,
-                &mut output_surface,
-                std_deviation_x,
-                std_deviation_y,
-            );
-            self
+
+        pub fn color_transform_hue_rotate(self, angle: f32) -> Self {
+            let operation =
+                Operation::ColorTransform(ColorMatrix::hue_rotate_in(angle, self.color_space));
+            self.push(operation)
         }
 
-        pub fn blend_inplace(mut self, blend_image: impl AsRef<Path>, mode: BlendMode) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_hue_rotate`"
+        )]
+        pub fn color_transform_hue_rotate_inplace(self, angle: f32) -> Self {
+            self.color_transform_hue_rotate(angle)
+        }
 
-            let mut blend_input_image = Self::open_image(blend_image.as_ref());
-            let mut blend_surface = Surface::from_image(&mut blend_input_image);
-            Surface::blend(
-                &mut input_surface,
-                &mut blend_surface,
-                &mut output_surface,
-                mode,
-            );
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+        pub fn color_transform_luminance_to_alpha(self) -> Self {
+            self.push(Operation::LuminanceToAlpha)
         }
 
-        pub fn blend(mut self, blend_image: impl AsRef<Path>, mode: BlendMode) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_luminance_to_alpha`"
+        )]
+        pub fn color_transform_luminance_to_alpha_inplace(self) -> Self {
+            self.color_transform_luminance_to_alpha()
+        }
 
-            let mut blend_input_image = Self::open_image(blend_image.as_ref());
-            let mut blend_surface = Surface::from_image(&mut blend_input_image);
-            Surface::blend(
-                &mut input_surface,
-                &mut blend_surface,
-                &mut output_surface,
-                mode,
-            );
-            self
+        pub fn color_transform_srgb_to_linear_rgb(self) -> Self {
+            self.push(Operation::SrgbToLinearRgb)
         }
-        pub fn composite_inplace(
-            mut self,
-            composite_image: impl AsRef<Path>,
-            op: CompositeOperator,
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_srgb_to_linear_rgb`"
+        )]
+        pub fn color_transform_srgb_to_linear_rgb_inplace(self) -> Self {
+            self.color_transform_srgb_to_linear_rgb()
+        }
+
+        pub fn color_transform_linear_rgb_to_srgb(self) -> Self {
+            self.push(Operation::LinearRgbToSrgb)
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `color_transform_linear_rgb_to_srgb`"
+        )]
+        pub fn color_transform_linear_rgb_to_srgb_inplace(self) -> Self {
+            self.color_transform_linear_rgb_to_srgb()
+        }
+
+        pub fn levels(self, black: f32, white: f32, gamma: f32) -> Self {
+            self.push(Operation::Levels(black, white, gamma))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `levels`"
+        )]
+        pub fn levels_inplace(self, black: f32, white: f32, gamma: f32) -> Self {
+            self.levels(black, white, gamma)
+        }
+
+        pub fn curves(self, points: &[[f32; 2]]) -> Self {
+            self.push(Operation::Curves(
+                points.to_vec(),
+                points.to_vec(),
+                points.to_vec(),
+            ))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `curves`"
+        )]
+        pub fn curves_inplace(self, points: &[[f32; 2]]) -> Self {
+            self.curves(points)
+        }
+
+        pub fn curves_rgb(self, red: &[[f32; 2]], green: &[[f32; 2]], blue: &[[f32; 2]]) -> Self {
+            self.push(Operation::Curves(
+                red.to_vec(),
+                green.to_vec(),
+                blue.to_vec(),
+            ))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `curves_rgb`"
+        )]
+        pub fn curves_rgb_inplace(
+            self,
+            red: &[[f32; 2]],
+            green: &[[f32; 2]],
+            blue: &[[f32; 2]],
         ) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-
-            let mut composite_input_image = Self::open_image(composite_image.as_ref());
-            let mut composite_surface = Surface::from_image(&mut composite_input_image);
-            Surface::composite(
-                &mut input_surface,
-                &mut composite_surface,
-                &mut output_surface,
-                op,
-            );
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+            self.curves_rgb(red, green, blue)
+        }
+
+        pub fn vibrance(self, amount: f32) -> Self {
+            self.push(Operation::Vibrance(amount))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `vibrance`"
+        )]
+        pub fn vibrance_inplace(self, amount: f32) -> Self {
+            self.vibrance(amount)
+        }
+
+        pub fn temperature_tint(self, temperature: f32, tint: f32) -> Self {
+            self.push(Operation::TemperatureTint(temperature, tint))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `temperature_tint`"
+        )]
+        pub fn temperature_tint_inplace(self, temperature: f32, tint: f32) -> Self {
+            self.temperature_tint(temperature, tint)
+        }
+
+        pub fn shadows_highlights(self, shadows: f32, highlights: f32) -> Self {
+            self.push(Operation::ShadowsHighlights(shadows, highlights))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `shadows_highlights`"
+        )]
+        pub fn shadows_highlights_inplace(self, shadows: f32, highlights: f32) -> Self {
+            self.shadows_highlights(shadows, highlights)
+        }
+
+        pub fn hsl_adjust(self, adjustments: &[(HueRange, HslAdjustment)]) -> Self {
+            self.push(Operation::HslAdjust(adjustments.to_vec()))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `hsl_adjust`"
+        )]
+        pub fn hsl_adjust_inplace(self, adjustments: &[(HueRange, HslAdjustment)]) -> Self {
+            self.hsl_adjust(adjustments)
+        }
+
+        pub fn apply_lut(self, lut: &Lut3d, interpolation: LutInterpolation) -> Self {
+            self.push(Operation::Lut(lut.clone(), interpolation))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `apply_lut`"
+        )]
+        pub fn apply_lut_inplace(self, lut: &Lut3d, interpolation: LutInterpolation) -> Self {
+            self.apply_lut(lut, interpolation)
+        }
+
+        pub fn gaussian_blur(self, std_deviation_x: f32, std_deviation_y: f32) -> Self {
+            self.push(Operation::GaussianBlur(std_deviation_x, std_deviation_y))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `gaussian_blur`"
+        )]
+        pub fn gaussian_blur_inplace(self, std_deviation_x: f32, std_deviation_y: f32) -> Self {
+            self.gaussian_blur(std_deviation_x, std_deviation_y)
+        }
+
+        pub fn bilateral_filter(self, sigma_spatial: f32, sigma_range: f32) -> Self {
+            self.push(Operation::BilateralFilter(sigma_spatial, sigma_range))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `bilateral_filter`"
+        )]
+        pub fn bilateral_filter_inplace(self, sigma_spatial: f32, sigma_range: f32) -> Self {
+            self.bilateral_filter(sigma_spatial, sigma_range)
+        }
+
+        pub fn rank_filter(self, radius_x: u32, radius_y: u32, percentile: f32) -> Self {
+            self.push(Operation::RankFilter(radius_x, radius_y, percentile))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `rank_filter`"
+        )]
+        pub fn rank_filter_inplace(self, radius_x: u32, radius_y: u32, percentile: f32) -> Self {
+            self.rank_filter(radius_x, radius_y, percentile)
+        }
+
+        pub fn median_filter(self, radius_x: u32, radius_y: u32) -> Self {
+            self.push(Operation::RankFilter(radius_x, radius_y, 0.5))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `median_filter`"
+        )]
+        pub fn median_filter_inplace(self, radius_x: u32, radius_y: u32) -> Self {
+            self.median_filter(radius_x, radius_y)
+        }
+
+        pub fn min_filter(self, radius_x: u32, radius_y: u32) -> Self {
+            self.push(Operation::RankFilter(radius_x, radius_y, 0.0))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `min_filter`"
+        )]
+        pub fn min_filter_inplace(self, radius_x: u32, radius_y: u32) -> Self {
+            self.min_filter(radius_x, radius_y)
+        }
+
+        pub fn max_filter(self, radius_x: u32, radius_y: u32) -> Self {
+            self.push(Operation::RankFilter(radius_x, radius_y, 1.0))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `max_filter`"
+        )]
+        pub fn max_filter_inplace(self, radius_x: u32, radius_y: u32) -> Self {
+            self.max_filter(radius_x, radius_y)
+        }
+
+        pub fn transform(
+            self,
+            matrix: [f32; 6],
+            filter: ResampleFilter,
+            edge_mode: EdgeMode,
+        ) -> Self {
+            self.push(Operation::Transform(matrix, filter, edge_mode))
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `transform`"
+        )]
+        pub fn transform_inplace(
+            self,
+            matrix: [f32; 6],
+            filter: ResampleFilter,
+            edge_mode: EdgeMode,
+        ) -> Self {
+            self.transform(matrix, filter, edge_mode)
+        }
+
+        /// Scales the image to the given size.
+        pub fn resize(self, width: u32, height: u32, filter: ResampleFilter) -> Self {
+            self.push(Operation::Resize(width, height, filter))
+        }
+
+        pub fn blend(self, blend_image: impl Into<FilterInput>, mode: BlendMode) -> Self {
+            let operation = Operation::Blend(self.layer(blend_image.into()), mode);
+            self.push(operation)
+        }
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `blend`"
+        )]
+        pub fn blend_inplace(self, blend_image: impl Into<FilterInput>, mode: BlendMode) -> Self {
+            self.blend(blend_image, mode)
         }
 
         pub fn composite(
-            mut self,
-            composite_image: impl AsRef<Path>,
+            self,
+            composite_image: impl Into<FilterInput>,
             op: CompositeOperator,
         ) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-
-            let mut composite_input_image = Self::open_image(composite_image.as_ref());
-            let mut composite_surface = Surface::from_image(&mut composite_input_image);
-            Surface::composite(
-                &mut input_surface,
-                &mut composite_surface,
-                &mut output_surface,
-                op,
-            );
-            self
+            let operation = Operation::Composite(self.layer(composite_image.into()), op);
+            self.push(operation)
         }
-        pub fn composite_arithmetic_inplace(
-            mut self,
-            composite_image: impl AsRef<Path>,
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `composite`"
+        )]
+        pub fn composite_inplace(
+            self,
+            composite_image: impl Into<FilterInput>,
+            op: CompositeOperator,
+        ) -> Self {
+            self.composite(composite_image, op)
+        }
+
+        pub fn composite_arithmetic(
+            self,
+            composite_image: impl Into<FilterInput>,
             k1: f32,
             k2: f32,
             k3: f32,
             k4: f32,
         ) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-
-            let mut composite_input_image = Self::open_image(composite_image.as_ref());
-            let mut composite_surface = Surface::from_image(&mut composite_input_image);
-            Surface::composite_arithmetic(
-                &mut input_surface,
-                &mut composite_surface,
-                &mut output_surface,
-                k1,
-                k2,
-                k3,
-                k4,
+            let operation = Operation::CompositeArithmetic(
+                self.layer(composite_image.into()),
+                [k1, k2, k3, k4],
             );
-            Surface::copy_output_into_input(&mut input_surface, &output_surface);
-            self
+            self.push(operation)
         }
-        pub fn composite_arithmetic(
-            mut self,
-            composite_image: impl AsRef<Path>,
+
+        #[deprecated(
+            since = "0.4.1",
+            note = "every operation continues from the current image, use `composite_arithmetic`"
+        )]
+        pub fn composite_arithmetic_inplace(
+            self,
+            composite_image: impl Into<FilterInput>,
             k1: f32,
             k2: f32,
             k3: f32,
             k4: f32,
         ) -> Self {
-            let mut input_surface = Surface::from_image(&mut self.input_image);
-            let mut output_surface = Surface::from_image(&mut self.output_image);
-
-            let mut composite_input_image = Self::open_image(composite_image.as_ref());
-            let mut composite_surface = Surface::from_image(&mut composite_input_image);
-            Surface::composite_arithmetic(
-                &mut input_surface,
-                &mut composite_surface,
-                &mut output_surface,
-                k1,
-                k2,
-                k3,
-                k4,
-            );
-            self
+            self.composite_arithmetic(composite_image, k1, k2, k3, k4)
         }
     }
 }
@@ -0,0 +1,71 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{
+    Surface, SurfaceF32, SurfaceView,
+    utils::*,
+    wide::{premultiply, unpremultiply},
+};
+use alloc::{vec, vec::Vec};
+
+/// A premultiplied, linear-light floating point copy of an 8-bit surface.
+///
+/// Filters that run in [crate::ColorInterpolation::LinearRGB] convert their inputs into this
+/// buffer, run the [SurfaceF32] primitive, and quantize back to 8 bits only when storing.
+pub(crate) struct LinearBuffer {
+    pixels: Vec<[f32; 4]>,
+    width: u32,
+    height: u32,
+}
+
+impl LinearBuffer {
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        Self {
+            pixels: vec![[0.0; 4]; (width * height) as usize],
+            width,
+            height,
+        }
+    }
+
+    pub(crate) fn from_surface(surface: &SurfaceView) -> Self {
+        let mut buffer = Self::new(surface.width, surface.height);
+        for y in 0..surface.height {
+            for x in 0..surface.width {
+                let pixel =
+                    init_load_pixel(surface, x, y, surface.channel).map(|c| c as f32 / 255.0);
+                let [r, g, b, a] = unpremultiply(pixel);
+                let [r, g, b] = [r, g, b].map(|c| surface.color_space.decode(c.min(1.0)));
+                buffer.pixels[(y * surface.width + x) as usize] = premultiply([r, g, b, a]);
+            }
+        }
+        buffer
+    }
+
+    pub(crate) fn surface(&mut self) -> SurfaceF32<'_> {
+        SurfaceF32 {
+            pixels: &mut self.pixels,
+            width: self.width,
+            height: self.height,
+            stride: self.width,
+        }
+    }
+
+    /// Encodes the buffer with the transfer function of `output` and stores it into the overlapping area of `output`.
+    pub(crate) fn store(&self, output: &mut Surface) {
+        for y in 0..self.height.min(output.height) {
+            for x in 0..self.width.min(output.width) {
+                let pixel = self.pixels[(y * self.width + x) as usize];
+                let [r, g, b, a] = unpremultiply(pixel.map(|c| c.clamp(0.0, 1.0)));
+                let space = output.color_space;
+                let [mut r, mut g, mut b, mut a] = [
+                    space.encode(r.min(1.0)),
+                    space.encode(g.min(1.0)),
+                    space.encode(b.min(1.0)),
+                    a,
+                ]
+                .map(|c| (c * 255.0).round() as u32);
+                premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+                store_pixel(output, x, y, r, g, b, a);
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::error::LutError;
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use alloc::vec::Vec;
+#[cfg(feature = "image")]
+use image::DynamicImage;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+const MAX_SIZE: usize = 256;
+
+/// Interpolation used to look up colors that fall between the entries of a [Lut3d].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LutInterpolation {
+    /// Weighted average of the 8 entries of the cell containing the color
+    Trilinear,
+    /// Weighted average of the 4 entries of the tetrahedron containing the color, which is
+    /// cheaper and keeps grays on the neutral axis of the table
+    #[default]
+    Tetrahedral,
+}
+
+/// A 3D color lookup table mapping RGB colors to RGB colors.
+///
+/// Entries are stored with red changing fastest, then green, then blue, as in `.cube` files and
+/// Hald CLUT images. Input colors are normalized to [0, 1] over the domain of the table.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{Lut3d, LutInterpolation};
+///
+/// let lut = Lut3d::parse_cube("LUT_3D_SIZE 2\n0 0 0\n0 0 1\n0 1 0\n0 1 1\n1 0 0\n1 0 1\n1 1 0\n1 1 1\n")?;
+/// let [r, g, b] = lut.lookup([1.0, 0.5, 0.0], LutInterpolation::Trilinear);
+/// assert_eq!([r, g, b], [0.0, 0.5, 1.0]);
+/// # Ok::<(), plutofilter_rs::LutError>(())
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    size: usize,
+    table: Vec<[f32; 3]>,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+impl Lut3d {
+    /// Creates a lookup table of `size` entries per axis from `size^3` entries.
+    pub fn new(size: usize, table: Vec<[f32; 3]>) -> Result<Self, LutError> {
+        if !(2..=MAX_SIZE).contains(&size) {
+            return Err(LutError::InvalidSize);
+        }
+        if table.len() != size * size * size {
+            return Err(LutError::EntryCount {
+                expected: size * size * size,
+                found: table.len(),
+            });
+        }
+        Ok(Self {
+            size,
+            table,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+        })
+    }
+
+    /// Creates a lookup table of `size` entries per axis that maps every color to itself.
+    pub fn identity(size: usize) -> Self {
+        if !(2..=MAX_SIZE).contains(&size) {
+            panic!("LUT size must be in the range [2, 256].")
+        }
+        let scale = 1.0 / (size - 1) as f32;
+        let table = (0..size * size * size)
+            .map(|i| [i % size, i / size % size, i / (size * size)].map(|c| c as f32 * scale))
+            .collect();
+        Self {
+            size,
+            table,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+        }
+    }
+
+    /// Returns the number of entries along each axis.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Parses an Adobe or DaVinci Resolve `.cube` file.
+    ///
+    ///Supports `LUT_3D_SIZE`, `DOMAIN_MIN`, `DOMAIN_MAX` and `LUT_3D_INPUT_RANGE`.
+    ///`TITLE`, comments and unknown keywords are ignored.
+    pub fn parse_cube(text: &str) -> Result<Self, LutError> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut tokens = line.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            let invalid = || LutError::InvalidLine(index + 1);
+            let mut numbers = |count: usize| -> Result<Vec<f32>, LutError> {
+                let values = tokens
+                    .by_ref()
+                    .map(str::parse)
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| invalid())?;
+                if values.len() != count {
+                    return Err(invalid());
+                }
+                Ok(values)
+            };
+            match keyword {
+                "LUT_3D_SIZE" => {
+                    let value = numbers(1)?[0];
+                    if value.fract() != 0.0 || !(2.0..=MAX_SIZE as f32).contains(&value) {
+                        return Err(LutError::InvalidSize);
+                    }
+                    size = Some(value as usize);
+                }
+                "LUT_1D_SIZE" => return Err(LutError::Unsupported1d),
+                "DOMAIN_MIN" => domain_min.copy_from_slice(&numbers(3)?),
+                "DOMAIN_MAX" => domain_max.copy_from_slice(&numbers(3)?),
+                "LUT_3D_INPUT_RANGE" => {
+                    let range = numbers(2)?;
+                    domain_min = [range[0]; 3];
+                    domain_max = [range[1]; 3];
+                }
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => {
+                    let green = tokens.next().ok_or_else(invalid)?;
+                    let blue = tokens.next().ok_or_else(invalid)?;
+                    if tokens.next().is_some() {
+                        return Err(invalid());
+                    }
+                    let mut entry = [0.0; 3];
+                    for (value, token) in entry.iter_mut().zip([keyword, green, blue]) {
+                        *value = token.parse().map_err(|_| invalid())?;
+                    }
+                    table.push(entry);
+                }
+            }
+        }
+
+        if (0..3).any(|i| domain_max[i] <= domain_min[i]) {
+            return Err(LutError::InvalidDomain);
+        }
+        let mut lut = Self::new(size.ok_or(LutError::MissingSize)?, table)?;
+        lut.domain_min = domain_min;
+        lut.domain_max = domain_max;
+        Ok(lut)
+    }
+
+    /// Reads and parses a `.cube` file.
+    #[cfg(feature = "std")]
+    pub fn from_cube_file(path: impl AsRef<Path>) -> Result<Self, LutError> {
+        Self::parse_cube(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads a Hald CLUT image.
+    ///
+    ///A Hald CLUT of level `L` is a square image of `L^3 x L^3` pixels holding a table of `L^2`
+    ///entries per axis, in row-major order.
+    #[cfg(feature = "image")]
+    pub fn from_hald_image(image: &DynamicImage) -> Result<Self, LutError> {
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let level = (1..)
+            .find(|level| level * level * level >= width)
+            .unwrap_or(1);
+        if width != height || level * level * level != width {
+            return Err(LutError::InvalidHaldSize);
+        }
+        let table = image.to_rgb32f().pixels().map(|pixel| pixel.0).collect();
+        Self::new(level * level, table)
+    }
+
+    #[inline(always)]
+    fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[r + self.size * (g + self.size * b)]
+    }
+
+    /// Maps a color through the table.
+    pub fn lookup(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let last = (self.size - 1) as f32;
+        let mut index = [0; 3];
+        let mut fraction = [0.0; 3];
+        for i in 0..3 {
+            let range = self.domain_max[i] - self.domain_min[i];
+            let position = ((rgb[i] - self.domain_min[i]) / range * last).clamp(0.0, last);
+            index[i] = (position as usize).min(self.size - 2);
+            fraction[i] = position - index[i] as f32;
+        }
+        let [r, g, b] = index;
+        let [fr, fg, fb] = fraction;
+        let corner = |dr: usize, dg: usize, db: usize| self.entry(r + dr, g + dg, b + db);
+        let mix = |weights: [(f32, [f32; 3]); 4]| {
+            let mut result = [0.0; 3];
+            for (weight, entry) in weights {
+                for (value, channel) in result.iter_mut().zip(entry) {
+                    *value += weight * channel;
+                }
+            }
+            result
+        };
+
+        match interpolation {
+            LutInterpolation::Trilinear => {
+                let lerp =
+                    |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t);
+                let c00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fr);
+                let c10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fr);
+                let c01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fr);
+                let c11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fr);
+                lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+            }
+            LutInterpolation::Tetrahedral => {
+                let (c000, c111) = (corner(0, 0, 0), corner(1, 1, 1));
+                if fr > fg {
+                    if fg > fb {
+                        mix([
+                            (1.0 - fr, c000),
+                            (fr - fg, corner(1, 0, 0)),
+                            (fg - fb, corner(1, 1, 0)),
+                            (fb, c111),
+                        ])
+                    } else if fr > fb {
+                        mix([
+                            (1.0 - fr, c000),
+                            (fr - fb, corner(1, 0, 0)),
+                            (fb - fg, corner(1, 0, 1)),
+                            (fg, c111),
+                        ])
+                    } else {
+                        mix([
+                            (1.0 - fb, c000),
+                            (fb - fr, corner(0, 0, 1)),
+                            (fr - fg, corner(1, 0, 1)),
+                            (fg, c111),
+                        ])
+                    }
+                } else if fb > fg {
+                    mix([
+                        (1.0 - fb, c000),
+                        (fb - fg, corner(0, 0, 1)),
+                        (fg - fr, corner(0, 1, 1)),
+                        (fr, c111),
+                    ])
+                } else if fb > fr {
+                    mix([
+                        (1.0 - fg, c000),
+                        (fg - fb, corner(0, 1, 0)),
+                        (fb - fr, corner(0, 1, 1)),
+                        (fr, c111),
+                    ])
+                } else {
+                    mix([
+                        (1.0 - fg, c000),
+                        (fg - fr, corner(0, 1, 0)),
+                        (fr - fb, corner(1, 1, 0)),
+                        (fb, c111),
+                    ])
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Float functions backed by libm for builds without `std`.
+//!
+//! Modules import [FloatMath] only when `std` is disabled, so the same method calls resolve to
+//! the inherent `std` functions otherwise. Test builds link `std` and use it as well.
+
+pub(crate) trait FloatMath {
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn fract(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+impl FloatMath for f32 {
+    #[inline(always)]
+    fn floor(self) -> Self {
+        libm::floorf(self)
+    }
+
+    #[inline(always)]
+    fn ceil(self) -> Self {
+        libm::ceilf(self)
+    }
+
+    #[inline(always)]
+    fn round(self) -> Self {
+        libm::roundf(self)
+    }
+
+    #[inline(always)]
+    fn fract(self) -> Self {
+        self - libm::truncf(self)
+    }
+
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        libm::sqrtf(self)
+    }
+
+    #[inline(always)]
+    fn cbrt(self) -> Self {
+        libm::cbrtf(self)
+    }
+
+    #[inline(always)]
+    fn sin(self) -> Self {
+        libm::sinf(self)
+    }
+
+    #[inline(always)]
+    fn cos(self) -> Self {
+        libm::cosf(self)
+    }
+
+    #[inline(always)]
+    fn powf(self, n: Self) -> Self {
+        libm::powf(self, n)
+    }
+
+    #[inline(always)]
+    fn hypot(self, other: Self) -> Self {
+        libm::hypotf(self, other)
+    }
+
+    #[inline(always)]
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = self % rhs;
+        if r < 0.0 { r + rhs.abs() } else { r }
+    }
+}
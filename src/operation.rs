@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use crate::{
+    BlendMode, ColorInterpolation, ColorMatrix, ColorSpace, CompositeOperator, EdgeMode,
+    HslAdjustment, HueRange, ImageSurface, Lut3d, LutInterpolation, ResampleFilter, Surface,
+    surface::copy_pixels, utils::calc_kernel_size,
+};
+use image::{DynamicImage, GenericImageView};
+
+/// An image an operation reads besides the current image.
+#[derive(Debug)]
+pub(crate) enum Layer {
+    /// The image as it was opened
+    Source,
+    /// An image stored with [Operation::SaveResult]
+    Result(String),
+    /// An image loaded in the color space of the step that reads it
+    Image(DynamicImage),
+}
+
+/// A filter run by [crate::ImageEditor], together with its arguments.
+#[derive(Debug)]
+pub(crate) enum Operation {
+    ColorTransform(ColorMatrix),
+    LuminanceToAlpha,
+    SrgbToLinearRgb,
+    LinearRgbToSrgb,
+    ConvertColorSpace(ColorSpace),
+    Levels(f32, f32, f32),
+    Curves(Vec<[f32; 2]>, Vec<[f32; 2]>, Vec<[f32; 2]>),
+    Vibrance(f32),
+    TemperatureTint(f32, f32),
+    ShadowsHighlights(f32, f32),
+    HslAdjust(Vec<(HueRange, HslAdjustment)>),
+    Lut(Lut3d, LutInterpolation),
+    GaussianBlur(f32, f32),
+    BilateralFilter(f32, f32),
+    RankFilter(u32, u32, f32),
+    Transform([f32; 6], ResampleFilter, EdgeMode),
+    Resize(u32, u32, ResampleFilter),
+    Blend(Layer, BlendMode),
+    Composite(Layer, CompositeOperator),
+    CompositeArithmetic(Layer, [f32; 4]),
+    SaveResult(String),
+    Input(Layer),
+}
+
+/// An [Operation] with the editor settings in effect when it was added to the pipeline.
+#[derive(Debug)]
+pub(crate) struct Step {
+    pub(crate) operation: Operation,
+    pub(crate) color_interpolation: ColorInterpolation,
+    pub(crate) color_space: ColorSpace,
+}
+
+impl Step {
+    /// Whether the operation returns its input unchanged, up to 8-bit rounding.
+    fn is_identity(&self) -> bool {
+        match &self.operation {
+            Operation::ColorTransform(matrix) => matrix.is_identity(),
+            Operation::GaussianBlur(x, y) => {
+                *x >= 0.0 && *y >= 0.0 && calc_kernel_size(*x) == 0 && calc_kernel_size(*y) == 0
+            }
+            Operation::BilateralFilter(spatial, range) => {
+                *spatial >= 0.0 && *range >= 0.0 && (*spatial == 0.0 || *range == 0.0)
+            }
+            Operation::RankFilter(x, y, percentile) => {
+                *x == 0 && *y == 0 && (0.0..=1.0).contains(percentile)
+            }
+            Operation::Levels(black, white, gamma) => {
+                *black == 0.0 && *white == 1.0 && *gamma == 1.0
+            }
+            Operation::Vibrance(amount) => *amount == 0.0,
+            Operation::TemperatureTint(temperature, tint) => *temperature == 0.0 && *tint == 0.0,
+            Operation::ShadowsHighlights(shadows, highlights) => {
+                *shadows == 0.0 && *highlights == 0.0
+            }
+            Operation::HslAdjust(adjustments) => adjustments
+                .iter()
+                .all(|(_, adjustment)| *adjustment == HslAdjustment::default()),
+            _ => false,
+        }
+    }
+}
+
+/// Borrows the pixels of an editor image as a surface.
+fn image_surface(image: &mut DynamicImage) -> ImageSurface<'_> {
+    Surface::try_from_image(image).expect("ERROR: Failed to make surface from image.")
+}
+
+/// Returns a copy of `image` converted from one color space into another.
+pub(crate) fn convert_image(
+    image: &mut DynamicImage,
+    from: ColorSpace,
+    to: ColorSpace,
+) -> DynamicImage {
+    let mut converted = DynamicImage::new_rgba8(image.width(), image.height());
+    {
+        let mut input_surface = image_surface(image).with_color_space(from);
+        let mut output_surface = image_surface(&mut converted).with_color_space(to);
+        Surface::convert_color_space(&mut *input_surface, &mut output_surface);
+    }
+    converted
+}
+
+/// Returns a copy of the image of `layer` in `color_space`.
+fn layer_image(
+    source: &mut (DynamicImage, ColorSpace),
+    results: &mut HashMap<String, (DynamicImage, ColorSpace)>,
+    layer: Layer,
+    color_space: ColorSpace,
+) -> DynamicImage {
+    let (image, from) = match layer {
+        Layer::Source => source,
+        Layer::Result(name) => match results.get_mut(&name) {
+            Some(result) => result,
+            None => panic!("No result named `{name}`."),
+        },
+        Layer::Image(image) => return image,
+    };
+    if *from == color_space {
+        image.clone()
+    } else {
+        convert_image(image, *from, color_space)
+    }
+}
+
+/// The images of an editor pipeline.
+///
+///Every operation reads `current` and writes `scratch`, then the two are swapped, so the result
+///of one operation is the input of the next without copying. Blends and composites only write
+///where the layer overlaps the image, so `current` is copied into `scratch` before them and the
+///rest of the image passes through unchanged.
+#[derive(Debug)]
+pub(crate) struct Buffers {
+    pub(crate) current: DynamicImage,
+    scratch: DynamicImage,
+    source: (DynamicImage, ColorSpace),
+    results: HashMap<String, (DynamicImage, ColorSpace)>,
+}
+
+impl Buffers {
+    pub(crate) fn new(image: DynamicImage, color_space: ColorSpace) -> Self {
+        Self {
+            current: image.clone(),
+            scratch: DynamicImage::new_rgba8(image.width(), image.height()),
+            source: (image, color_space),
+            results: HashMap::new(),
+        }
+    }
+
+    /// Runs a step, making its result the current image.
+    pub(crate) fn run(&mut self, step: Step) {
+        let Step {
+            operation,
+            color_interpolation,
+            color_space,
+        } = step;
+        let Self {
+            current,
+            scratch,
+            source,
+            results,
+        } = self;
+
+        let operation = match operation {
+            Operation::SaveResult(name) => {
+                results.insert(name, (current.clone(), color_space));
+                return;
+            }
+            Operation::Input(layer) => {
+                *current = layer_image(source, results, layer, color_space);
+                return;
+            }
+            operation => operation,
+        };
+        let (input_width, input_height) = current.dimensions();
+        let (width, height) = match operation {
+            Operation::Resize(width, height, _) => (width, height),
+            _ => (input_width, input_height),
+        };
+        if scratch.dimensions() != (width, height) {
+            *scratch = DynamicImage::new_rgba8(width, height);
+        }
+        let output_color_space = match operation {
+            Operation::ConvertColorSpace(target) => target,
+            _ => color_space,
+        };
+
+        {
+            let mut input = image_surface(current).with_color_space(color_space);
+            let mut output = image_surface(scratch)
+                .with_color_interpolation(color_interpolation)
+                .with_color_space(output_color_space);
+            let (input, output) = (&mut *input, &mut *output);
+            match operation {
+                Operation::ColorTransform(matrix) => {
+                    Surface::color_transform(input, output, matrix)
+                }
+                Operation::LuminanceToAlpha => {
+                    Surface::color_transform_luminance_to_alpha(input, output)
+                }
+                Operation::SrgbToLinearRgb => {
+                    Surface::color_transform_srgb_to_linear_rgb(input, output)
+                }
+                Operation::LinearRgbToSrgb => {
+                    Surface::color_transform_linear_rgb_to_srgb(input, output)
+                }
+                Operation::ConvertColorSpace(_) => Surface::convert_color_space(input, output),
+                Operation::Levels(black, white, gamma) => {
+                    Surface::levels(input, output, black, white, gamma)
+                }
+                Operation::Curves(red, green, blue) => {
+                    Surface::curves_rgb(input, output, &red, &green, &blue)
+                }
+                Operation::Vibrance(amount) => Surface::vibrance(input, output, amount),
+                Operation::TemperatureTint(temperature, tint) => {
+                    Surface::temperature_tint(input, output, temperature, tint)
+                }
+                Operation::ShadowsHighlights(shadows, highlights) => {
+                    Surface::shadows_highlights(input, output, shadows, highlights)
+                }
+                Operation::HslAdjust(adjustments) => {
+                    Surface::hsl_adjust(input, output, &adjustments)
+                }
+                Operation::Lut(lut, interpolation) => {
+                    Surface::apply_lut(input, output, &lut, interpolation)
+                }
+                Operation::GaussianBlur(x, y) => Surface::gaussian_blur(input, output, x, y),
+                Operation::BilateralFilter(spatial, range) => {
+                    Surface::bilateral_filter(input, output, spatial, range)
+                }
+                Operation::RankFilter(x, y, percentile) => {
+                    Surface::rank_filter(input, output, x, y, percentile)
+                }
+                Operation::Transform(matrix, filter, edge_mode) => {
+                    Surface::transform(input, output, matrix, filter, edge_mode)
+                }
+                Operation::Resize(width, height, filter) => {
+                    let matrix = [
+                        width as f32 / input_width as f32,
+                        0.0,
+                        0.0,
+                        height as f32 / input_height as f32,
+                        0.0,
+                        0.0,
+                    ];
+                    Surface::transform(input, output, matrix, filter, EdgeMode::Duplicate)
+                }
+                Operation::Blend(layer, mode) => {
+                    copy_pixels(&input.view(), output);
+                    let mut image = layer_image(source, results, layer, color_space);
+                    let mut blend_surface = image_surface(&mut image);
+                    Surface::blend(input, &mut *blend_surface, output, mode)
+                }
+                Operation::Composite(layer, operator) => {
+                    copy_pixels(&input.view(), output);
+                    let mut image = layer_image(source, results, layer, color_space);
+                    let mut composite_surface = image_surface(&mut image);
+                    Surface::composite(input, &mut *composite_surface, output, operator)
+                }
+                Operation::CompositeArithmetic(layer, [k1, k2, k3, k4]) => {
+                    copy_pixels(&input.view(), output);
+                    let mut image = layer_image(source, results, layer, color_space);
+                    let mut composite_surface = image_surface(&mut image);
+                    Surface::composite_arithmetic(
+                        input,
+                        &mut *composite_surface,
+                        output,
+                        k1,
+                        k2,
+                        k3,
+                        k4,
+                    )
+                }
+                Operation::SaveResult(_) | Operation::Input(_) => unreachable!(),
+            }
+        }
+        std::mem::swap(current, scratch);
+    }
+}
+
+/// Rewrites recorded steps into an equivalent, shorter list.
+///
+/// * Steps that leave the image unchanged are dropped.
+/// * Consecutive color matrices in the same [ColorInterpolation] are multiplied into one, as long
+///   as the first keeps every color inside `[0, 1]`. Separate passes clamp between the matrices,
+///   so merging a matrix that pushes colors out of range would change the result.
+pub(crate) fn optimize(steps: Vec<Step>) -> Vec<Step> {
+    let mut optimized: Vec<Step> = Vec::with_capacity(steps.len());
+    for step in steps {
+        if step.is_identity() {
+            continue;
+        }
+        if let Some(previous) = optimized.last_mut()
+            && previous.color_interpolation == step.color_interpolation
+            && let Operation::ColorTransform(first) = &mut previous.operation
+            && let Operation::ColorTransform(second) = &step.operation
+            && first.keeps_unit_range()
+        {
+            *first = first.then(*second);
+            if previous.is_identity() {
+                optimized.pop();
+            }
+            continue;
+        }
+        optimized.push(step);
+    }
+    optimized
+}
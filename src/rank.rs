@@ -0,0 +1,133 @@
+use crate::{Surface, SurfaceView, utils::*};
+use alloc::vec;
+
+const BINS: usize = 256;
+const CHANNELS: usize = 4;
+
+/// Per-channel 256-bin histogram of the pixels inside a window.
+#[derive(Clone)]
+struct Histogram {
+    bins: [[u32; BINS]; CHANNELS],
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            bins: [[0; BINS]; CHANNELS],
+        }
+    }
+
+    #[inline(always)]
+    fn add_pixel(&mut self, pixel: [u32; 4]) {
+        for (bins, value) in self.bins.iter_mut().zip(pixel) {
+            bins[value as usize] += 1;
+        }
+    }
+
+    #[inline(always)]
+    fn remove_pixel(&mut self, pixel: [u32; 4]) {
+        for (bins, value) in self.bins.iter_mut().zip(pixel) {
+            bins[value as usize] -= 1;
+        }
+    }
+
+    #[inline(always)]
+    fn add(&mut self, other: &Self) {
+        for (bins, other) in self.bins.iter_mut().zip(&other.bins) {
+            for (bin, count) in bins.iter_mut().zip(other) {
+                *bin += count;
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn subtract(&mut self, other: &Self) {
+        for (bins, other) in self.bins.iter_mut().zip(&other.bins) {
+            for (bin, count) in bins.iter_mut().zip(other) {
+                *bin -= count;
+            }
+        }
+    }
+
+    /// Returns the value of the `rank`-th smallest sample of every channel.
+    #[inline(always)]
+    fn select(&self, rank: u32) -> [u32; 4] {
+        let mut result = [0; 4];
+        for (value, bins) in result.iter_mut().zip(&self.bins) {
+            let mut seen = 0;
+            for (bin, count) in bins.iter().enumerate() {
+                seen += count;
+                if seen > rank {
+                    *value = bin as u32;
+                    break;
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Rank-order filter using the constant-time median algorithm of Perreault and Hébert.
+///
+/// One histogram is kept per column, covering `2 * radius_y + 1` rows. Moving down a row updates
+/// each column histogram with one removal and one insertion, and moving right along a row updates
+/// the window histogram with one column histogram subtraction and one addition. The cost per pixel
+/// is therefore independent of the window radius. Pixels outside the surface are not counted.
+pub(crate) fn rank_filter(
+    input: &SurfaceView,
+    output: &mut Surface,
+    radius_x: u32,
+    radius_y: u32,
+    percentile: f32,
+) {
+    let (width, height) = (output.width as usize, output.height as usize);
+    let (radius_x, radius_y) = (radius_x as usize, radius_y as usize);
+    let mut columns = vec![Histogram::new(); width];
+
+    for y in 0..radius_y.min(height) {
+        for (x, column) in columns.iter_mut().enumerate() {
+            column.add_pixel(init_load_pixel(input, x as u32, y as u32, input.channel));
+        }
+    }
+
+    for y in 0..height {
+        if let Some(top) = y.checked_sub(radius_y + 1) {
+            for (x, column) in columns.iter_mut().enumerate() {
+                column.remove_pixel(init_load_pixel(input, x as u32, top as u32, input.channel));
+            }
+        }
+        let bottom = y + radius_y;
+        if bottom < height {
+            for (x, column) in columns.iter_mut().enumerate() {
+                column.add_pixel(init_load_pixel(
+                    input,
+                    x as u32,
+                    bottom as u32,
+                    input.channel,
+                ));
+            }
+        }
+        let rows = (y + radius_y).min(height - 1) + 1 - y.saturating_sub(radius_y);
+
+        let mut window = Histogram::new();
+        for column in columns.iter().take(radius_x.min(width)) {
+            window.add(column);
+        }
+
+        for x in 0..width {
+            if let Some(left) = x.checked_sub(radius_x + 1) {
+                window.subtract(&columns[left]);
+            }
+            let right = x + radius_x;
+            if right < width {
+                window.add(&columns[right]);
+            }
+            let cols = (x + radius_x).min(width - 1) + 1 - x.saturating_sub(radius_x);
+            let count = (rows * cols) as u32;
+            let rank = (percentile * (count - 1) as f32 + 0.5) as u32;
+
+            let [r, g, b, a] = window.select(rank.min(count - 1));
+            store_pixel(output, x as u32, y as u32, r.min(a), g.min(a), b.min(a), a);
+        }
+    }
+}
@@ -0,0 +1,364 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{
+    EdgeMode, ResampleFilter, Surface,
+    bilateral::cell_spatial,
+    transform::{invert_affine, kernel_scale},
+};
+
+/// A rectangle of pixels, such as an SVG filter region given by `x`, `y`, `width` and `height`.
+///
+///Used by [crate::Surface::filter_region] to filter only part of a surface, for example the
+///damaged rectangles of a frame.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::Rect;
+/// let damaged = Rect::new(10, 10, 20, 20);
+/// let source = damaged.inflate(4, 4).intersect(Rect::new(0, 0, 32, 32));
+/// assert_eq!(source, Rect::new(6, 6, 26, 26));
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    /// The horizontal offset of the rectangle, in pixels.
+    pub x: u32,
+    /// The vertical offset of the rectangle, in pixels.
+    pub y: u32,
+    /// The width of the rectangle in pixels.
+    pub width: u32,
+    /// The height of the rectangle in pixels.
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the rectangle covering `width * height` pixels from the origin.
+    pub fn from_size(width: u32, height: u32) -> Self {
+        Self::new(0, 0, width, height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns the part of the rectangle that is inside `other`.
+    ///
+    ///Rectangles that do not overlap give an empty rectangle.
+    pub fn intersect(&self, other: Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+
+    /// Grows the rectangle by `dx` pixels on the left and right and `dy` pixels on the top and
+    /// bottom, stopping at the origin.
+    pub fn inflate(&self, dx: u32, dy: u32) -> Rect {
+        let x = self.x.saturating_sub(dx);
+        let y = self.y.saturating_sub(dy);
+        Rect::new(
+            x,
+            y,
+            self.right().saturating_add(dx) - x,
+            self.bottom().saturating_add(dy) - y,
+        )
+    }
+
+    pub(crate) fn right(&self) -> u32 {
+        self.x.saturating_add(self.width)
+    }
+
+    pub(crate) fn bottom(&self) -> u32 {
+        self.y.saturating_add(self.height)
+    }
+}
+
+/// Source coordinates far enough outside any surface to stand for an unbounded edge.
+const UNBOUNDED: f32 = 1e30;
+
+/// The rectangle covering every pixel, returned for changes that can reach the whole output.
+const EVERYWHERE: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: u32::MAX,
+    height: u32::MAX,
+};
+
+/// Relates the pixels a filter reads to the pixels it writes.
+///
+///Compositors use it to cull filters whose input is outside the visible area, to invalidate only the
+///output damaged by a change of the input, and to size intermediate surfaces. Chains of primitives
+///implement it through slices, applying each primitive in order.
+///
+///Rectangles are in the coordinates of the input and output surfaces and are not clipped to them.
+///They are conservative: they may hold a few more pixels than strictly needed, but never fewer.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{Bounds, FilterPrimitive, Rect};
+/// let chain = [
+///     FilterPrimitive::RankFilter(2, 2),
+///     FilterPrimitive::Pointwise,
+///     FilterPrimitive::RankFilter(1, 3),
+/// ];
+/// assert_eq!(chain.bounds(Rect::new(10, 10, 4, 4)), Rect::new(7, 5, 10, 14));
+/// assert_eq!(chain.required_input_rect(Rect::new(10, 10, 4, 4)), Rect::new(7, 5, 10, 14));
+/// ```
+///
+pub trait Bounds {
+    /// Returns the output pixels that change when the pixels of `input` change.
+    fn bounds(&self, input: Rect) -> Rect;
+
+    /// Returns the input pixels read to compute the pixels of `output`.
+    fn required_input_rect(&self, output: Rect) -> Rect;
+}
+
+/// A filter primitive, described by how far its output pixels reach into its input.
+///
+///Filters that read a neighborhood grow rectangles by its radius, and transforms map them through
+///their matrix, so a translating transform moves them.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{Bounds, EdgeMode, FilterPrimitive, Rect, ResampleFilter};
+/// let offset = FilterPrimitive::Transform {
+///     matrix: [1.0, 0.0, 0.0, 1.0, 8.0, -4.0],
+///     filter: ResampleFilter::Nearest,
+///     edge_mode: EdgeMode::None,
+///     input_width: 64,
+///     input_height: 64,
+/// };
+/// assert_eq!(offset.bounds(Rect::new(10, 10, 4, 4)), Rect::new(18, 6, 4, 4));
+/// ```
+///
+#[derive(Debug, Clone, Copy)]
+pub enum FilterPrimitive {
+    /// Color transforms, adjustments, LUTs, color space conversions, blends and composites, which
+    /// compute each pixel from the same pixel of their inputs.
+    Pointwise,
+    /// [Surface::gaussian_blur] with its horizontal and vertical standard deviations.
+    GaussianBlur(f32, f32),
+    /// [Surface::bilateral_filter] with its spatial standard deviation.
+    BilateralFilter(f32),
+    /// [Surface::rank_filter] and the median, min and max filters with their horizontal and vertical
+    /// radius.
+    RankFilter(u32, u32),
+    /// [Surface::transform] of an input of `input_width * input_height` pixels.
+    Transform {
+        matrix: [f32; 6],
+        filter: ResampleFilter,
+        edge_mode: EdgeMode,
+        input_width: u32,
+        input_height: u32,
+    },
+}
+
+impl FilterPrimitive {
+    /// Returns the transform that resizes an input of `input_width * input_height` pixels to
+    /// `width * height` the way `ImageEditor::resize` does.
+    pub fn resize(
+        input_width: u32,
+        input_height: u32,
+        width: u32,
+        height: u32,
+        filter: ResampleFilter,
+    ) -> Self {
+        Self::Transform {
+            matrix: [
+                width as f32 / input_width as f32,
+                0.0,
+                0.0,
+                height as f32 / input_height as f32,
+                0.0,
+                0.0,
+            ],
+            filter,
+            edge_mode: EdgeMode::Duplicate,
+            input_width,
+            input_height,
+        }
+    }
+
+    /// How many pixels the primitive reads beyond each pixel it writes, horizontally and
+    /// vertically, or `None` for transforms.
+    pub(crate) fn padding(&self) -> Option<(u32, u32)> {
+        match *self {
+            Self::Pointwise => Some((0, 0)),
+            Self::GaussianBlur(x, y) => Some(Surface::gaussian_blur_padding(x, y)),
+            // Each pixel is sliced from two grid cells, each blurred with the two cells on either
+            // side, and each cell gathers the pixels within half a cell of its center.
+            Self::BilateralFilter(sigma) if sigma > 0.0 => {
+                let padding = (3.5 * cell_spatial(sigma)).ceil() as u32 + 1;
+                Some((padding, padding))
+            }
+            Self::BilateralFilter(_) => Some((0, 0)),
+            Self::RankFilter(x, y) => Some((x, y)),
+            Self::Transform { .. } => None,
+        }
+    }
+}
+
+impl Bounds for FilterPrimitive {
+    fn bounds(&self, input: Rect) -> Rect {
+        if input.is_empty() {
+            return Rect::default();
+        }
+        let Self::Transform {
+            matrix,
+            filter,
+            edge_mode,
+            input_width,
+            input_height,
+        } = *self
+        else {
+            let (x, y) = self.padding().unwrap_or_default();
+            return input.inflate(x, y);
+        };
+
+        let input = input.intersect(Rect::from_size(input_width, input_height));
+        let Some(inverse) = invert_affine(matrix) else {
+            return Rect::default();
+        };
+        if input.is_empty() {
+            return Rect::default();
+        }
+        if let EdgeMode::Wrap = edge_mode {
+            return EVERYWHERE;
+        }
+        let (support_x, support_y) = transform_support(inverse, filter);
+        // The sample positions whose kernel reaches the input pixels. Edge pixels repeated by
+        // EdgeMode::Duplicate reach samples arbitrarily far outside the input.
+        let repeats = matches!(edge_mode, EdgeMode::Duplicate);
+        let source = |start: u32, end: u32, size: u32, support: f32| {
+            (
+                if repeats && start == 0 {
+                    -UNBOUNDED
+                } else {
+                    start as f32 + 0.5 - support
+                },
+                if repeats && end == size {
+                    UNBOUNDED
+                } else {
+                    end as f32 - 0.5 + support
+                },
+            )
+        };
+        let (left, right) = source(input.x, input.right(), input_width, support_x);
+        let (top, bottom) = source(input.y, input.bottom(), input_height, support_y);
+
+        // The output pixels whose center maps onto those samples.
+        let [a, b, c, d, e, f] = matrix;
+        let corners = [(left, top), (right, top), (left, bottom), (right, bottom)];
+        let (x, width) = clip_range(covered(corners.map(|(x, y)| a * x + c * y + e - 0.5), 0.0));
+        let (y, height) = clip_range(covered(corners.map(|(x, y)| b * x + d * y + f - 0.5), 0.0));
+        Rect::new(x, y, width, height)
+    }
+
+    fn required_input_rect(&self, output: Rect) -> Rect {
+        if output.is_empty() {
+            return Rect::default();
+        }
+        let Self::Transform {
+            matrix,
+            filter,
+            edge_mode,
+            input_width,
+            input_height,
+        } = *self
+        else {
+            let (x, y) = self.padding().unwrap_or_default();
+            return output.inflate(x, y);
+        };
+
+        let Some(inverse) = invert_affine(matrix) else {
+            return Rect::default();
+        };
+        if input_width == 0 || input_height == 0 {
+            return Rect::default();
+        }
+        let (support_x, support_y) = transform_support(inverse, filter);
+        // The centers of the first and last output pixels, mapped back to the input, read the
+        // pixels whose center is within the support of the kernel.
+        let (left, right) = (output.x as f32 + 0.5, output.right() as f32 - 0.5);
+        let (top, bottom) = (output.y as f32 + 0.5, output.bottom() as f32 - 0.5);
+        let corners = [(left, top), (right, top), (left, bottom), (right, bottom)];
+        let [a, b, c, d, e, f] = inverse;
+        let xs = covered(corners.map(|(x, y)| a * x + c * y + e - 0.5), support_x);
+        let ys = covered(corners.map(|(x, y)| b * x + d * y + f - 0.5), support_y);
+        let (x, width) = edge_range(xs, input_width, edge_mode);
+        let (y, height) = edge_range(ys, input_height, edge_mode);
+        Rect::new(x, y, width, height)
+    }
+}
+
+impl<T: Bounds> Bounds for [T] {
+    fn bounds(&self, input: Rect) -> Rect {
+        self.iter()
+            .fold(input, |rect, primitive| primitive.bounds(rect))
+    }
+
+    fn required_input_rect(&self, output: Rect) -> Rect {
+        self.iter().rev().fold(output, |rect, primitive| {
+            primitive.required_input_rect(rect)
+        })
+    }
+}
+
+/// Returns how far the resampling kernel reaches around a sample along each source axis, widened
+/// when downscaling like in [Surface::transform].
+fn transform_support([a, b, c, d, ..]: [f32; 6], filter: ResampleFilter) -> (f32, f32) {
+    let scale_x = kernel_scale(filter, f32::hypot(a, c));
+    let scale_y = kernel_scale(filter, f32::hypot(b, d));
+    (filter.radius() * scale_x, filter.radius() * scale_y)
+}
+
+/// Returns the first and last pixel index, inclusive, within `reach` of the range of `values`.
+fn covered(values: [f32; 4], reach: f32) -> (i64, i64) {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    // Allow for rounding differences with the coordinates computed per pixel.
+    let margin = 1e-3 + min.abs().max(max.abs()) * 4.0 * f32::EPSILON;
+    (
+        (min - reach - margin).ceil() as i64,
+        (max + reach + margin).floor() as i64,
+    )
+}
+
+/// Returns the start and length of the pixels `first..=last`, clipped to `u32`.
+fn clip_range((first, last): (i64, i64)) -> (u32, u32) {
+    let clip = |value: i64| value.clamp(0, u32::MAX as i64) as u32;
+    let start = clip(first);
+    (start, clip(last.saturating_add(1)).saturating_sub(start))
+}
+
+/// Returns the start and length of the input pixels read for the sample indices `first..=last`
+/// along an axis of `size` pixels.
+fn edge_range((first, last): (i64, i64), size: u32, edge_mode: EdgeMode) -> (u32, u32) {
+    let size = size as i64;
+    let (first, last) = match edge_mode {
+        EdgeMode::None => (first.max(0), last.min(size - 1)),
+        EdgeMode::Duplicate => (first.clamp(0, size - 1), last.clamp(0, size - 1)),
+        EdgeMode::Wrap => {
+            let (start, end) = (first.rem_euclid(size), last.rem_euclid(size));
+            if last - first + 1 >= size || start > end {
+                (0, size - 1)
+            } else {
+                (start, end)
+            }
+        }
+    };
+    if first > last {
+        return (0, 0);
+    }
+    (first as u32, (last - first + 1) as u32)
+}
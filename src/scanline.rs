@@ -0,0 +1,414 @@
+use crate::{
+    BlendMode, ColorChannel, ColorInterpolation, ColorMatrix, ColorSpace, CompositeOperator,
+    RowWriter, Surface, SurfaceView,
+    utils::{MAX_KERNEL_SIZE, box_blur_step, calc_kernel_size, pack_pixel, unpack_pixel},
+};
+use alloc::{vec, vec::Vec};
+
+/// A filter that consumes an image one row at a time, for row-at-a-time producers such as
+/// decoders and rasterizers.
+///
+///Rows hold premultiplied pixels in [ColorChannel::RGBA32] order, as for [crate::RowReader].
+///Filters write their output rows to a [RowWriter] as soon as they are ready, so filters can be
+///chained with [RowFilter::rows].
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{BlurRows, ColorMatrix, ColorMatrixRows, RowFilter, RowWriter};
+///
+/// let (width, height) = (64, 48);
+/// let mut sepia = ColorMatrixRows::new(ColorMatrix::sepia(1.0));
+/// let mut blur = BlurRows::new(width, height, 2.0, 2.0);
+/// let mut output: Vec<Vec<u32>> = Vec::new();
+///
+/// let mut blurred = blur.rows(&mut output);
+/// let mut rows = sepia.rows(&mut blurred);
+/// for y in 0..height {
+///     let row = vec![0xff00_80ffu32; width as usize];
+///     rows.write_row(&row).unwrap();
+/// }
+/// rows.finish().unwrap();
+/// blurred.finish().unwrap();
+/// assert_eq!(output.len(), height as usize);
+/// ```
+///
+pub trait RowFilter {
+    /// Filters the next row of the image, writing the rows that are ready to `output`.
+    fn push_row<W: RowWriter>(&mut self, row: &[u32], output: &mut W) -> Result<(), W::Error>;
+
+    /// Writes the rows that are still held back, once the last row has been pushed.
+    fn finish<W: RowWriter>(&mut self, _output: &mut W) -> Result<(), W::Error> {
+        Ok(())
+    }
+
+    /// Returns a [RowWriter] that filters the rows written to it into `output`.
+    fn rows<'a, W: RowWriter>(&'a mut self, output: &'a mut W) -> FilteredRows<'a, Self, W>
+    where
+        Self: Sized,
+    {
+        FilteredRows {
+            filter: self,
+            output,
+        }
+    }
+}
+
+/// A [RowWriter] that runs a [RowFilter] on every row, see [RowFilter::rows].
+#[derive(Debug)]
+pub struct FilteredRows<'a, F, W> {
+    filter: &'a mut F,
+    output: &'a mut W,
+}
+
+impl<F: RowFilter, W: RowWriter> FilteredRows<'_, F, W> {
+    /// Writes the rows the filter still holds back, once the last row has been written.
+    pub fn finish(&mut self) -> Result<(), W::Error> {
+        self.filter.finish(self.output)
+    }
+}
+
+impl<F: RowFilter, W: RowWriter> RowWriter for FilteredRows<'_, F, W> {
+    type Error = W::Error;
+
+    fn write_row(&mut self, row: &[u32]) -> Result<(), Self::Error> {
+        self.filter.push_row(row, self.output)
+    }
+}
+
+fn row_view(row: &[u32], color_space: ColorSpace) -> SurfaceView<'_> {
+    let width = row.len() as u32;
+    SurfaceView::make(row, width, 1, width, Some(ColorChannel::RGBA32))
+        .expect("row holds width pixels")
+        .with_color_space(color_space)
+}
+
+/// Runs `filter` into a surface over `pixels` of one row of `width` pixels.
+fn filter_row(
+    pixels: &mut Vec<u32>,
+    width: usize,
+    color_interpolation: ColorInterpolation,
+    color_space: ColorSpace,
+    filter: impl FnOnce(&mut Surface),
+) -> &[u32] {
+    pixels.resize(width, 0);
+    let mut surface = Surface::make(
+        pixels,
+        width as u32,
+        1,
+        width as u32,
+        Some(ColorChannel::RGBA32),
+    )
+    .expect("row holds width pixels")
+    .with_color_interpolation(color_interpolation)
+    .with_color_space(color_space);
+    filter(&mut surface);
+    pixels
+}
+
+/// Applies a color matrix to every row as it is pushed, see [Surface::color_transform].
+#[derive(Debug, Clone)]
+pub struct ColorMatrixRows {
+    matrix: ColorMatrix,
+    color_interpolation: ColorInterpolation,
+    color_space: ColorSpace,
+    row: Vec<u32>,
+}
+
+impl ColorMatrixRows {
+    pub fn new(matrix: impl Into<ColorMatrix>) -> Self {
+        Self {
+            matrix: matrix.into(),
+            color_interpolation: ColorInterpolation::default(),
+            color_space: ColorSpace::default(),
+            row: Vec::new(),
+        }
+    }
+
+    /// Sets the [ColorInterpolation] the matrix is applied in.
+    pub fn with_color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.color_interpolation = color_interpolation;
+        self
+    }
+
+    /// Tags the rows with the color space their pixels are encoded in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+}
+
+impl RowFilter for ColorMatrixRows {
+    fn push_row<W: RowWriter>(&mut self, row: &[u32], output: &mut W) -> Result<(), W::Error> {
+        let (input, matrix) = (row_view(row, self.color_space), self.matrix);
+        output.write_row(filter_row(
+            &mut self.row,
+            row.len(),
+            self.color_interpolation,
+            self.color_space,
+            |surface| Surface::color_transform(input, surface, matrix),
+        ))
+    }
+}
+
+/// Blends rows of two images as they are pushed, see [Surface::blend].
+#[derive(Debug, Clone)]
+pub struct BlendRows {
+    mode: BlendMode,
+    color_interpolation: ColorInterpolation,
+    color_space: ColorSpace,
+    row: Vec<u32>,
+}
+
+impl BlendRows {
+    pub fn new(mode: BlendMode) -> Self {
+        Self {
+            mode,
+            color_interpolation: ColorInterpolation::default(),
+            color_space: ColorSpace::default(),
+            row: Vec::new(),
+        }
+    }
+
+    /// Sets the [ColorInterpolation] the rows are blended in.
+    pub fn with_color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.color_interpolation = color_interpolation;
+        self
+    }
+
+    /// Tags the rows with the color space their pixels are encoded in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Blends the next row of the source over the next row of the backdrop, writing the result to
+    /// `output` right away.
+    pub fn push_rows<W: RowWriter>(
+        &mut self,
+        source: &[u32],
+        backdrop: &[u32],
+        output: &mut W,
+    ) -> Result<(), W::Error> {
+        let (source, backdrop) = (
+            row_view(source, self.color_space),
+            row_view(backdrop, self.color_space),
+        );
+        let mode = self.mode;
+        output.write_row(filter_row(
+            &mut self.row,
+            source.width.min(backdrop.width) as usize,
+            self.color_interpolation,
+            self.color_space,
+            |surface| Surface::blend(source, backdrop, surface, mode),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compositing {
+    Operator(CompositeOperator),
+    Arithmetic([f32; 4]),
+}
+
+/// Composites rows of two images as they are pushed, see [Surface::composite] and
+/// [Surface::composite_arithmetic].
+#[derive(Debug, Clone)]
+pub struct CompositeRows {
+    compositing: Compositing,
+    color_interpolation: ColorInterpolation,
+    color_space: ColorSpace,
+    row: Vec<u32>,
+}
+
+impl CompositeRows {
+    pub fn new(operator: CompositeOperator) -> Self {
+        Self::with_compositing(Compositing::Operator(operator))
+    }
+
+    /// Composites with the SVG `arithmetic` operator, `k1 * in1 * in2 + k2 * in1 + k3 * in2 + k4`.
+    pub fn arithmetic(k1: f32, k2: f32, k3: f32, k4: f32) -> Self {
+        Self::with_compositing(Compositing::Arithmetic([k1, k2, k3, k4]))
+    }
+
+    fn with_compositing(compositing: Compositing) -> Self {
+        Self {
+            compositing,
+            color_interpolation: ColorInterpolation::default(),
+            color_space: ColorSpace::default(),
+            row: Vec::new(),
+        }
+    }
+
+    /// Sets the [ColorInterpolation] the rows are composited in.
+    pub fn with_color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.color_interpolation = color_interpolation;
+        self
+    }
+
+    /// Tags the rows with the color space their pixels are encoded in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Composites the next row of the source with the next row of the backdrop, writing the
+    /// result to `output` right away.
+    pub fn push_rows<W: RowWriter>(
+        &mut self,
+        source: &[u32],
+        backdrop: &[u32],
+        output: &mut W,
+    ) -> Result<(), W::Error> {
+        let (source, backdrop) = (
+            row_view(source, self.color_space),
+            row_view(backdrop, self.color_space),
+        );
+        let compositing = self.compositing;
+        output.write_row(filter_row(
+            &mut self.row,
+            source.width.min(backdrop.width) as usize,
+            self.color_interpolation,
+            self.color_space,
+            |surface| match compositing {
+                Compositing::Operator(operator) => {
+                    Surface::composite(source, backdrop, surface, operator)
+                }
+                Compositing::Arithmetic([k1, k2, k3, k4]) => {
+                    Surface::composite_arithmetic(source, backdrop, surface, k1, k2, k3, k4)
+                }
+            },
+        ))
+    }
+}
+
+/// One vertical box blur pass over a stream of rows.
+///
+///Each column keeps the pixels inside its window in a ring of `kernel` pixels, like the vertical
+///pass of [Surface::gaussian_blur], so the pass holds `kernel` rows.
+#[derive(Debug, Clone)]
+struct VerticalPass {
+    kernel: u32,
+    height: u32,
+    offset: u32,
+    sums: Vec<[u32; 4]>,
+    ring: Vec<[u32; 4]>,
+    row: Vec<u32>,
+}
+
+impl VerticalPass {
+    fn new(width: u32, height: u32, kernel: u32) -> Self {
+        Self {
+            kernel,
+            height,
+            offset: 0,
+            sums: vec![[0; 4]; width as usize],
+            ring: vec![[0; 4]; width as usize * kernel as usize],
+            row: vec![0; width as usize],
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.offset >= self.height + self.kernel
+    }
+
+    /// Moves the windows one row down, loading `row` while inside the image, and returns the row
+    /// the windows are centered on once it is complete.
+    fn step(&mut self, row: Option<&[u32]>) -> Option<&[u32]> {
+        let mut ready = false;
+        let columns = self
+            .sums
+            .iter_mut()
+            .zip(self.ring.chunks_exact_mut(self.kernel as usize))
+            .zip(&mut self.row);
+        for (x, ((sum, ring), pixel)) in columns.enumerate() {
+            let load = || row.map_or([0; 4], |row| unpack_pixel(&row[x], ColorChannel::RGBA32));
+            let step = box_blur_step(sum, ring, self.kernel, self.offset, self.height, load);
+            if let Some((_, [r, g, b, a])) = step {
+                *pixel = pack_pixel(r, g, b, a, ColorChannel::RGBA32);
+                ready = true;
+            }
+        }
+        self.offset += 1;
+        ready.then_some(&self.row)
+    }
+}
+
+/// Pushes `row` through the vertical `passes`, writing the rows that come out to `output`.
+fn push_vertical<W: RowWriter>(
+    passes: &mut [VerticalPass],
+    row: &[u32],
+    output: &mut W,
+) -> Result<(), W::Error> {
+    match passes.split_first_mut() {
+        None => output.write_row(row),
+        Some((pass, rest)) => match pass.step(Some(row)) {
+            Some(row) => push_vertical(rest, row, output),
+            None => Ok(()),
+        },
+    }
+}
+
+/// Applies a Gaussian blur to rows as they are pushed, see [Surface::gaussian_blur].
+///
+///Each row is blurred horizontally as soon as it is pushed. The vertical box blur passes hold
+///`kernel_height` rows each, and write each row once the rows below it that it depends on have
+///been pushed. The result is the same as blurring the whole image in
+///[ColorInterpolation::SRGB].
+#[derive(Debug, Clone)]
+pub struct BlurRows {
+    std_deviation_x: f32,
+    passes: Vec<VerticalPass>,
+    row: Vec<u32>,
+}
+
+impl BlurRows {
+    /// # Arguments
+    /// * `width` - The width of the rows in pixels.
+    /// * `height` - The number of rows that will be pushed.
+    /// * `std_deviation_x` - The standard deviation of the blur along the X axis.
+    /// * `std_deviation_y` - The standard deviation of the blur along the Y axis.
+    ///
+    pub fn new(width: u32, height: u32, std_deviation_x: f32, std_deviation_y: f32) -> Self {
+        if std_deviation_x < 0.0 || std_deviation_y < 0.0 {
+            panic!("standard deviation can not be less than zero.")
+        }
+        let kernel_height = calc_kernel_size(std_deviation_y)
+            .min(MAX_KERNEL_SIZE)
+            .min(height);
+        let passes = match kernel_height {
+            0 => Vec::new(),
+            kernel => vec![VerticalPass::new(width, height, kernel); 3],
+        };
+        Self {
+            std_deviation_x,
+            passes,
+            row: Vec::new(),
+        }
+    }
+}
+
+impl RowFilter for BlurRows {
+    fn push_row<W: RowWriter>(&mut self, row: &[u32], output: &mut W) -> Result<(), W::Error> {
+        let (input, std_deviation) = (row_view(row, ColorSpace::Srgb), self.std_deviation_x);
+        let row = filter_row(
+            &mut self.row,
+            row.len(),
+            ColorInterpolation::SRGB,
+            ColorSpace::Srgb,
+            |surface| Surface::blur_horizontal(input, surface, std_deviation),
+        );
+        push_vertical(&mut self.passes, row, output)
+    }
+
+    fn finish<W: RowWriter>(&mut self, output: &mut W) -> Result<(), W::Error> {
+        for index in 0..self.passes.len() {
+            let (pass, rest) = self.passes[index..].split_first_mut().expect("index < len");
+            while !pass.is_done() {
+                if let Some(row) = pass.step(None) {
+                    push_vertical(rest, row, output)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,57 @@
-use crate::{error::SurfaceError, utils::*};
-use image::DynamicImage;
-use std::{cell::RefCell, rc::Rc};
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{
+    ColorMatrix, Lut3d, LutInterpolation, Rect, SurfaceBuf, SurfaceF32, SurfaceView,
+    adjust::{
+        adjust_pixels, apply_tables, curve_table, hsl_adjust, levels_table, shadows_highlights,
+        temperature_tint, vibrance,
+    },
+    bilateral::bilateral_grid,
+    color_space::conversion_matrix,
+    error::SurfaceError,
+    linear::LinearBuffer,
+    rank::rank_filter,
+    transform::{affine_transform, invert_affine},
+    utils::*,
+};
+#[cfg(feature = "image")]
+use image::{DynamicImage, RgbaImage};
+
+/// Resolves the [ColorChannel] of both surfaces to their [crate::PixelFormat] once, so `$body`
+/// is monomorphized for each combination instead of matching on the channel for every pixel.
+macro_rules! with_channel_formats {
+    ($input:ident, $output:ident, |$i:pat_param, $o:pat_param| $body:block) => {{
+        use crate::format::{ChannelSurface, ChannelView};
+        with_channel_formats!(@format $input.channel, Input, {
+            with_channel_formats!(@format $output.channel, Output, {
+                let ($i, $o) = (
+                    ChannelView::<Input>::new(&$input),
+                    ChannelSurface::<Output>::new($output),
+                );
+                $body
+            })
+        })
+    }};
+    (@format $channel:expr, $format:ident, $body:block) => {
+        match $channel {
+            ColorChannel::ARGB32 => {
+                type $format = crate::format::Argb8;
+                $body
+            }
+            ColorChannel::RGBA32 => {
+                type $format = crate::format::Rgba8;
+                $body
+            }
+            ColorChannel::BGRA32 => {
+                type $format = crate::format::Bgra8;
+                $body
+            }
+        }
+    };
+}
 
-const MAX_KERNEL_SIZE: u32 = 512;
 /// Blend modes for combining source and backdrop surfaces.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlendMode {
     /// Standard alpha compositing (source over backdrop)
     Normal,
@@ -33,7 +80,7 @@ pub enum BlendMode {
 }
 
 /// Compositing operators for combining source and backdrop surfaces.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompositeOperator {
     /// Display source over backdrop
     Over,
@@ -47,11 +94,111 @@ pub enum CompositeOperator {
     Xor,
 }
 
-/// Primary use is to deserialize the u32 pixel into sub pixel as ARGB32 or RGBA32
+/// Resampling filters used when transforming a surface.
+#[derive(Debug, Clone, Copy)]
+pub enum ResampleFilter {
+    /// Picks the closest source pixel
+    Nearest,
+    /// Linear interpolation between the 2x2 closest source pixels
+    Bilinear,
+    /// Catmull-Rom cubic interpolation over the 4x4 closest source pixels
+    Bicubic,
+    /// Windowed sinc interpolation over the 6x6 closest source pixels
+    Lanczos,
+}
+
+/// Determines how source pixels outside the surface are sampled.
 #[derive(Debug, Clone, Copy)]
+pub enum EdgeMode {
+    /// Pixels outside the surface are transparent black
+    None,
+    /// Pixels outside the surface repeat the nearest edge pixel
+    Duplicate,
+    /// Pixels outside the surface wrap around to the opposite edge
+    Wrap,
+}
+
+/// Primary use is to deserialize the u32 pixel into sub pixel as ARGB32 or RGBA32
+///
+/// Each channel is resolved to its [crate::PixelFormat] once per operation. Use
+/// [crate::FormatSurface] for other layouts such as RGB or RGB565.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorChannel {
+    /// Bytes ordered alpha, red, green, blue, see [crate::Argb8]
     ARGB32,
+    /// Bytes ordered red, green, blue, alpha, see [crate::Rgba8]
     RGBA32,
+    /// Bytes ordered blue, green, red, alpha, see [crate::Bgra8]
+    ///
+    /// On little-endian targets this is a native `0xAARRGGBB` integer, as used by the C library.
+    BGRA32,
+}
+
+/// The color space in which filter primitives interpolate and blend colors.
+///
+/// Mirrors the `color-interpolation-filters` property of SVG. Pixels are always stored in sRGB.
+/// With [ColorInterpolation::LinearRGB] the inputs are converted to premultiplied linear light in
+/// floating point, filtered, and converted back to sRGB once when stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorInterpolation {
+    /// Operate directly on the stored sRGB values
+    #[default]
+    SRGB,
+    /// Operate on linear light, as browsers do by default for SVG filters
+    LinearRGB,
+}
+
+/// RGB color spaces that a surface can be tagged with.
+///
+/// All spaces share the D65 white point. Luminance based operations, such as saturate, hue
+/// rotate, grayscale and luminance to alpha, use coefficients derived from the primaries of the
+/// space, and [Surface::convert_color_space] maps colors between spaces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// sRGB (IEC 61966-2-1), the default for untagged images
+    #[default]
+    Srgb,
+    /// Display P3, with DCI-P3 primaries and the sRGB transfer function
+    DisplayP3,
+    /// ITU-R BT.2020 primaries with the BT.2020 transfer function
+    Rec2020,
+    /// Adobe RGB (1998)
+    AdobeRgb,
+}
+
+/// Hue ranges that [Surface::hsl_adjust] adjusts independently.
+///
+/// Each range is centered on its hue, and colors between two centers receive a linear blend of
+/// both adjustments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HueRange {
+    /// Centered on 0 degrees
+    Red,
+    /// Centered on 30 degrees
+    Orange,
+    /// Centered on 60 degrees
+    Yellow,
+    /// Centered on 120 degrees
+    Green,
+    /// Centered on 180 degrees
+    Aqua,
+    /// Centered on 240 degrees
+    Blue,
+    /// Centered on 270 degrees
+    Purple,
+    /// Centered on 300 degrees
+    Magenta,
+}
+
+/// Hue, saturation and lightness adjustment of one [HueRange].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HslAdjustment {
+    /// Hue shift in degrees
+    pub hue: f32,
+    /// Relative change of saturation, in [-1, 1] (-1 removes the color, 0 for unchanged)
+    pub saturation: f32,
+    /// Change of lightness, in [-1, 1] (-1 toward black, 1 toward white, 0 for unchanged)
+    pub lightness: f32,
 }
 
 // OLD(Represents a 2D image surface in ARGB32 premultiplied format.)
@@ -68,7 +215,7 @@ pub struct Surface<'a> {
     /// Pointer to the pixel buffer.
     ///
     /// Must point to at least `stride * height` elements in ARGB32 premultiplied format.
-    pub(crate) pixels: &'a mut [u32],
+    pub(crate) pixels: SurfacePixels<'a>,
 
     /// The width of the surface in pixels.
     pub(crate) width: u32,
@@ -84,11 +231,131 @@ pub struct Surface<'a> {
     /// Sets the color channel as `ARGB` or `RGBA`
     /// as image crate reads as RGBA channel and other cases use ARGB channel
     pub(crate) channel: ColorChannel,
+
+    /// The color space that filters writing into this surface operate in.
+    pub(crate) color_interpolation: ColorInterpolation,
+
+    /// The color space of the pixel data.
+    pub(crate) color_space: ColorSpace,
+}
+
+/// The pixels of a [Surface], borrowed from a buffer or owned by it.
+#[derive(Debug)]
+pub(crate) enum SurfacePixels<'a> {
+    Borrowed(&'a mut [u32]),
+    /// A copy of an image whose bytes are not aligned for `u32`, see [ImageSurface].
+    #[cfg(feature = "image")]
+    Owned(Vec<u32>),
+}
+
+impl<'a> From<&'a mut [u32]> for SurfacePixels<'a> {
+    fn from(pixels: &'a mut [u32]) -> Self {
+        Self::Borrowed(pixels)
+    }
+}
+
+impl core::ops::Deref for SurfacePixels<'_> {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        match self {
+            Self::Borrowed(pixels) => pixels,
+            #[cfg(feature = "image")]
+            Self::Owned(pixels) => pixels,
+        }
+    }
+}
+
+impl core::ops::DerefMut for SurfacePixels<'_> {
+    fn deref_mut(&mut self) -> &mut [u32] {
+        match self {
+            Self::Borrowed(pixels) => pixels,
+            #[cfg(feature = "image")]
+            Self::Owned(pixels) => pixels,
+        }
+    }
+}
+
+/// A [Surface] over the pixels of an image, returned by [Surface::try_from_image].
+///
+///Dereferences to the surface, so it is passed to filters like one.
+///
+/// # Write-back
+///If the bytes of the image are not aligned for `u32`, the surface filters a copy of them. The
+///copy only reaches the image when the [ImageSurface] is dropped or [ImageSurface::finish] is
+///called, so:
+/// * the image still holds the old pixels while the [ImageSurface] is alive, and
+/// * results are lost if it is leaked, for example with [core::mem::forget].
+///
+///Call [ImageSurface::finish] before reading the image to make this explicit.
+#[cfg(feature = "image")]
+#[derive(Debug)]
+pub struct ImageSurface<'a> {
+    surface: Surface<'a>,
+    /// The image to write the copied pixels back into, if the surface does not borrow them.
+    image: Option<&'a mut RgbaImage>,
+}
+
+#[cfg(feature = "image")]
+impl ImageSurface<'_> {
+    /// See [Surface::with_color_interpolation].
+    pub fn with_color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.surface.color_interpolation = color_interpolation;
+        self
+    }
+
+    /// See [Surface::with_color_space].
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.surface.color_space = color_space;
+        self
+    }
+
+    /// Returns whether the surface filters a copy of the image instead of its own bytes.
+    pub fn is_copy(&self) -> bool {
+        self.image.is_some()
+    }
+
+    /// Writes the pixels back into the image, if they were copied, and releases the borrow of
+    ///the image.
+    ///
+    ///This is what dropping the [ImageSurface] does, spelled out at the point where the image is
+    ///read again.
+    pub fn finish(self) {}
+
+    /// Writes the copied pixels back into the image.
+    fn write_back(&mut self) {
+        if let Some(image) = &mut self.image {
+            image.copy_from_slice(bytemuck::cast_slice(&self.surface.pixels));
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl<'a> core::ops::Deref for ImageSurface<'a> {
+    type Target = Surface<'a>;
+
+    fn deref(&self) -> &Surface<'a> {
+        &self.surface
+    }
+}
+
+#[cfg(feature = "image")]
+impl<'a> core::ops::DerefMut for ImageSurface<'a> {
+    fn deref_mut(&mut self) -> &mut Surface<'a> {
+        &mut self.surface
+    }
+}
+
+#[cfg(feature = "image")]
+impl Drop for ImageSurface<'_> {
+    fn drop(&mut self) {
+        self.write_back();
+    }
 }
 
 impl<'a> Surface<'a> {
     pub fn pixels(&'a mut self) -> &'a mut [u32] {
-        self.pixels
+        &mut self.pixels
     }
 
     pub fn width(&self) -> u32 {
@@ -99,34 +366,237 @@ impl<'a> Surface<'a> {
         self.height
     }
 
+    /// Borrows the surface as a read-only [SurfaceView], to use as a filter input.
+    ///
+    ///Any number of views of the same surface can be used at once, for example to blend a
+    ///surface with itself.
+    pub fn view(&self) -> SurfaceView<'_> {
+        SurfaceView {
+            pixels: &self.pixels,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            channel: self.channel,
+            color_space: self.color_space,
+        }
+    }
+
+    pub fn color_interpolation(&self) -> ColorInterpolation {
+        self.color_interpolation
+    }
+
+    /// Sets the color space used by color transforms, blurs, blends and composites that write into
+    /// this surface.
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::{ColorInterpolation, Surface};
+    /// let mut pixels = vec![0u32; 16];
+    /// let surface = Surface::make(&mut pixels, 4, 4, 4, None)
+    ///     .expect("ERROR: Failed to make surface struct.")
+    ///     .with_color_interpolation(ColorInterpolation::LinearRGB);
+    /// ```
+    ///
+    pub fn with_color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.color_interpolation = color_interpolation;
+        self
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Tags the surface with the color space its pixels are encoded in.
+    ///
+    ///This does not change the pixels, use [Surface::convert_color_space] to convert them.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Creates a surface over the pixels of an image, which must be 8-bit RGBA.
+    ///
+    ///Use [Surface::try_from_image] instead, which converts any image and handles buffers that
+    ///are not aligned for `u32`.
+    ///
+    /// # Panics
+    ///If the image is not 8-bit RGBA, its bytes are not aligned for `u32`, or it has more than
+    ///`u32::MAX` pixels.
     #[cfg(feature = "image")]
+    #[deprecated(
+        since = "0.4.1",
+        note = "panics on failure, use `Surface::try_from_image`"
+    )]
     pub fn from_image(image: &'a mut DynamicImage) -> Self {
-        let width = image.width();
-        let height = image.height();
-        let stride = image.width();
-        // dbg!("[width: {}, height: {}]", width, height);
-        let pixels = {
-            // SAFETY: The caller needs to convert the DynamicImage into a Rgba8Image
-            let raw_u8 = image.as_mut_rgba8().unwrap();
-            // dbg!("length of rau_u8: {}", raw_u8.len());
-            let len = raw_u8.len() / 4;
-            // dbg!("length of rau_u8/4: {}", len);
-            assert_eq!((width * height) as usize, len);
-            let ptr_u32 = raw_u8.as_ptr() as *mut u32;
-            // SAFETY: Converting &[u8] to &[u32]
-            unsafe { std::slice::from_raw_parts_mut(ptr_u32, len) }
+        let buffer = image
+            .as_mut_rgba8()
+            .expect("ERROR: Image is not 8-bit RGBA, use Surface::try_from_image.");
+        let (width, height) = buffer.dimensions();
+        let pixels = bytemuck::try_cast_slice_mut::<u8, u32>(buffer)
+            .expect("ERROR: Image is not aligned for u32, use Surface::try_from_image.");
+        Self::make(pixels, width, height, width, Some(ColorChannel::RGBA32))
+            .expect("ERROR: Failed to make surface from image.")
+    }
+
+    /// Creates a surface over the pixels of an image, converting it to 8-bit RGBA first if needed.
+    ///
+    ///The bytes are borrowed in place as [ColorChannel::RGBA32] pixels, so filters writing into
+    ///the surface write into the image. They are not premultiplied. If the buffer is not aligned
+    ///for `u32`, the surface filters a copy of the pixels instead, which is written back into the
+    ///image by [ImageSurface::finish] or when the [ImageSurface] is dropped, see its
+    ///[write-back](ImageSurface#write-back) rules.
+    ///
+    /// # Returns
+    /// [ImageSurface] over the image, or [SurfaceError::TooManyPixels] if the image has more than
+    ///`u32::MAX` pixels, which surfaces can not index.
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::Surface;
+    /// let mut image = image::DynamicImage::new_rgb8(4, 4);
+    /// let surface = Surface::try_from_image(&mut image).expect("ERROR: Failed to make surface struct.");
+    /// surface.finish();
+    /// assert!(image.as_rgba8().is_some());
+    /// ```
+    ///
+    #[cfg(feature = "image")]
+    pub fn try_from_image(image: &'a mut DynamicImage) -> Result<ImageSurface<'a>, SurfaceError> {
+        let (width, height) = (image.width(), image.height());
+        if width.checked_mul(height).is_none() {
+            return Err(SurfaceError::TooManyPixels);
+        }
+        if !matches!(image, DynamicImage::ImageRgba8(_)) {
+            *image = DynamicImage::ImageRgba8(image.to_rgba8());
+        }
+        let Some(buffer) = image.as_mut_rgba8() else {
+            unreachable!("image was converted to rgba8")
+        };
+        let (pixels, image) = match bytemuck::try_cast_slice::<u8, u32>(buffer) {
+            Ok(_) => (
+                SurfacePixels::Borrowed(bytemuck::cast_slice_mut(buffer)),
+                None,
+            ),
+            Err(_) => (
+                SurfacePixels::Owned(bytemuck::pod_collect_to_vec(buffer)),
+                Some(buffer),
+            ),
         };
-        Surface {
+        let surface = Self {
             pixels,
             width,
             height,
-            stride,
+            stride: width,
             channel: ColorChannel::RGBA32,
+            color_interpolation: ColorInterpolation::SRGB,
+            color_space: ColorSpace::Srgb,
+        };
+        Ok(ImageSurface { surface, image })
+    }
+
+    /// Runs a filter with the same surface as input and output.
+    ///
+    ///The filter receives a [SurfaceView] of a snapshot of the pixels as its input and writes into
+    ///`surface`, so spatial filters such as blurs and transforms read the original pixels, not the
+    ///ones already written. The snapshot costs one copy of the surface.
+    /// # Arguments
+    /// * `surface` - The surface read by and written to by the filter.
+    /// * `filter` - The filter to run, called once with the input view and the output surface.
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::{BlendMode, Surface};
+    /// let mut pixels = vec![0x8080_8080u32; 16];
+    /// let mut surface = Surface::make(&mut pixels, 4, 4, 4, None)
+    ///     .expect("ERROR: Failed to make surface struct.");
+    /// Surface::in_place(&mut surface, |input, output| {
+    ///     Surface::gaussian_blur(input, output, 1.0, 1.0)
+    /// });
+    /// Surface::in_place(&mut surface, |input, output| {
+    ///     Surface::blend(input, input, output, BlendMode::Multiply)
+    /// });
+    /// ```
+    ///
+    pub fn in_place(surface: &mut Self, filter: impl FnOnce(SurfaceView<'_>, &mut Self)) {
+        let len = (surface.stride * surface.height) as usize;
+        let pixels = surface.pixels[..len.min(surface.pixels.len())].to_vec();
+        let input = SurfaceView {
+            pixels: &pixels,
+            ..surface.view()
+        };
+        filter(input, surface);
+    }
+
+    /// Runs `filter` on a region of interest, such as a damaged rectangle or an SVG filter region.
+    ///
+    ///Only the pixels of `output` inside `region` are written. The filter reads `region` grown
+    ///by `padding` pixels on each side, so neighborhood filters see the same source pixels as
+    ///when filtering the whole surface and the result has no seams along the region edges.
+    ///The padding needed by [Surface::gaussian_blur] is given by [Surface::gaussian_blur_padding],
+    ///and the rank filters need their radius.
+    ///
+    ///Pixel positions passed to `filter` are relative to the padded region.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `region` - The rectangle to write, clipped to both surfaces.
+    /// * `padding` - The number of extra input pixels to read left and right, and above and below the region.
+    /// * `filter` - The filter primitive to run.
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::{ColorChannel, Rect, Surface, SurfaceBuf};
+    /// let mut image = SurfaceBuf::new(64, 64, ColorChannel::RGBA32);
+    /// image.fill([255, 128, 0, 255]);
+    /// let mut output = image.clone();
+    /// let padding = Surface::gaussian_blur_padding(3.0, 3.0);
+    /// Surface::filter_region(&image, &mut output.as_surface(), Rect::new(8, 8, 16, 16), padding, |input, output| {
+    ///     Surface::gaussian_blur(input, output, 3.0, 3.0)
+    /// });
+    /// ```
+    ///
+    pub fn filter_region<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        region: Rect,
+        (padding_x, padding_y): (u32, u32),
+        filter: impl FnOnce(SurfaceView<'_>, &mut Surface<'_>),
+    ) {
+        let input = input.into();
+        let bounds = Rect::from_size(
+            input.width.min(output.width),
+            input.height.min(output.height),
+        );
+        let region = region.intersect(bounds);
+        if region.is_empty() {
+            return;
         }
+        let source = region
+            .inflate(padding_x, padding_y)
+            .intersect(Rect::from_size(input.width, input.height));
+        let mut result = SurfaceBuf::new(source.width, source.height, output.channel)
+            .with_color_interpolation(output.color_interpolation)
+            .with_color_space(output.color_space);
+        filter(
+            input.make_sub(source.x, source.y, source.width, source.height),
+            &mut result.as_surface(),
+        );
+        let result = result.as_view().make_sub(
+            region.x - source.x,
+            region.y - source.y,
+            region.width,
+            region.height,
+        );
+        let mut target = Surface {
+            pixels: (&mut output.pixels[(region.y * output.stride + region.x) as usize..]).into(),
+            width: region.width,
+            height: region.height,
+            ..*output
+        };
+        copy_pixels(&result, &mut target);
     }
 
     pub fn copy_output_into_input(input: &mut Self, output: &Self) {
-        input.pixels.copy_from_slice(output.pixels);
+        input.pixels.copy_from_slice(&output.pixels);
     }
     /// Creates a surface from a raw pixel buffer.
     ///
@@ -161,15 +631,77 @@ impl<'a> Surface<'a> {
             Err(SurfaceError::StrideLessThanWidth)
         } else {
             Ok(Self {
-                pixels,
+                pixels: pixels.into(),
                 width,
                 height,
                 stride,
                 channel: channel.unwrap_or(ColorChannel::ARGB32),
+                color_interpolation: ColorInterpolation::SRGB,
+                color_space: ColorSpace::Srgb,
             })
         }
     }
 
+    /// Creates a surface over a raw byte buffer, such as a framebuffer or a GPU readback.
+    ///
+    ///The bytes are filtered in place, 4 per pixel in the order of `channel`. Rows may be padded,
+    ///and the last row only needs `width * 4` bytes. The buffer must be aligned for `u32` and the
+    ///stride a multiple of 4 bytes, use [crate::FormatSurface] for any other layout.
+    /// # Arguments
+    /// * `bytes` - The pixel buffer in premultiplied format.
+    /// * `width`  - The width of the surface in pixels.
+    /// * `height` - The height of the surface in pixels.
+    /// * `stride_bytes` - The number of bytes per row (must be greater than or equal to `width * 4`).
+    /// * `channel` - The byte order of the pixels.
+    /// # Returns [Surface] representing the given bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use plutofilter_rs::{ColorChannel, Surface};
+    /// let mut frame = vec![0u32; 256 * 100];
+    /// let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut frame);
+    /// let surface = Surface::from_bytes(bytes, 250, 100, 1024, ColorChannel::RGBA32)
+    ///     .expect("ERROR: Failed to make surface struct.");
+    /// ```
+    ///
+    pub fn from_bytes(
+        bytes: &'a mut [u8],
+        width: u32,
+        height: u32,
+        stride_bytes: usize,
+        channel: ColorChannel,
+    ) -> Result<Self, SurfaceError> {
+        let row_bytes = width as usize * 4;
+        if stride_bytes < row_bytes {
+            return Err(SurfaceError::StrideLessThanWidth);
+        }
+        if !stride_bytes.is_multiple_of(4) {
+            return Err(SurfaceError::UnalignedStride);
+        }
+        let stride = u32::try_from(stride_bytes / 4).map_err(|_| SurfaceError::InvalidStride)?;
+        let required = match height {
+            0 => 0,
+            _ => stride_bytes * (height as usize - 1) + row_bytes,
+        };
+        if bytes.len() < required {
+            return Err(SurfaceError::BufferTooShort {
+                required,
+                found: bytes.len(),
+            });
+        }
+        let pixels = bytemuck::try_cast_slice_mut::<u8, u32>(&mut bytes[..required])
+            .map_err(|_| SurfaceError::MisalignedPixels)?;
+        Ok(Self {
+            pixels: pixels.into(),
+            width,
+            height,
+            stride,
+            channel,
+            color_interpolation: ColorInterpolation::SRGB,
+            color_space: ColorSpace::Srgb,
+        })
+    }
+
     /// Creates a subregion of an existing surface.
     ///
     /// # Arguments
@@ -204,8 +736,13 @@ impl<'a> Surface<'a> {
         if y + height > self.height {
             height = self.height - y;
         }
+        let (color_interpolation, color_space) = (self.color_interpolation, self.color_space);
         let pixels = &mut (self.pixels[((y * self.stride + x) as usize)..]);
-        Self::make(pixels, width, height, self.stride, Some(self.channel))
+        Self::make(pixels, width, height, self.stride, Some(self.channel)).map(|surface| {
+            surface
+                .with_color_interpolation(color_interpolation)
+                .with_color_space(color_space)
+        })
     }
 
     // ----------------------------------------------------
@@ -223,195 +760,138 @@ impl<'a> Surface<'a> {
     ///    [A']   [m15 m16 m17 m18 m19]   [A]
     ///
     /// The matrix must be provided in row-major order as a flat array of 20 floats.
-    /// The input and output surfaces may refer to the same buffer.
+    /// Use [Surface::in_place] to filter a surface into itself.
+    /// Runs in the [ColorInterpolation] of the output surface.
     /// # Arguments
-    /// * `input`  - The input surface (read-only if different from out).
+    /// * `input`  - The input surface.
     /// * `output` - The output surface.
-    /// * `matrix` - A 5x4 color matrix represented as a 20-element float array.
+    /// * `matrix` - A 5x4 color matrix, either a [ColorMatrix] or a 20-element float array.
     ///
-    pub fn color_transform(input: &mut Self, output: &mut Self, matrix: [f32; 20]) {
-        overlap_surface(input, output);
-        for y in 0..output.height {
-            for x in 0..output.width {
-                let [mut r, mut g, mut b, mut a] = init_load_pixel(input, x, y, input.channel);
-                unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
-
-                let rr = r as f32 * matrix[0]
-                    + g as f32 * matrix[1]
-                    + b as f32 * matrix[2]
-                    + a as f32 * matrix[3]
-                    + matrix[4] * 255.0;
-                let gg = r as f32 * matrix[5]
-                    + g as f32 * matrix[6]
-                    + b as f32 * matrix[7]
-                    + a as f32 * matrix[8]
-                    + matrix[9] * 255.0;
-                let bb = r as f32 * matrix[10]
-                    + g as f32 * matrix[11]
-                    + b as f32 * matrix[12]
-                    + a as f32 * matrix[13]
-                    + matrix[14] * 255.0;
-                let aa = r as f32 * matrix[15]
-                    + g as f32 * matrix[16]
-                    + b as f32 * matrix[17]
-                    + a as f32 * matrix[18]
-                    + matrix[19] * 255.0;
-                r = clamp_pixel(rr as u32);
-                g = clamp_pixel(gg as u32);
-                b = clamp_pixel(bb as u32);
-                a = clamp_pixel(aa as u32);
-
-                premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
-                store_pixel(output, x, y, r, g, b, a);
-            }
+    pub fn color_transform<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        matrix: impl Into<ColorMatrix>,
+    ) {
+        let matrix = matrix.into().to_array();
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
+        if output.color_interpolation == ColorInterpolation::LinearRGB {
+            let mut source = LinearBuffer::from_surface(&input);
+            let mut result = LinearBuffer::new(output.width, output.height);
+            SurfaceF32::color_transform(&mut source.surface(), &mut result.surface(), matrix);
+            result.store(output);
+            return;
         }
+        with_channel_formats!(input, output, |input, mut output| {
+            color_transform_pixels(&input, &mut output, &matrix)
+        });
     }
 
     /// Adjusts the opacity of each pixel by a uniform amount.
     ///
     /// Multiplies the alpha channel by the given amount. The result is clamped to the range [0, 255].
-    /// The input and output surfaces may refer to the same buffer.
+    /// Use [Surface::in_place] to filter a surface into itself.
     ///
     /// # Arguments
     /// * `input`  - The input surface.
     /// * `output` - The output surface.
     /// * `amount` - The opacity multiplier (0 for fully transparent, 1 for unchanged).
     ///
-    pub fn color_transform_opacity(input: &mut Self, output: &mut Self, amount: f32) {
-        let matrix = [
-            1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0,
-            0.0, amount, 0.0,
-        ];
-
-        Self::color_transform(input, output, matrix);
+    pub fn color_transform_opacity<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, opacity_matrix(amount));
     }
 
     /// Adjusts the brightness of each pixel by a uniform amount.
     ///
     /// Multiplies the color channels (red, green, blue) by the given amount. The result is clamped to the range [0, 255].
-    /// The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    /// The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input`  - The input surface.
     /// * `output` - The output surface.
     /// * `amount` - The brightness multiplier (1 for unchanged, <1 to darken, >1 to brighten).
     ///
-    pub fn color_transform_brightness(input: &mut Self, output: &mut Self, amount: f32) {
-        let matrix = [
-            amount, 0.0, 0.0, 0.0, 0.0, 0.0, amount, 0.0, 0.0, 0.0, 0.0, 0.0, amount, 0.0, 0.0,
-            0.0, 0.0, 0.0, 1.0, 0.0,
-        ];
-        Self::color_transform(input, output, matrix);
+    pub fn color_transform_brightness<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, brightness_matrix(amount));
     }
 
     /// Inverts the color channels of each pixel by a uniform amount.
     ///
     ///Linearly interpolates between the original color and its inverse based on the given amount.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     ///
     /// # Arguments
     /// * `input`  - The input surface.
     /// * `output` - The output surface.
     /// * `amount` - The inversion amount (0 for unchanged, 1 for fully inverted).
     ///
-    pub fn color_transform_invert(input: &mut Self, output: &mut Self, amount: f32) {
-        let scale = 1.0 - 2.0 * amount;
-        let matrix = [
-            scale, 0.0, 0.0, 0.0, amount, 0.0, scale, 0.0, 0.0, amount, 0.0, 0.0, scale, 0.0,
-            amount, 0.0, 0.0, 0.0, 1.0, 0.0,
-        ];
-
-        Self::color_transform(input, output, matrix);
+    pub fn color_transform_invert<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, invert_matrix(amount));
     }
 
     /// Adjusts the contrast of each pixel by a uniform amount.
     ///
     ///Scales the color channels (red, green, blue) away from or toward the midpoint (0.5) by the given amount.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input` - The input surface.
     /// * `output` - The output surface.
     /// * `amount` - The contrast multiplier (1 for unchanged, <1 to reduce contrast, >1 to increase contrast).
     ///
-    pub fn color_transform_contrast(input: &mut Self, output: &mut Self, amount: f32) {
-        let offset = (1.0 - amount) * 0.5;
-        let matrix = [
-            amount, 0.0, 0.0, 0.0, offset, 0.0, amount, 0.0, 0.0, offset, 0.0, 0.0, amount, 0.0,
-            offset, 0.0, 0.0, 0.0, 1.0, 0.0,
-        ];
-
-        Self::color_transform(input, output, matrix);
+    pub fn color_transform_contrast<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, contrast_matrix(amount));
     }
 
     /// Adjusts the saturation of each pixel by a uniform amount.
     ///
     ///Modifies the intensity of color while preserving luminance. A value of 0 produces a fully desaturated result.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input`  - The input surface.
     /// * `output` - The output surface.
     /// * `amount` - The saturation multiplier (1 for unchanged, 0 for fully desaturated, >1 to increase saturation).
     ///
-    pub fn color_transform_saturate(input: &mut Self, output: &mut Self, amount: f32) {
-        let matrix = [
-            0.213 + 0.787 * amount,
-            0.715 - 0.715 * amount,
-            0.072 - 0.072 * amount,
-            0.0,
-            0.0,
-            0.213 - 0.213 * amount,
-            0.715 + 0.285 * amount,
-            0.072 - 0.072 * amount,
-            0.0,
-            0.0,
-            0.213 - 0.213 * amount,
-            0.715 - 0.715 * amount,
-            0.072 + 0.928 * amount,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            1.0,
-            0.0,
-        ];
-
+    pub fn color_transform_saturate<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        let input = input.into();
+        let matrix = input.color_space.saturate_matrix(amount);
         Self::color_transform(input, output, matrix);
     }
 
     /// Converts each pixel toward grayscale by a uniform amount.
     ///
     ///Reduces the influence of color while preserving luminance. A value of 1 produces a fully grayscale result.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// *`input` - The input surface.
     /// *`output` - The output surface.
     /// *`amount` -  The grayscale amount (0 for unchanged, 1 for fully grayscale).
     ///
-    pub fn color_transform_grayscale(input: &mut Self, output: &mut Self, amount: f32) {
-        let inv_amount = 1.0 - amount;
-        let matrix = [
-            inv_amount + amount * 0.2126,
-            amount * 0.7152,
-            amount * 0.0722,
-            0.0,
-            0.0,
-            amount * 0.2126,
-            inv_amount + amount * 0.7152,
-            amount * 0.0722,
-            0.0,
-            0.0,
-            amount * 0.2126,
-            amount * 0.7152,
-            inv_amount + amount * 0.0722,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            1.0,
-            0.0,
-        ];
-
+    pub fn color_transform_grayscale<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        let input = input.into();
+        let matrix = input.color_space.grayscale_matrix(amount);
         Self::color_transform(input, output, matrix);
     }
 
@@ -419,79 +899,71 @@ impl<'a> Surface<'a> {
     ///
     ///Shifts the colors toward warm brown tones, simulating the appearance of old photographs.
     ///A value of 1 produces a fully sepia-toned result. The alpha channel is not affected.
-    ///The input and output surfaces may refer to the same buffer.
+    ///Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input` - The input surface.
     /// * `output` - The output surface.
     /// * `amount` - The sepia amount (0 for unchanged, 1 for fully sepia).
     ///
-    pub fn color_transform_sepia(input: &mut Self, output: &mut Self, amount: f32) {
-        let inv_amount = 1.0 - amount;
-        let matrix = [
-            0.393 + 0.607 * inv_amount,
-            0.769 - 0.769 * inv_amount,
-            0.189 - 0.189 * inv_amount,
-            0.0,
-            0.0,
-            0.349 - 0.349 * inv_amount,
-            0.686 + 0.314 * inv_amount,
-            0.168 - 0.168 * inv_amount,
-            0.0,
-            0.0,
-            0.272 - 0.272 * inv_amount,
-            0.534 - 0.534 * inv_amount,
-            0.131 + 0.869 * inv_amount,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            1.0,
-            0.0,
-        ];
-
-        Self::color_transform(input, output, matrix);
+    pub fn color_transform_sepia<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        amount: f32,
+    ) {
+        Self::color_transform(input, output, sepia_matrix(amount));
     }
 
     /// Rotates the hue of each pixel by a given angle.
     ///
     ///Shifts the hue component of the color while preserving luminance and saturation.
     ///The angle is specified in degrees and wraps around automatically.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input` - The input surface.
     /// * `output` - The output surface.
     /// * `angle` - The hue rotation angle in degrees (0 for unchanged, 360 for full rotation).
     ///
-    pub fn color_transform_hue_rotate(input: &mut Self, output: &mut Self, angle: f32) {
-        let a1 = f32::cos(deg2rad(angle));
-        let a2 = f32::sin(deg2rad(angle));
-        let matrix = [
-            0.213 + a1 * 0.787 - a2 * 0.213,
-            0.715 - a1 * 0.715 - a2 * 0.715,
-            0.072 - a1 * 0.072 + a2 * 0.928,
-            0.0,
-            0.0,
-            0.213 - a1 * 0.213 + a2 * 0.143,
-            0.715 + a1 * 0.285 + a2 * 0.140,
-            0.072 - a1 * 0.072 - a2 * 0.283,
-            0.0,
-            0.0,
-            0.213 - a1 * 0.213 - a2 * 0.787,
-            0.715 - a1 * 0.715 + a2 * 0.715,
-            0.072 + a1 * 0.928 + a2 * 0.072,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            1.0,
-            0.0,
-        ];
-
+    pub fn color_transform_hue_rotate<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        angle: f32,
+    ) {
+        let input = input.into();
+        let matrix = input.color_space.hue_rotate_matrix(angle);
         Self::color_transform(input, output, matrix);
     }
 
+    /// Converts the colors of the input surface into the color space of the output surface.
+    ///
+    ///Each pixel is decoded to linear light, mapped through CIE XYZ into the primaries of the
+    ///output space and encoded with its transfer function. Colors outside the output gamut are clipped.
+    /// # Arguments
+    /// * `input`  - The input surface, tagged with its source color space.
+    /// * `output` - The output surface, tagged with the target color space.
+    ///
+    pub fn convert_color_space<'i>(input: impl Into<SurfaceView<'i>>, output: &mut Self) {
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
+        let (from, to) = (input.color_space, output.color_space);
+        let matrix = conversion_matrix(from, to);
+
+        for y in 0..output.height {
+            for x in 0..output.width {
+                let [mut r, mut g, mut b, mut a] = init_load_pixel(&input, x, y, input.channel);
+                if from != to && a > 0 {
+                    unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+                    let linear = [r, g, b].map(|c| from.decode(c as f32 / 255.0));
+                    [r, g, b] = matrix.map(|row| {
+                        let c = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+                        (to.encode(c.clamp(0.0, 1.0)) * 255.0).round() as u32
+                    });
+                    premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+                }
+                store_pixel(output, x, y, r, g, b, a);
+            }
+        }
+    }
+
     ///Sets the alpha channel of each pixel based on its luminance.
     ///
     ///Replaces the alpha channel with the computed luminance of the color channels.
@@ -499,20 +971,25 @@ impl<'a> Surface<'a> {
     ///
     ///alpha = 0.2126 * R + 0.7152 * G + 0.0722 * B
     ///
-    ///The input and output surfaces may refer to the same buffer.
+    ///Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input` - in The input surface.
     /// * `output` - out The output surface.
     ///
-    pub fn color_transform_luminance_to_alpha(input: &mut Self, output: &mut Self) {
-        overlap_surface(input, output);
+    pub fn color_transform_luminance_to_alpha<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+    ) {
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
+        let [lr, lg, lb] = input.color_space.luminance_to_alpha_coefficients();
 
         for y in 0..output.height {
             for x in 0..output.width {
-                let [mut r, mut g, mut b, mut a] = init_load_pixel(input, x, y, input.channel);
+                let [mut r, mut g, mut b, mut a] = init_load_pixel(&input, x, y, input.channel);
                 unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
 
-                let l = r as f32 * 0.2125 + g as f32 * 0.7154 + b as f32 * 0.0721;
+                let l = r as f32 * lr + g as f32 * lg + b as f32 * lb;
 
                 store_pixel(output, x, y, 0, 0, 0, clamp_pixel(l as u32));
             }
@@ -522,16 +999,20 @@ impl<'a> Surface<'a> {
     /// Converts the color channels from sRGB to linear RGB.
     ///
     ///Applies gamma correction to convert red, green, and blue channels from sRGB to linear space.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input`  - in The input surface.
     /// * `output` - out The output surface.
     ///
-    pub fn color_transform_srgb_to_linear_rgb(input: &mut Self, output: &mut Self) {
-        overlap_surface(input, output);
+    pub fn color_transform_srgb_to_linear_rgb<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+    ) {
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
         for y in 0..output.height {
             for x in 0..output.width {
-                let [mut r, mut g, mut b, mut a] = init_load_pixel(input, x, y, input.channel);
+                let [mut r, mut g, mut b, mut a] = init_load_pixel(&input, x, y, input.channel);
                 unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
                 srgb_to_linear_rgb(&mut r, &mut g, &mut b);
                 premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
@@ -543,16 +1024,20 @@ impl<'a> Surface<'a> {
     ///Converts the color channels from linear RGB to sRGB.
     ///
     ///Applies gamma encoding to convert red, green, and blue channels from linear space to sRGB.
-    ///The alpha channel is not affected. The input and output surfaces may refer to the same buffer.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
     /// # Arguments
     /// * `input` - in The input surface.
     /// * `output` - out The output surface.
     ///
-    pub fn color_transform_linear_rgb_to_srgb(input: &mut Self, output: &mut Self) {
-        overlap_surface(input, output);
+    pub fn color_transform_linear_rgb_to_srgb<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+    ) {
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
         for y in 0..output.height {
             for x in 0..output.width {
-                let [mut r, mut g, mut b, mut a] = init_load_pixel(input, x, y, input.channel);
+                let [mut r, mut g, mut b, mut a] = init_load_pixel(&input, x, y, input.channel);
                 unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
                 liner_rgb_to_srgb(&mut r, &mut g, &mut b);
                 premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
@@ -561,20 +1046,185 @@ impl<'a> Surface<'a> {
         }
     }
 
+    /// Remaps the tonal range of each color channel.
+    ///
+    ///Values at or below `black` become 0 and values at or above `white` become 1. Values in between
+    ///are stretched linearly and then raised to `1 / gamma`, so a gamma above 1 brightens midtones.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `black` - The input black point, in [0, 1].
+    /// * `white` - The input white point, in [0, 1] and greater than `black`.
+    /// * `gamma` - The midtone gamma (1 for unchanged).
+    ///
+    pub fn levels<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        black: f32,
+        white: f32,
+        gamma: f32,
+    ) {
+        if white <= black {
+            panic!("white point must be greater than the black point.")
+        }
+        if gamma <= 0.0 {
+            panic!("gamma must be positive.")
+        }
+        let table = levels_table(black, white, gamma);
+        apply_tables(input.into(), output, &[table; 3]);
+    }
+
+    /// Applies the same tone curve to the red, green and blue channels.
+    ///
+    ///The curve is a monotone cubic spline through the control points `[x, y]`, with both
+    ///coordinates in [0, 1]. It does not overshoot between points, and inputs outside the first and
+    ///last points map to their values.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `points` - At least two control points with distinct x coordinates.
+    ///
+    pub fn curves<'i>(input: impl Into<SurfaceView<'i>>, output: &mut Self, points: &[[f32; 2]]) {
+        if points.len() < 2 {
+            panic!("curves need at least two control points.")
+        }
+        let table = curve_table(points);
+        apply_tables(input.into(), output, &[table; 3]);
+    }
+
+    /// Applies a separate tone curve to each of the red, green and blue channels.
+    ///
+    ///See [Surface::curves] for how each curve is built from its control points.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `red` - Control points of the red curve.
+    /// * `green` - Control points of the green curve.
+    /// * `blue` - Control points of the blue curve.
+    ///
+    pub fn curves_rgb<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        red: &[[f32; 2]],
+        green: &[[f32; 2]],
+        blue: &[[f32; 2]],
+    ) {
+        if [red, green, blue].iter().any(|points| points.len() < 2) {
+            panic!("curves need at least two control points.")
+        }
+        let tables = [curve_table(red), curve_table(green), curve_table(blue)];
+        apply_tables(input.into(), output, &tables);
+    }
+
+    /// Adjusts the saturation of muted colors more than that of saturated colors.
+    ///
+    ///Scales the chroma of each pixel in CIE L*a*b* by `1 + amount * (1 - saturation)`, so colors
+    ///that are already vivid change little and lightness is preserved.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `amount` - The vibrance amount (0 for unchanged, <0 to mute, >0 to boost colors).
+    ///
+    pub fn vibrance<'i>(input: impl Into<SurfaceView<'i>>, output: &mut Self, amount: f32) {
+        vibrance(input.into(), output, amount);
+    }
+
+    /// Adjusts the white balance of each pixel.
+    ///
+    ///Shifts colors along the blue-yellow (`b`) and green-magenta (`a`) axes of CIE L*a*b*.
+    ///The shift grows with lightness, so black stays neutral.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `temperature` - The temperature shift, in [-1, 1] (<0 cooler, >0 warmer, 0 for unchanged).
+    /// * `tint` - The tint shift, in [-1, 1] (<0 greener, >0 more magenta, 0 for unchanged).
+    ///
+    pub fn temperature_tint<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        temperature: f32,
+        tint: f32,
+    ) {
+        temperature_tint(input.into(), output, temperature, tint);
+    }
+
+    /// Brightens or darkens the shadows and highlights of each pixel.
+    ///
+    ///Changes the CIE L*a*b* lightness with weights centered on dark and bright tones.
+    ///Black, white and the colors (`a` and `b`) are kept.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `shadows` - The shadow adjustment, in [-1, 1] (>0 to lift shadows, 0 for unchanged).
+    /// * `highlights` - The highlight adjustment, in [-1, 1] (<0 to recover highlights, 0 for unchanged).
+    ///
+    pub fn shadows_highlights<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        shadows: f32,
+        highlights: f32,
+    ) {
+        shadows_highlights(input.into(), output, shadows, highlights);
+    }
+
+    /// Adjusts the hue, saturation and lightness of selected hue ranges.
+    ///
+    ///Each pixel is converted to HSL and receives the adjustments of the two [HueRange]s around its
+    ///hue, blended linearly. Ranges that are not listed are unchanged, and grays are not affected.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `adjustments` - The adjustment of each hue range, the last one wins for a repeated range.
+    ///
+    pub fn hsl_adjust<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        adjustments: &[(HueRange, HslAdjustment)],
+    ) {
+        hsl_adjust(input.into(), output, adjustments);
+    }
+
+    /// Maps the colors of each pixel through a 3D lookup table.
+    ///
+    ///The table is applied to unpremultiplied colors, normalized to [0, 1], and the result is clamped.
+    ///The alpha channel is not affected. Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `lut` - The lookup table, see [Lut3d::parse_cube] and [Lut3d::from_hald_image].
+    /// * `interpolation` - How colors between the table entries are interpolated.
+    ///
+    pub fn apply_lut<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        lut: &Lut3d,
+        interpolation: LutInterpolation,
+    ) {
+        adjust_pixels(input.into(), output, |rgb| lut.lookup(rgb, interpolation));
+    }
+
     /// Applies a Gaussian blur to the input surface.
     ///
     ///Performs separable convolution with a Gaussian kernel along the X and Y axes.
     ///The blur strength is controlled by the standard deviation parameters.
     ///
-    ///The input and output surfaces may refer to the same buffer.
+    ///Use [Surface::in_place] to filter a surface into itself.
+    ///Runs in the [ColorInterpolation] of the output surface.
     /// # Arguments
     /// * `input` - The input surface.
     /// * `output` - The output surface.
     /// * `std_deviation_x` - The standard deviation of the blur along the X axis.
     /// * `std_deviation_y` - The standard deviation of the blur along the Y axis.
     ///
-    pub fn gaussian_blur(
-        input: &mut Self,
+    pub fn gaussian_blur<'i>(
+        input: impl Into<SurfaceView<'i>>,
         output: &mut Self,
         std_deviation_x: f32,
         std_deviation_y: f32,
@@ -582,12 +1232,25 @@ impl<'a> Surface<'a> {
         if std_deviation_x < 0.0 || std_deviation_y < 0.0 {
             panic!("standard deviation can not be less than zero.")
         }
-        overlap_surface(input, output);
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
+        if output.color_interpolation == ColorInterpolation::LinearRGB {
+            let mut source = LinearBuffer::from_surface(&input);
+            let mut result = LinearBuffer::new(output.width, output.height);
+            SurfaceF32::gaussian_blur(
+                &mut source.surface(),
+                &mut result.surface(),
+                std_deviation_x,
+                std_deviation_y,
+            );
+            result.store(output);
+            return;
+        }
         let mut kernel_width = calc_kernel_size(std_deviation_x);
         let mut kernel_height = calc_kernel_size(std_deviation_y);
 
         if kernel_width == 0 && kernel_height == 0 {
-            output.pixels.copy_from_slice(input.pixels);
+            copy_pixels(&input, output);
             return;
         }
 
@@ -598,81 +1261,318 @@ impl<'a> Surface<'a> {
             kernel_height = MAX_KERNEL_SIZE;
         }
 
-        let mut intermediate = [0; MAX_KERNEL_SIZE as usize];
+        with_channel_formats!(input, output, |input, mut output| {
+            gaussian_blur_pixels(&input, &mut output, kernel_width, kernel_height)
+        });
+    }
 
-        let output: Rc<RefCell<_>> = Rc::new(RefCell::new(output));
-        let input: Rc<RefCell<_>> = Rc::new(RefCell::new(input));
-        box_blur(
-            Rc::clone(&input),
-            Rc::clone(&output),
-            &mut intermediate,
-            kernel_width,
-            kernel_height,
-        );
-        box_blur(
-            Rc::clone(&output),
-            Rc::clone(&output),
-            &mut intermediate,
-            kernel_width,
-            kernel_height,
-        );
-        box_blur(
-            Rc::clone(&output),
-            Rc::clone(&output),
-            &mut intermediate,
-            kernel_width,
-            kernel_height,
-        );
+    /// Applies a Gaussian blur along the X axis only.
+    ///
+    ///Runs the horizontal passes of [Surface::gaussian_blur], so following it with
+    ///[Surface::blur_vertical] gives the same result as blurring both axes at once.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `std_deviation` - The standard deviation of the blur along the X axis.
+    ///
+    pub fn blur_horizontal<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        std_deviation: f32,
+    ) {
+        Self::gaussian_blur(input, output, std_deviation, 0.0);
+    }
+
+    /// Applies a Gaussian blur along the Y axis only.
+    ///
+    ///Runs the vertical passes of [Surface::gaussian_blur].
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `std_deviation` - The standard deviation of the blur along the Y axis.
+    ///
+    pub fn blur_vertical<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        std_deviation: f32,
+    ) {
+        Self::gaussian_blur(input, output, 0.0, std_deviation);
+    }
+
+    /// Returns how many pixels [Surface::gaussian_blur] reads beyond each pixel it writes.
+    ///
+    ///Pass it to [Surface::filter_region] to blur a region without seams.
+    /// # Arguments
+    /// * `std_deviation_x` - The standard deviation of the blur along the X axis.
+    /// * `std_deviation_y` - The standard deviation of the blur along the Y axis.
+    /// # Returns
+    /// The horizontal and vertical padding in pixels.
+    ///
+    pub fn gaussian_blur_padding(std_deviation_x: f32, std_deviation_y: f32) -> (u32, u32) {
+        // Each of the three box blur passes reads at most half its kernel on either side. The
+        // kernel is not limited to MAX_KERNEL_SIZE as in linear RGB.
+        let padding = |std_deviation| 3 * (calc_kernel_size(std_deviation) / 2);
+        (padding(std_deviation_x), padding(std_deviation_y))
+    }
+
+    /// Applies an edge-preserving bilateral filter to the input surface.
+    ///
+    ///Smooths areas of similar intensity while keeping strong edges intact. Each pixel is averaged
+    ///with its neighbours, weighted by both spatial distance and difference in luminance.
+    ///The filter is approximated with a bilateral grid, so its cost does not grow with the spatial sigma.
+    ///The grid cells are at least 2 pixels wide and 8 levels tall, which bounds its memory to a
+    ///small multiple of the surface, so smaller sigmas smooth slightly more than requested.
+    ///
+    ///Use [Surface::in_place] to filter a surface into itself.
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `sigma_spatial` - The spatial standard deviation in pixels (0 for unchanged).
+    /// * `sigma_range` - The range standard deviation as a fraction of full intensity, in [0, 1] (0 for unchanged).
+    ///
+    pub fn bilateral_filter<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        sigma_spatial: f32,
+        sigma_range: f32,
+    ) {
+        if sigma_spatial < 0.0 || sigma_range < 0.0 {
+            panic!("sigma can not be less than zero.")
+        }
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
+        if sigma_spatial == 0.0 || sigma_range == 0.0 {
+            copy_pixels(&input, output);
+            return;
+        }
+        bilateral_grid(&input, output, sigma_spatial, sigma_range);
+    }
+
+    /// Applies a rank-order filter to the input surface.
+    ///
+    ///Replaces each channel of every pixel with the value at the given percentile of the same channel
+    ///in the surrounding `(2 * radius_x + 1) x (2 * radius_y + 1)` neighborhood.
+    ///Channels are ranked independently on premultiplied values. The neighborhood is clipped to the surface.
+    ///The cost per pixel does not depend on the radius.
+    ///
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `radius_x` - The horizontal radius of the neighborhood in pixels.
+    /// * `radius_y` - The vertical radius of the neighborhood in pixels.
+    /// * `percentile` - The rank to select, in [0, 1] (0 for minimum, 0.5 for median, 1 for maximum).
+    ///
+    pub fn rank_filter<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        radius_x: u32,
+        radius_y: u32,
+        percentile: f32,
+    ) {
+        if !(0.0..=1.0).contains(&percentile) {
+            panic!("percentile must be in the range [0, 1].")
+        }
+        let mut input = input.into();
+        overlap_surface(&mut input, output);
+        if output.width == 0 || output.height == 0 {
+            return;
+        }
+        rank_filter(&input, output, radius_x, radius_y, percentile);
+    }
+
+    /// Applies a median filter to the input surface.
+    ///
+    ///Replaces each channel with the median of the surrounding neighborhood, which removes
+    ///salt-and-pepper noise while keeping edges sharp.
+    ///
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `radius_x` - The horizontal radius of the neighborhood in pixels.
+    /// * `radius_y` - The vertical radius of the neighborhood in pixels.
+    ///
+    pub fn median_filter<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        radius_x: u32,
+        radius_y: u32,
+    ) {
+        Self::rank_filter(input, output, radius_x, radius_y, 0.5);
+    }
+
+    /// Applies a minimum filter to the input surface.
+    ///
+    ///Replaces each channel with the minimum of the surrounding neighborhood.
+    ///This is equivalent to the SVG `feMorphology` erode operator.
+    ///
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `radius_x` - The horizontal radius of the neighborhood in pixels.
+    /// * `radius_y` - The vertical radius of the neighborhood in pixels.
+    ///
+    pub fn min_filter<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        radius_x: u32,
+        radius_y: u32,
+    ) {
+        Self::rank_filter(input, output, radius_x, radius_y, 0.0);
+    }
+
+    /// Applies a maximum filter to the input surface.
+    ///
+    ///Replaces each channel with the maximum of the surrounding neighborhood.
+    ///This is equivalent to the SVG `feMorphology` dilate operator.
+    ///
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `radius_x` - The horizontal radius of the neighborhood in pixels.
+    /// * `radius_y` - The vertical radius of the neighborhood in pixels.
+    ///
+    pub fn max_filter<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        radius_x: u32,
+        radius_y: u32,
+    ) {
+        Self::rank_filter(input, output, radius_x, radius_y, 1.0);
+    }
+
+    /// Applies an affine transformation to the input surface.
+    ///
+    ///Scales, rotates, skews and translates the input into the output using the given
+    ///2x3 matrix `[a, b, c, d, e, f]`, which maps an input point to an output point as:
+    ///
+    /// ```text
+    /// x' = a * x + c * y + e
+    /// y' = b * x + d * y + f
+    /// ```
+    ///
+    ///Each output pixel is sampled from the input with the selected filter. Premultiplied channels are
+    ///interpolated directly, and the filter is widened when downscaling to avoid aliasing.
+    ///The output may have a different size than the input. A singular matrix produces a transparent output.
+    ///
+    /// # Arguments
+    /// * `input` - The input surface.
+    /// * `output` - The output surface.
+    /// * `matrix` - The affine transformation matrix, in the same order as SVG `matrix(a, b, c, d, e, f)`.
+    /// * `filter` - The resampling filter.
+    /// * `edge_mode` - How pixels outside the input surface are sampled.
+    ///
+    pub fn transform<'i>(
+        input: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        matrix: [f32; 6],
+        filter: ResampleFilter,
+        edge_mode: EdgeMode,
+    ) {
+        let input = input.into();
+        match invert_affine(matrix) {
+            Some(inverse) if input.width > 0 && input.height > 0 => {
+                affine_transform(&input, output, inverse, filter, edge_mode)
+            }
+            _ => {
+                for y in 0..output.height {
+                    for x in 0..output.width {
+                        store_pixel(output, x, y, 0, 0, 0, 0);
+                    }
+                }
+            }
+        }
     }
 
     /// Blends two input surfaces using the specified blend mode.
     ///
     ///Applies the selected blend mode to combine `in1` (source) over `in2` (backdrop).
-    ///The output surface may refer to either input.
+    ///The same surface may be passed as both inputs. Use [Surface::in_place] to write the result
+    ///back into an input.
     ///
+    ///Runs in the [ColorInterpolation] of the output surface.
     /// # Arguments
     /// * `in1` - The source surface.
     /// * `in2` - The backdrop surface.
     /// * `out` - The output surface.
     /// * `mode` - The blend mode to apply.
     ///
-    pub fn blend(input1: &mut Self, input2: &mut Self, output: &mut Self, mode: BlendMode) {
-        overlap_surface3(input1, input2, output);
+    pub fn blend<'i>(
+        input1: impl Into<SurfaceView<'i>>,
+        input2: impl Into<SurfaceView<'i>>,
+        output: &mut Self,
+        mode: BlendMode,
+    ) {
+        let (mut input1, mut input2) = (input1.into(), input2.into());
+        overlap_surface3(&mut input1, &mut input2, output);
+        if output.color_interpolation == ColorInterpolation::LinearRGB {
+            let mut source = LinearBuffer::from_surface(&input1);
+            let mut backdrop = LinearBuffer::from_surface(&input2);
+            let mut result = LinearBuffer::new(output.width, output.height);
+            SurfaceF32::blend(
+                &mut source.surface(),
+                &mut backdrop.surface(),
+                &mut result.surface(),
+                mode,
+            );
+            result.store(output);
+            return;
+        }
         match mode {
-            BlendMode::Normal => blend_normal(input1, input2, output),
-            BlendMode::Multiply => blend_multiply(input1, input2, output),
-            BlendMode::Screen => blend_screen(input1, input2, output),
-            BlendMode::Overlay => blend_overlay(input1, input2, output),
-            BlendMode::Darken => blend_darken(input1, input2, output),
-            BlendMode::Lighten => blend_lighten(input1, input2, output),
-            BlendMode::ColorDodge => blend_color_dodge(input1, input2, output),
-            BlendMode::ColorBurn => blend_color_burn(input1, input2, output),
-            BlendMode::HardLight => blend_hard_light(input1, input2, output),
-            BlendMode::SoftLight => blend_soft_light(input1, input2, output),
-            BlendMode::Difference => blend_difference(input1, input2, output),
-            BlendMode::Exclusion => blend_exclusion(input1, input2, output),
+            BlendMode::Normal => blend_normal(&input1, &input2, output),
+            BlendMode::Multiply => blend_multiply(&input1, &input2, output),
+            BlendMode::Screen => blend_screen(&input1, &input2, output),
+            BlendMode::Overlay => blend_overlay(&input1, &input2, output),
+            BlendMode::Darken => blend_darken(&input1, &input2, output),
+            BlendMode::Lighten => blend_lighten(&input1, &input2, output),
+            BlendMode::ColorDodge => blend_color_dodge(&input1, &input2, output),
+            BlendMode::ColorBurn => blend_color_burn(&input1, &input2, output),
+            BlendMode::HardLight => blend_hard_light(&input1, &input2, output),
+            BlendMode::SoftLight => blend_soft_light(&input1, &input2, output),
+            BlendMode::Difference => blend_difference(&input1, &input2, output),
+            BlendMode::Exclusion => blend_exclusion(&input1, &input2, output),
         }
     }
 
     /// Composites two input surfaces using the specified operator.
     ///
     ///Applies the selected compositing rule to combine `in1` (source) over `in2` (backdrop).
-    ///The output surface may refer to the same buffer as either input.
+    ///The same surface may be passed as both inputs. Use [Surface::in_place] to write the result
+    ///back into an input.
     ///
+    ///Runs in the [ColorInterpolation] of the output surface.
     /// # Arguments
     /// * `in1` - The source surface.
     /// * `in2` - The backdrop surface.
     /// * `out` - The output surface.
     /// * `op` - The compositing operator to apply.
     ///
-    pub fn composite(in1: &mut Self, in2: &mut Self, out: &mut Self, op: CompositeOperator) {
-        overlap_surface3(in1, in2, out);
+    pub fn composite<'i>(
+        in1: impl Into<SurfaceView<'i>>,
+        in2: impl Into<SurfaceView<'i>>,
+        out: &mut Self,
+        op: CompositeOperator,
+    ) {
+        let (mut in1, mut in2) = (in1.into(), in2.into());
+        overlap_surface3(&mut in1, &mut in2, out);
+        if out.color_interpolation == ColorInterpolation::LinearRGB {
+            let mut source = LinearBuffer::from_surface(&in1);
+            let mut backdrop = LinearBuffer::from_surface(&in2);
+            let mut result = LinearBuffer::new(out.width, out.height);
+            SurfaceF32::composite(
+                &mut source.surface(),
+                &mut backdrop.surface(),
+                &mut result.surface(),
+                op,
+            );
+            result.store(out);
+            return;
+        }
         match op {
-            CompositeOperator::Over => composite_over(in1, in2, out),
-            CompositeOperator::In => composite_in(in1, in2, out),
-            CompositeOperator::Out => composite_out(in1, in2, out),
-            CompositeOperator::Atop => composite_atop(in1, in2, out),
-            CompositeOperator::Xor => composite_xor(in1, in2, out),
+            CompositeOperator::Over => composite_over(&in1, &in2, out),
+            CompositeOperator::In => composite_in(&in1, &in2, out),
+            CompositeOperator::Out => composite_out(&in1, &in2, out),
+            CompositeOperator::Atop => composite_atop(&in1, &in2, out),
+            CompositeOperator::Xor => composite_xor(&in1, &in2, out),
         }
     }
 
@@ -682,8 +1582,10 @@ impl<'a> Surface<'a> {
     ///result = k1 * in1 * in2 + k2 * in1 + k3 * in2 + k4
     ///The result is clamped to the valid range.
     ///
-    ///The output surface may refer to the same buffer as either input.
+    ///The same surface may be passed as both inputs. Use [Surface::in_place] to write the result
+    ///back into an input.
     ///
+    ///Runs in the [ColorInterpolation] of the output surface.
     /// # Arguments
     /// * `in1` - The source surface.
     /// * `in2` - The backdrop surface.
@@ -693,21 +1595,38 @@ impl<'a> Surface<'a> {
     /// * `k3` - The coefficient for in2.
     /// * `k4` - The constant bias term.
     ///
-    pub fn composite_arithmetic(
-        in1: &mut Self,
-        in2: &mut Self,
+    pub fn composite_arithmetic<'i>(
+        in1: impl Into<SurfaceView<'i>>,
+        in2: impl Into<SurfaceView<'i>>,
         out: &mut Self,
         k1: f32,
         k2: f32,
         k3: f32,
         k4: f32,
     ) {
-        overlap_surface3(in1, in2, out);
+        let (mut in1, mut in2) = (in1.into(), in2.into());
+        overlap_surface3(&mut in1, &mut in2, out);
+        if out.color_interpolation == ColorInterpolation::LinearRGB {
+            let mut source = LinearBuffer::from_surface(&in1);
+            let mut backdrop = LinearBuffer::from_surface(&in2);
+            let mut result = LinearBuffer::new(out.width, out.height);
+            SurfaceF32::composite_arithmetic(
+                &mut source.surface(),
+                &mut backdrop.surface(),
+                &mut result.surface(),
+                k1,
+                k2,
+                k3,
+                k4,
+            );
+            result.store(out);
+            return;
+        }
 
         for y in 0..out.height {
             for x in 0..out.width {
-                let [sr, sg, sb, sa] = init_load_pixel(in1, x, y, in1.channel);
-                let [dr, dg, db, da] = init_load_pixel(in2, x, y, in2.channel);
+                let [sr, sg, sb, sa] = init_load_pixel(&in1, x, y, in1.channel);
+                let [dr, dg, db, da] = init_load_pixel(&in2, x, y, in2.channel);
 
                 let (sr, sg, sb, sa) = (sr as f32, sg as f32, sb as f32, sa as f32);
                 let (dr, dg, db, da) = (dr as f32, dg as f32, db as f32, da as f32);
@@ -728,3 +1647,13 @@ impl<'a> Surface<'a> {
     // ===end=== this should not be part of Surface
     // --------------------------------------------
 }
+
+/// Copies the pixels of `input` into `output`, converting between their channel orders.
+pub(crate) fn copy_pixels(input: &SurfaceView, output: &mut Surface) {
+    for y in 0..output.height {
+        for x in 0..output.width {
+            let [r, g, b, a] = init_load_pixel(input, x, y, input.channel);
+            store_pixel(output, x, y, r, g, b, a);
+        }
+    }
+}
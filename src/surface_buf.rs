@@ -0,0 +1,214 @@
+#[cfg(feature = "image")]
+use crate::utils::{unpack_pixel, unpremultiply_pixel};
+use crate::{
+    ColorChannel, ColorInterpolation, ColorSpace, Surface, SurfaceView,
+    error::SurfaceError,
+    utils::{pack_pixel, premultiply_pixel},
+};
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "image")]
+use image::RgbaImage;
+
+/// A 2D image surface that owns its pixel buffer.
+///
+/// Holds the same premultiplied pixels as [Surface], without tying them to the lifetime of a
+/// borrowed `Vec<u32>`, so intermediate results can be stored in structs. Borrow it as a
+/// [Surface] with [SurfaceBuf::as_surface] to run filters.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf};
+///
+/// let mut input = SurfaceBuf::new(64, 64, ColorChannel::RGBA32);
+/// input.fill([255, 128, 0, 255]);
+/// let mut output = SurfaceBuf::new(64, 64, ColorChannel::RGBA32);
+/// Surface::color_transform_invert(&mut input.as_surface(), &mut output.as_surface(), 1.0);
+///
+/// let mut expected = SurfaceBuf::new(64, 64, ColorChannel::RGBA32);
+/// expected.fill([0, 127, 255, 255]);
+/// assert_eq!(output, expected);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceBuf {
+    pixels: Vec<u32>,
+    width: u32,
+    height: u32,
+    channel: ColorChannel,
+    color_interpolation: ColorInterpolation,
+    color_space: ColorSpace,
+}
+
+impl SurfaceBuf {
+    /// Allocates a transparent surface of `width * height` pixels.
+    pub fn new(width: u32, height: u32, channel: ColorChannel) -> Self {
+        Self::from_parts(
+            vec![0; width as usize * height as usize],
+            width,
+            height,
+            channel,
+        )
+    }
+
+    fn from_parts(pixels: Vec<u32>, width: u32, height: u32, channel: ColorChannel) -> Self {
+        Self {
+            pixels,
+            width,
+            height,
+            channel,
+            color_interpolation: ColorInterpolation::SRGB,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// Takes ownership of `width * height` premultiplied pixels, with no padding between rows.
+    pub fn from_pixels(
+        pixels: Vec<u32>,
+        width: u32,
+        height: u32,
+        channel: ColorChannel,
+    ) -> Result<Self, SurfaceError> {
+        if pixels.len() != width as usize * height as usize {
+            return Err(SurfaceError::InvalidPixelLength);
+        }
+        Ok(Self::from_parts(pixels, width, height, channel))
+    }
+
+    /// Sets the [ColorInterpolation] of the surfaces borrowed from this buffer.
+    pub fn with_color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.color_interpolation = color_interpolation;
+        self
+    }
+
+    /// Sets the [ColorSpace] of the surfaces borrowed from this buffer.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Borrows the buffer as a [Surface] for filtering.
+    pub fn as_surface(&mut self) -> Surface<'_> {
+        Surface {
+            pixels: self.pixels.as_mut_slice().into(),
+            width: self.width,
+            height: self.height,
+            stride: self.width,
+            channel: self.channel,
+            color_interpolation: self.color_interpolation,
+            color_space: self.color_space,
+        }
+    }
+
+    /// Borrows the buffer as a read-only [SurfaceView], to use as a filter input.
+    pub fn as_view(&self) -> SurfaceView<'_> {
+        SurfaceView {
+            pixels: &self.pixels,
+            width: self.width,
+            height: self.height,
+            stride: self.width,
+            channel: self.channel,
+            color_space: self.color_space,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn channel(&self) -> ColorChannel {
+        self.channel
+    }
+
+    /// Returns the pixels in row-major order.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u32> {
+        self.pixels
+    }
+
+    /// Sets every pixel to an unpremultiplied `[r, g, b, a]` color.
+    pub fn fill(&mut self, [r, g, b, a]: [u8; 4]) {
+        let [mut r, mut g, mut b, mut a] = [r, g, b, a].map(u32::from);
+        premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+        self.pixels.fill(pack_pixel(r, g, b, a, self.channel));
+    }
+
+    /// Changes the size of the buffer.
+    ///
+    ///Pixels inside both the old and the new size keep their position, new pixels are
+    ///transparent. Use [Surface::transform] to scale the contents instead.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let mut pixels = vec![0; width as usize * height as usize];
+        let copy_width = self.width.min(width) as usize;
+        for y in 0..self.height.min(height) as usize {
+            let source = y * self.width as usize;
+            let target = y * width as usize;
+            pixels[target..target + copy_width]
+                .copy_from_slice(&self.pixels[source..source + copy_width]);
+        }
+        self.pixels = pixels;
+        self.width = width;
+        self.height = height;
+    }
+}
+
+/// Premultiplies the unpremultiplied colors of the image, the same way [SurfaceBuf::fill] does.
+#[cfg(feature = "image")]
+impl From<&RgbaImage> for SurfaceBuf {
+    fn from(image: &RgbaImage) -> Self {
+        let pixels = image
+            .pixels()
+            .map(|pixel| {
+                let [mut r, mut g, mut b, mut a] = pixel.0.map(u32::from);
+                premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+                pack_pixel(r, g, b, a, ColorChannel::RGBA32)
+            })
+            .collect();
+        Self::from_parts(pixels, image.width(), image.height(), ColorChannel::RGBA32)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<RgbaImage> for SurfaceBuf {
+    fn from(image: RgbaImage) -> Self {
+        Self::from(&image)
+    }
+}
+
+/// Unpremultiplies the pixels and writes them in RGBA byte order, whatever the [ColorChannel] of
+/// the buffer.
+///
+///Colors of pixels with low alpha lose precision in the round trip through [SurfaceBuf].
+#[cfg(feature = "image")]
+impl From<&SurfaceBuf> for RgbaImage {
+    fn from(surface: &SurfaceBuf) -> Self {
+        let bytes = surface
+            .pixels
+            .iter()
+            .flat_map(|pixel| {
+                let [mut r, mut g, mut b, mut a] = unpack_pixel(pixel, surface.channel);
+                unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+                [r, g, b, a].map(|c| c.min(255) as u8)
+            })
+            .collect();
+        RgbaImage::from_raw(surface.width, surface.height, bytes)
+            .expect("buffer holds width * height pixels")
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<SurfaceBuf> for RgbaImage {
+    fn from(surface: SurfaceBuf) -> Self {
+        Self::from(&surface)
+    }
+}
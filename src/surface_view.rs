@@ -0,0 +1,147 @@
+use crate::{ColorChannel, ColorSpace, Surface, SurfaceBuf, error::SurfaceError};
+
+/// A read-only 2D image surface, used for the inputs of filter primitives.
+///
+/// Holds the same premultiplied pixels as [Surface], but only borrows them shared, so the same
+/// buffer can be passed as several inputs at once, such as blending an image with itself.
+/// Every primitive accepts anything that converts into a view for its inputs, including
+/// `&Surface`, `&mut Surface` and `&SurfaceBuf`.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{BlendMode, ColorChannel, Surface, SurfaceBuf};
+///
+/// let mut image = SurfaceBuf::new(8, 8, ColorChannel::RGBA32);
+/// image.fill([128, 128, 128, 255]);
+/// let mut output = SurfaceBuf::new(8, 8, ColorChannel::RGBA32);
+/// Surface::blend(&image, &image, &mut output.as_surface(), BlendMode::Multiply);
+///
+/// let [r, _, _, a] = output.pixels()[0].to_le_bytes();
+/// assert_eq!((r, a), (64, 255));
+/// ```
+///
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceView<'a> {
+    /// The pixel buffer.
+    ///
+    /// Must hold at least `stride * height` elements in premultiplied format.
+    pub(crate) pixels: &'a [u32],
+
+    /// The width of the surface in pixels.
+    pub(crate) width: u32,
+
+    /// The height of the surface in pixels.
+    pub(crate) height: u32,
+
+    /// The number of pixels per row.
+    pub(crate) stride: u32,
+
+    /// The byte order of the pixels, see [ColorChannel].
+    pub(crate) channel: ColorChannel,
+
+    /// The color space of the pixel data.
+    pub(crate) color_space: ColorSpace,
+}
+
+impl<'a> SurfaceView<'a> {
+    /// Creates a read-only surface from a raw pixel buffer.
+    ///
+    /// # Arguments
+    /// * `pixels` - The pixel buffer in premultiplied format.
+    /// * `width`  - The width of the surface in pixels.
+    /// * `height` - The height of the surface in pixels.
+    /// * `stride` - The number of pixels per row (must be greater than or equal to width).
+    /// * `channel` - The byte order of the pixels, [ColorChannel::ARGB32] if `None`.
+    /// # Returns [SurfaceView] representing the given pixel buffer.
+    ///
+    pub fn make(
+        pixels: &'a [u32],
+        width: u32,
+        height: u32,
+        stride: u32,
+        channel: Option<ColorChannel>,
+    ) -> Result<Self, SurfaceError> {
+        if pixels.len() < (stride * height) as usize {
+            Err(SurfaceError::InvalidPixelLength)
+        } else if stride < width {
+            Err(SurfaceError::StrideLessThanWidth)
+        } else {
+            Ok(Self {
+                pixels,
+                width,
+                height,
+                stride,
+                channel: channel.unwrap_or(ColorChannel::ARGB32),
+                color_space: ColorSpace::Srgb,
+            })
+        }
+    }
+
+    /// Creates a read-only subregion of the view.
+    ///
+    /// # Arguments
+    /// * `x` - The horizontal offset of the subregion, in pixels.
+    /// * `y` - The vertical offset of the subregion, in pixels.
+    /// * `width` - The width of the subregion in pixels.
+    /// * `height` - The height of the subregion in pixels.
+    ///
+    /// # Returns
+    /// [SurfaceView] referencing the specified subregion, clipped to the bounds of the view.
+    ///
+    pub fn make_sub(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        let offset = (y * self.stride + x) as usize;
+        Self {
+            pixels: self.pixels.get(offset..).unwrap_or_default(),
+            width: width.min(self.width - x),
+            height: height.min(self.height - y),
+            ..*self
+        }
+    }
+
+    /// Tags the view with the color space its pixels are encoded in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn pixels(&self) -> &'a [u32] {
+        self.pixels
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+}
+
+impl<'a> From<&'a Surface<'_>> for SurfaceView<'a> {
+    fn from(surface: &'a Surface<'_>) -> Self {
+        surface.view()
+    }
+}
+
+impl<'a> From<&'a mut Surface<'_>> for SurfaceView<'a> {
+    fn from(surface: &'a mut Surface<'_>) -> Self {
+        surface.view()
+    }
+}
+
+impl<'a> From<&SurfaceView<'a>> for SurfaceView<'a> {
+    fn from(view: &SurfaceView<'a>) -> Self {
+        *view
+    }
+}
+
+impl<'a> From<&'a SurfaceBuf> for SurfaceView<'a> {
+    fn from(surface: &'a SurfaceBuf) -> Self {
+        surface.as_view()
+    }
+}
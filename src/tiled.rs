@@ -0,0 +1,715 @@
+use crate::{
+    Bounds, ColorChannel, ColorInterpolation, ColorMatrix, ColorSpace, FilterPrimitive,
+    HslAdjustment, HueRange, Lut3d, LutInterpolation, Rect, Surface, SurfaceBuf, SurfaceView,
+    utils::{init_load_pixel, pack_pixel, store_pixel, unpack_pixel},
+};
+use alloc::vec::Vec;
+use core::{
+    convert::Infallible,
+    fmt::{self, Display},
+};
+
+/// The default width and height of the tiles of a [TiledPipeline].
+const DEFAULT_TILE_SIZE: u32 = 512;
+
+/// A filter run by [TiledPipeline] on every tile, together with its arguments.
+#[derive(Debug, Clone)]
+enum TileFilter {
+    ColorTransform(ColorMatrix),
+    LuminanceToAlpha,
+    Levels(f32, f32, f32),
+    Curves(Vec<[f32; 2]>, Vec<[f32; 2]>, Vec<[f32; 2]>),
+    Vibrance(f32),
+    TemperatureTint(f32, f32),
+    ShadowsHighlights(f32, f32),
+    HslAdjust(Vec<(HueRange, HslAdjustment)>),
+    Lut(Lut3d, LutInterpolation),
+    GaussianBlur(f32, f32),
+    RankFilter(u32, u32, f32),
+}
+
+impl TileFilter {
+    /// The primitive the filter runs, which tells how far it reads beyond each pixel it writes.
+    fn primitive(&self) -> FilterPrimitive {
+        match *self {
+            Self::GaussianBlur(x, y) => FilterPrimitive::GaussianBlur(x, y),
+            Self::RankFilter(x, y, _) => FilterPrimitive::RankFilter(x, y),
+            _ => FilterPrimitive::Pointwise,
+        }
+    }
+
+    fn apply(&self, input: SurfaceView, output: &mut Surface) {
+        match self {
+            Self::ColorTransform(matrix) => Surface::color_transform(input, output, *matrix),
+            Self::LuminanceToAlpha => Surface::color_transform_luminance_to_alpha(input, output),
+            Self::Levels(black, white, gamma) => {
+                Surface::levels(input, output, *black, *white, *gamma)
+            }
+            Self::Curves(red, green, blue) => Surface::curves_rgb(input, output, red, green, blue),
+            Self::Vibrance(amount) => Surface::vibrance(input, output, *amount),
+            Self::TemperatureTint(temperature, tint) => {
+                Surface::temperature_tint(input, output, *temperature, *tint)
+            }
+            Self::ShadowsHighlights(shadows, highlights) => {
+                Surface::shadows_highlights(input, output, *shadows, *highlights)
+            }
+            Self::HslAdjust(adjustments) => Surface::hsl_adjust(input, output, adjustments),
+            Self::Lut(lut, interpolation) => Surface::apply_lut(input, output, lut, *interpolation),
+            Self::GaussianBlur(x, y) => Surface::gaussian_blur(input, output, *x, *y),
+            Self::RankFilter(x, y, percentile) => {
+                Surface::rank_filter(input, output, *x, *y, *percentile)
+            }
+        }
+    }
+}
+
+/// A source of image rows for [TiledPipeline::run], read from top to bottom.
+///
+///Rows hold premultiplied pixels in [ColorChannel::RGBA32] order.
+pub trait RowReader {
+    type Error;
+
+    fn width(&self) -> u32;
+
+    fn height(&self) -> u32;
+
+    /// Reads the next row into `row`, which holds `width` pixels.
+    fn read_row(&mut self, row: &mut [u32]) -> Result<(), Self::Error>;
+}
+
+/// A destination for the image rows written by [TiledPipeline::run], from top to bottom.
+///
+///Rows hold premultiplied pixels in [ColorChannel::RGBA32] order.
+pub trait RowWriter {
+    type Error;
+
+    /// Writes the next row of `width` pixels.
+    fn write_row(&mut self, row: &[u32]) -> Result<(), Self::Error>;
+}
+
+/// Reads the rows of a surface that is already in memory.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceReader<'a> {
+    view: SurfaceView<'a>,
+    y: u32,
+}
+
+impl<'a> SurfaceReader<'a> {
+    pub fn new(surface: impl Into<SurfaceView<'a>>) -> Self {
+        Self {
+            view: surface.into(),
+            y: 0,
+        }
+    }
+}
+
+impl RowReader for SurfaceReader<'_> {
+    type Error = Infallible;
+
+    fn width(&self) -> u32 {
+        self.view.width
+    }
+
+    fn height(&self) -> u32 {
+        self.view.height
+    }
+
+    fn read_row(&mut self, row: &mut [u32]) -> Result<(), Self::Error> {
+        for (x, pixel) in (0..self.view.width).zip(row) {
+            let [r, g, b, a] = init_load_pixel(&self.view, x, self.y, self.view.channel);
+            *pixel = pack_pixel(r, g, b, a, ColorChannel::RGBA32);
+        }
+        self.y += 1;
+        Ok(())
+    }
+}
+
+/// Writes rows into a surface that is already in memory. Rows below the surface are dropped.
+#[derive(Debug)]
+pub struct SurfaceWriter<'a, 'b> {
+    surface: &'a mut Surface<'b>,
+    y: u32,
+}
+
+impl<'a, 'b> SurfaceWriter<'a, 'b> {
+    pub fn new(surface: &'a mut Surface<'b>) -> Self {
+        Self { surface, y: 0 }
+    }
+}
+
+impl RowWriter for SurfaceWriter<'_, '_> {
+    type Error = Infallible;
+
+    fn write_row(&mut self, row: &[u32]) -> Result<(), Self::Error> {
+        if self.y < self.surface.height {
+            for (x, pixel) in (0..self.surface.width).zip(row) {
+                let [r, g, b, a] = unpack_pixel(pixel, ColorChannel::RGBA32);
+                store_pixel(self.surface, x, self.y, r, g, b, a);
+            }
+        }
+        self.y += 1;
+        Ok(())
+    }
+}
+
+/// Collects the rows in memory.
+impl RowWriter for Vec<Vec<u32>> {
+    type Error = Infallible;
+
+    fn write_row(&mut self, row: &[u32]) -> Result<(), Self::Error> {
+        self.push(row.to_vec());
+        Ok(())
+    }
+}
+
+/// An error from the [RowReader] or the [RowWriter] of [TiledPipeline::run].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TileError<R, W> {
+    Read(R),
+    Write(W),
+}
+
+impl<R: core::error::Error, W: core::error::Error> core::error::Error for TileError<R, W> {}
+
+impl<R: Display, W: Display> Display for TileError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileError::Read(err) => write!(f, "Unable to read image rows: {err}"),
+            TileError::Write(err) => write!(f, "Unable to write image rows: {err}"),
+        }
+    }
+}
+
+/// Runs a chain of filters over an image one tile at a time, for images too large to hold in
+/// memory.
+///
+///Rows are read from a [RowReader] and written to a [RowWriter] one band of tiles at a time,
+///so only a band of tiles and its halo are held in memory. Each tile is filtered with a halo of
+///neighbouring pixels sized from the footprint of every filter in the chain, so the result is the
+///same as filtering the whole image, with no seams between the tiles.
+///
+///Only filters that read a bounded neighborhood are available: color transforms, adjustments,
+///LUTs, blurs and rank filters.
+///
+/// # Example
+/// ```
+/// use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf, SurfaceReader, SurfaceWriter, TiledPipeline};
+///
+/// let mut image = SurfaceBuf::new(300, 200, ColorChannel::RGBA32);
+/// image.fill([200, 120, 40, 255]);
+/// let mut output = SurfaceBuf::new(300, 200, ColorChannel::RGBA32);
+/// let pipeline = TiledPipeline::new()
+///     .with_tile_size(128, 64)
+///     .gaussian_blur(4.0, 4.0)
+///     .color_transform_sepia(0.5);
+/// pipeline
+///     .run(&mut SurfaceReader::new(&image), &mut SurfaceWriter::new(&mut output.as_surface()))
+///     .unwrap();
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct TiledPipeline {
+    filters: Vec<(TileFilter, ColorInterpolation)>,
+    color_interpolation: ColorInterpolation,
+    color_space: ColorSpace,
+    tile_width: u32,
+    tile_height: u32,
+}
+
+impl Default for TiledPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bounds for TiledPipeline {
+    fn bounds(&self, input: Rect) -> Rect {
+        self.filters
+            .iter()
+            .fold(input, |rect, (filter, _)| filter.primitive().bounds(rect))
+    }
+
+    fn required_input_rect(&self, output: Rect) -> Rect {
+        self.filters.iter().rev().fold(output, |rect, (filter, _)| {
+            filter.primitive().required_input_rect(rect)
+        })
+    }
+}
+
+impl TiledPipeline {
+    /// Creates an empty pipeline with 512x512 tiles.
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            color_interpolation: ColorInterpolation::default(),
+            color_space: ColorSpace::default(),
+            tile_width: DEFAULT_TILE_SIZE,
+            tile_height: DEFAULT_TILE_SIZE,
+        }
+    }
+
+    /// Sets the size of the tiles written at a time, not counting their halo.
+    pub fn with_tile_size(mut self, width: u32, height: u32) -> Self {
+        if width == 0 || height == 0 {
+            panic!("tile size can not be zero.")
+        }
+        self.tile_width = width;
+        self.tile_height = height;
+        self
+    }
+
+    /// Tags the image with the color space its pixels are encoded in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Sets the color space used by the color transforms and blurs that follow in the pipeline.
+    pub fn color_interpolation(mut self, color_interpolation: ColorInterpolation) -> Self {
+        self.color_interpolation = color_interpolation;
+        self
+    }
+
+    /// Returns how many pixels each tile reads beyond its edges, horizontally and vertically.
+    ///
+    ///This is the sum of the footprints of the filters, such as the reach of the blur kernels.
+    pub fn halo(&self) -> (u32, u32) {
+        self.filters
+            .iter()
+            .map(|(filter, _)| filter.primitive().padding().unwrap_or_default())
+            .fold((0, 0), |(x, y), (dx, dy)| {
+                (x.saturating_add(dx), y.saturating_add(dy))
+            })
+    }
+
+    fn push(mut self, filter: TileFilter) -> Self {
+        self.filters.push((filter, self.color_interpolation));
+        self
+    }
+
+    /// Filters the rows of `reader` into `writer`.
+    ///
+    ///Rows are read at most once and written in order. Memory use grows with the width of the
+    ///image and the tile height, not with the image height.
+    pub fn run<R: RowReader, W: RowWriter>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), TileError<R::Error, W::Error>> {
+        let (width, height) = (reader.width(), reader.height());
+        let (_, halo_y) = self.halo();
+        let row_len = width as usize;
+        // Rows `top..bottom` of the image, the band being filtered and its halo.
+        let mut window = Vec::new();
+        let (mut top, mut bottom) = (0, 0);
+        let mut band = Vec::new();
+        let mut y = 0;
+        while y < height {
+            let band_height = self.tile_height.min(height - y);
+            let first = y.saturating_sub(halo_y);
+            let last = (y + band_height).saturating_add(halo_y).min(height);
+            window.drain(..(first - top) as usize * row_len);
+            top = first;
+            while bottom < last {
+                let start = window.len();
+                window.resize(start + row_len, 0);
+                reader
+                    .read_row(&mut window[start..])
+                    .map_err(TileError::Read)?;
+                bottom += 1;
+            }
+
+            band.clear();
+            band.resize(row_len * band_height as usize, 0);
+            for x in (0..width).step_by(self.tile_width as usize) {
+                let region = Rect::new(x, y, self.tile_width.min(width - x), band_height);
+                self.filter_tile(&window, (width, top, bottom), region, &mut band);
+            }
+            for row in 0..band_height as usize {
+                writer
+                    .write_row(&band[row * row_len..(row + 1) * row_len])
+                    .map_err(TileError::Write)?;
+            }
+            y += band_height;
+        }
+        Ok(())
+    }
+
+    /// Filters the pixels of `region` into `band`, reading them and their halo from `window`,
+    /// which holds the rows `top..bottom` of an image `width` pixels wide.
+    fn filter_tile(
+        &self,
+        window: &[u32],
+        (width, top, bottom): (u32, u32, u32),
+        region: Rect,
+        band: &mut [u32],
+    ) {
+        let (halo_x, halo_y) = self.halo();
+        let source =
+            region
+                .inflate(halo_x, halo_y)
+                .intersect(Rect::new(0, top, width, bottom - top));
+        let mut pixels = Vec::with_capacity(source.width as usize * source.height as usize);
+        for y in source.y..source.bottom() {
+            let start = ((y - top) * width + source.x) as usize;
+            pixels.extend_from_slice(&window[start..start + source.width as usize]);
+        }
+        let mut tile =
+            SurfaceBuf::from_pixels(pixels, source.width, source.height, ColorChannel::RGBA32)
+                .expect("tile holds width * height pixels")
+                .with_color_space(self.color_space);
+        for (filter, color_interpolation) in &self.filters {
+            let mut surface = tile
+                .as_surface()
+                .with_color_interpolation(*color_interpolation);
+            Surface::in_place(&mut surface, |input, output| filter.apply(input, output));
+        }
+
+        let (dx, dy) = (region.x - source.x, region.y - source.y);
+        let (row_len, tile_len) = (width as usize, region.width as usize);
+        for row in 0..region.height {
+            let from = ((dy + row) * source.width + dx) as usize;
+            let to = row as usize * row_len + region.x as usize;
+            band[to..to + tile_len].copy_from_slice(&tile.pixels()[from..from + tile_len]);
+        }
+    }
+
+    pub fn color_transform(self, matrix: impl Into<ColorMatrix>) -> Self {
+        self.push(TileFilter::ColorTransform(matrix.into()))
+    }
+
+    pub fn color_transform_opacity(self, amount: f32) -> Self {
+        self.color_transform(ColorMatrix::opacity(amount))
+    }
+
+    pub fn color_transform_brightness(self, amount: f32) -> Self {
+        self.color_transform(ColorMatrix::brightness(amount))
+    }
+
+    pub fn color_transform_invert(self, amount: f32) -> Self {
+        self.color_transform(ColorMatrix::invert(amount))
+    }
+
+    pub fn color_transform_contrast(self, amount: f32) -> Self {
+        self.color_transform(ColorMatrix::contrast(amount))
+    }
+
+    pub fn color_transform_saturate(self, amount: f32) -> Self {
+        let matrix = ColorMatrix::saturate_in(amount, self.color_space);
+        self.color_transform(matrix)
+    }
+
+    pub fn color_transform_grayscale(self, amount: f32) -> Self {
+        let matrix = ColorMatrix::grayscale_in(amount, self.color_space);
+        self.color_transform(matrix)
+    }
+
+    pub fn color_transform_sepia(self, amount: f32) -> Self {
+        self.color_transform(ColorMatrix::sepia(amount))
+    }
+
+    pub fn color_transform_hue_rotate(self, angle: f32) -> Self {
+        let matrix = ColorMatrix::hue_rotate_in(angle, self.color_space);
+        self.color_transform(matrix)
+    }
+
+    pub fn color_transform_luminance_to_alpha(self) -> Self {
+        self.push(TileFilter::LuminanceToAlpha)
+    }
+
+    pub fn levels(self, black: f32, white: f32, gamma: f32) -> Self {
+        self.push(TileFilter::Levels(black, white, gamma))
+    }
+
+    pub fn curves(self, points: &[[f32; 2]]) -> Self {
+        self.curves_rgb(points, points, points)
+    }
+
+    pub fn curves_rgb(self, red: &[[f32; 2]], green: &[[f32; 2]], blue: &[[f32; 2]]) -> Self {
+        self.push(TileFilter::Curves(
+            red.to_vec(),
+            green.to_vec(),
+            blue.to_vec(),
+        ))
+    }
+
+    pub fn vibrance(self, amount: f32) -> Self {
+        self.push(TileFilter::Vibrance(amount))
+    }
+
+    pub fn temperature_tint(self, temperature: f32, tint: f32) -> Self {
+        self.push(TileFilter::TemperatureTint(temperature, tint))
+    }
+
+    pub fn shadows_highlights(self, shadows: f32, highlights: f32) -> Self {
+        self.push(TileFilter::ShadowsHighlights(shadows, highlights))
+    }
+
+    pub fn hsl_adjust(self, adjustments: &[(HueRange, HslAdjustment)]) -> Self {
+        self.push(TileFilter::HslAdjust(adjustments.to_vec()))
+    }
+
+    pub fn apply_lut(self, lut: &Lut3d, interpolation: LutInterpolation) -> Self {
+        self.push(TileFilter::Lut(lut.clone(), interpolation))
+    }
+
+    pub fn gaussian_blur(self, std_deviation_x: f32, std_deviation_y: f32) -> Self {
+        if std_deviation_x < 0.0 || std_deviation_y < 0.0 {
+            panic!("standard deviation can not be less than zero.")
+        }
+        self.push(TileFilter::GaussianBlur(std_deviation_x, std_deviation_y))
+    }
+
+    pub fn rank_filter(self, radius_x: u32, radius_y: u32, percentile: f32) -> Self {
+        if !(0.0..=1.0).contains(&percentile) {
+            panic!("percentile must be in the range [0, 1].")
+        }
+        self.push(TileFilter::RankFilter(radius_x, radius_y, percentile))
+    }
+
+    pub fn median_filter(self, radius_x: u32, radius_y: u32) -> Self {
+        self.rank_filter(radius_x, radius_y, 0.5)
+    }
+
+    pub fn min_filter(self, radius_x: u32, radius_y: u32) -> Self {
+        self.rank_filter(radius_x, radius_y, 0.0)
+    }
+
+    pub fn max_filter(self, radius_x: u32, radius_y: u32) -> Self {
+        self.rank_filter(radius_x, radius_y, 1.0)
+    }
+}
+
+#[cfg(feature = "image")]
+pub use png_rows::{PngRowReader, PngRowWriter};
+
+/// Streams rows from and to PNG files, and runs a [TiledPipeline] between image files.
+#[cfg(feature = "image")]
+mod png_rows {
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader, BufWriter, Seek, Write},
+        path::Path,
+    };
+
+    use image::{
+        ImageError, ImageFormat, ImageResult, RgbaImage,
+        error::{DecodingError, EncodingError, UnsupportedError, UnsupportedErrorKind},
+    };
+    use png::{BitDepth, ColorType, Transformations};
+
+    use super::{RowReader, RowWriter, TileError, TiledPipeline};
+
+    fn decoding_error(err: png::DecodingError) -> ImageError {
+        ImageError::Decoding(DecodingError::new(ImageFormat::Png.into(), err))
+    }
+
+    fn encoding_error(err: png::EncodingError) -> ImageError {
+        ImageError::Encoding(EncodingError::new(ImageFormat::Png.into(), err))
+    }
+
+    /// Packs a row of 8-bit gray, gray and alpha, RGB or RGBA samples as [crate::ColorChannel::RGBA32]
+    /// pixels, keeping the bytes as they are like [crate::ImageEditor] does.
+    fn pack_row(bytes: &[u8], samples: usize, row: &mut [u32]) {
+        for (pixel, bytes) in row.iter_mut().zip(bytes.chunks_exact(samples)) {
+            let rgba = match *bytes {
+                [gray] => [gray, gray, gray, 255],
+                [gray, alpha] => [gray, gray, gray, alpha],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!("PNG rows hold 1 to 4 samples per pixel"),
+            };
+            *pixel = u32::from_le_bytes(rgba);
+        }
+    }
+
+    /// Reads the rows of a PNG image one at a time as they are decoded.
+    ///
+    ///Every color type is expanded to 8-bit RGBA. Interlaced images are not stored row by row and
+    ///are rejected with [ImageError::Unsupported].
+    pub struct PngRowReader<R: BufRead + Seek> {
+        reader: png::Reader<R>,
+        samples: usize,
+    }
+
+    impl<R: BufRead + Seek> PngRowReader<R> {
+        pub fn new(input: R) -> ImageResult<Self> {
+            let mut decoder = png::Decoder::new(input);
+            decoder.set_transformations(Transformations::normalize_to_color8());
+            let reader = decoder.read_info().map_err(decoding_error)?;
+            if reader.info().interlaced {
+                return Err(ImageError::Unsupported(
+                    UnsupportedError::from_format_and_kind(
+                        ImageFormat::Png.into(),
+                        UnsupportedErrorKind::GenericFeature(
+                            "reading interlaced rows in order".into(),
+                        ),
+                    ),
+                ));
+            }
+            let samples = reader.output_color_type().0.samples();
+            Ok(Self { reader, samples })
+        }
+    }
+
+    impl<R: BufRead + Seek> RowReader for PngRowReader<R> {
+        type Error = ImageError;
+
+        fn width(&self) -> u32 {
+            self.reader.info().width
+        }
+
+        fn height(&self) -> u32 {
+            self.reader.info().height
+        }
+
+        fn read_row(&mut self, row: &mut [u32]) -> Result<(), Self::Error> {
+            let bytes = self
+                .reader
+                .next_row()
+                .map_err(decoding_error)?
+                .ok_or_else(|| decoding_error(png::DecodingError::LimitsExceeded))?;
+            pack_row(bytes.data(), self.samples, row);
+            Ok(())
+        }
+    }
+
+    /// Encodes rows into an 8-bit RGBA PNG image as they are written.
+    ///
+    ///Call [PngRowWriter::finish] after the last row to complete the image.
+    pub struct PngRowWriter<W: Write + 'static> {
+        writer: png::StreamWriter<'static, W>,
+        bytes: Vec<u8>,
+    }
+
+    impl<W: Write + 'static> PngRowWriter<W> {
+        pub fn new(output: W, width: u32, height: u32) -> ImageResult<Self> {
+            let mut encoder = png::Encoder::new(output, width, height);
+            encoder.set_color(ColorType::Rgba);
+            encoder.set_depth(BitDepth::Eight);
+            let writer = encoder
+                .write_header()
+                .and_then(|writer| writer.into_stream_writer())
+                .map_err(encoding_error)?;
+            Ok(Self {
+                writer,
+                bytes: Vec::new(),
+            })
+        }
+
+        /// Writes the end of the image.
+        pub fn finish(self) -> ImageResult<()> {
+            self.writer.finish().map_err(encoding_error)
+        }
+    }
+
+    impl<W: Write + 'static> RowWriter for PngRowWriter<W> {
+        type Error = ImageError;
+
+        fn write_row(&mut self, row: &[u32]) -> Result<(), Self::Error> {
+            self.bytes.clear();
+            self.bytes
+                .extend(row.iter().flat_map(|pixel| pixel.to_le_bytes()));
+            Ok(self.writer.write_all(&self.bytes)?)
+        }
+    }
+
+    /// Reads the rows of an image decoded in full, for formats that can not be streamed.
+    struct DecodedRows {
+        image: RgbaImage,
+        y: u32,
+    }
+
+    impl RowReader for DecodedRows {
+        type Error = ImageError;
+
+        fn width(&self) -> u32 {
+            self.image.width()
+        }
+
+        fn height(&self) -> u32 {
+            self.image.height()
+        }
+
+        fn read_row(&mut self, row: &mut [u32]) -> Result<(), Self::Error> {
+            let len = self.image.width() as usize * 4;
+            let start = self.y as usize * len;
+            pack_row(&self.image.as_raw()[start..start + len], 4, row);
+            self.y += 1;
+            Ok(())
+        }
+    }
+
+    /// Collects rows into an image, for formats that can not be streamed.
+    struct ImageRows {
+        image: RgbaImage,
+        y: u32,
+    }
+
+    impl RowWriter for ImageRows {
+        type Error = ImageError;
+
+        fn write_row(&mut self, row: &[u32]) -> Result<(), Self::Error> {
+            let len = self.image.width() as usize * 4;
+            let start = self.y as usize * len;
+            let bytes: &mut [u8] = &mut self.image;
+            for (bytes, pixel) in bytes[start..start + len].chunks_exact_mut(4).zip(row) {
+                bytes.copy_from_slice(&pixel.to_le_bytes());
+            }
+            self.y += 1;
+            Ok(())
+        }
+    }
+
+    fn image_error(err: TileError<ImageError, ImageError>) -> ImageError {
+        match err {
+            TileError::Read(err) | TileError::Write(err) => err,
+        }
+    }
+
+    impl TiledPipeline {
+        /// Filters an image file into another, streaming the rows of PNG files.
+        ///
+        ///Non-interlaced PNG images are decoded and encoded row by row, so only a band of tiles is
+        ///held in memory. Other formats are decoded or encoded in full. Pixels are read as they
+        ///are stored, like [crate::ImageEditor] reads them.
+        pub fn process_file(
+            &self,
+            input_path: impl AsRef<Path>,
+            output_path: impl AsRef<Path>,
+        ) -> ImageResult<()> {
+            let input_path = input_path.as_ref();
+            if ImageFormat::from_path(input_path)? == ImageFormat::Png {
+                let input = BufReader::new(File::open(input_path)?);
+                match PngRowReader::new(input) {
+                    Ok(mut reader) => return self.write_file(&mut reader, output_path.as_ref()),
+                    Err(ImageError::Unsupported(_)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            let image = image::open(input_path)?.into_rgba8();
+            self.write_file(&mut DecodedRows { image, y: 0 }, output_path.as_ref())
+        }
+
+        fn write_file<R: RowReader<Error = ImageError>>(
+            &self,
+            reader: &mut R,
+            output_path: &Path,
+        ) -> ImageResult<()> {
+            let (width, height) = (reader.width(), reader.height());
+            if ImageFormat::from_path(output_path)? == ImageFormat::Png {
+                let output = BufWriter::new(File::create(output_path)?);
+                let mut writer = PngRowWriter::new(output, width, height)?;
+                self.run(reader, &mut writer).map_err(image_error)?;
+                writer.finish()
+            } else {
+                let mut writer = ImageRows {
+                    image: RgbaImage::new(width, height),
+                    y: 0,
+                };
+                self.run(reader, &mut writer).map_err(image_error)?;
+                writer.image.save(output_path)
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{EdgeMode, ResampleFilter, Surface, SurfaceView, utils::*};
+use alloc::vec::Vec;
+
+/// Returns the inverse of a 2x3 affine matrix `[a, b, c, d, e, f]`, or `None` if it is singular.
+///
+///Only a zero or non-finite determinant is singular, so strong downscales with a tiny determinant
+///still invert.
+pub(crate) fn invert_affine([a, b, c, d, e, f]: [f32; 6]) -> Option<[f32; 6]> {
+    let det = a * d - b * c;
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+    let inv = 1.0 / det;
+    let inverse = [
+        d * inv,
+        -b * inv,
+        -c * inv,
+        a * inv,
+        (c * f - d * e) * inv,
+        (b * e - a * f) * inv,
+    ];
+    inverse
+        .iter()
+        .all(|value| value.is_finite())
+        .then_some(inverse)
+}
+
+/// Largest distance in source pixels the resampling kernel reaches on either side of a sample.
+///
+///Downscaling widens the kernel by the scale factor. Beyond this reach the kernel is kept at this
+///width, so extreme downscales alias slightly instead of looping over huge kernels.
+const MAX_SUPPORT: f32 = 32.0;
+
+/// Returns the factor the kernel of `filter` is widened by when one output pixel step moves
+/// `step` pixels in the source.
+pub(crate) fn kernel_scale(filter: ResampleFilter, step: f32) -> f32 {
+    step.max(1.0).min(MAX_SUPPORT / filter.radius())
+}
+
+impl ResampleFilter {
+    /// Radius of the filter kernel, in source pixels, at a scale of 1.
+    pub(crate) fn radius(&self) -> f32 {
+        match self {
+            ResampleFilter::Nearest => 0.5,
+            ResampleFilter::Bilinear => 1.0,
+            ResampleFilter::Bicubic => 2.0,
+            ResampleFilter::Lanczos => 3.0,
+        }
+    }
+
+    fn weight(&self, t: f32) -> f32 {
+        let t = t.abs();
+        match self {
+            ResampleFilter::Nearest => {
+                if t <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResampleFilter::Bilinear => (1.0 - t).max(0.0),
+            // Catmull-Rom spline (a = -0.5)
+            ResampleFilter::Bicubic => {
+                if t < 1.0 {
+                    1.5 * t * t * t - 2.5 * t * t + 1.0
+                } else if t < 2.0 {
+                    -0.5 * t * t * t + 2.5 * t * t - 4.0 * t + 2.0
+                } else {
+                    0.0
+                }
+            }
+            ResampleFilter::Lanczos => {
+                if t < f32::EPSILON {
+                    1.0
+                } else if t < 3.0 {
+                    let pt = core::f32::consts::PI * t;
+                    3.0 * f32::sin(pt) * f32::sin(pt / 3.0) / (pt * pt)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Maps a source coordinate onto the surface according to the edge mode.
+#[inline(always)]
+fn edge_coordinate(value: i64, size: u32, edge_mode: EdgeMode) -> Option<u32> {
+    let size = size as i64;
+    match edge_mode {
+        EdgeMode::None => (0..size).contains(&value).then_some(value as u32),
+        EdgeMode::Duplicate => Some(value.clamp(0, size - 1) as u32),
+        EdgeMode::Wrap => Some(value.rem_euclid(size) as u32),
+    }
+}
+
+/// Kernel weights along one axis for a single sample position.
+struct AxisWeights {
+    start: i64,
+    weights: Vec<f32>,
+}
+
+impl AxisWeights {
+    fn new() -> Self {
+        Self {
+            start: 0,
+            weights: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, filter: ResampleFilter, center: f32, scale: f32) {
+        let support = filter.radius() * scale;
+        self.start = (center - support).floor() as i64;
+        let end = (center + support).ceil() as i64;
+        self.weights.clear();
+        self.weights
+            .extend((self.start..=end).map(|i| filter.weight((i as f32 + 0.5 - center) / scale)));
+        let sum: f32 = self.weights.iter().sum();
+        if sum.abs() > f32::EPSILON {
+            self.weights.iter_mut().for_each(|w| *w /= sum);
+        }
+    }
+}
+
+/// Resamples `input` into `output`, where `inverse` maps output coordinates back to the input.
+///
+/// Premultiplied channels are interpolated directly, which is the correct way to filter
+/// colors with varying alpha. When minifying, the kernel is widened by the scale factor so the
+/// filter also acts as a low-pass and avoids aliasing.
+pub(crate) fn affine_transform(
+    input: &SurfaceView,
+    output: &mut Surface,
+    inverse: [f32; 6],
+    filter: ResampleFilter,
+    edge_mode: EdgeMode,
+) {
+    let [a, b, c, d, e, f] = inverse;
+    // How far one output pixel step moves in the source, along each source axis.
+    let scale_x = kernel_scale(filter, f32::hypot(a, c));
+    let scale_y = kernel_scale(filter, f32::hypot(b, d));
+    let mut weights_x = AxisWeights::new();
+    let mut weights_y = AxisWeights::new();
+
+    for y in 0..output.height {
+        for x in 0..output.width {
+            let (ox, oy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let sx = a * ox + c * oy + e;
+            let sy = b * ox + d * oy + f;
+
+            if let ResampleFilter::Nearest = filter {
+                let px = edge_coordinate(sx.floor() as i64, input.width, edge_mode);
+                let py = edge_coordinate(sy.floor() as i64, input.height, edge_mode);
+                let [r, g, b, a] = match (px, py) {
+                    (Some(px), Some(py)) => init_load_pixel(input, px, py, input.channel),
+                    _ => [0, 0, 0, 0],
+                };
+                store_pixel(output, x, y, r, g, b, a);
+                continue;
+            }
+
+            weights_x.update(filter, sx, scale_x);
+            weights_y.update(filter, sy, scale_y);
+
+            let mut sum = [0.0f32; 4];
+            for (j, wy) in weights_y.weights.iter().enumerate() {
+                if *wy == 0.0 {
+                    continue;
+                }
+                let Some(py) = edge_coordinate(weights_y.start + j as i64, input.height, edge_mode)
+                else {
+                    continue;
+                };
+                for (i, wx) in weights_x.weights.iter().enumerate() {
+                    if *wx == 0.0 {
+                        continue;
+                    }
+                    let Some(px) =
+                        edge_coordinate(weights_x.start + i as i64, input.width, edge_mode)
+                    else {
+                        continue;
+                    };
+                    let pixel = init_load_pixel(input, px, py, input.channel);
+                    let weight = wx * wy;
+                    for (value, channel) in sum.iter_mut().zip(pixel) {
+                        *value += weight * channel as f32;
+                    }
+                }
+            }
+
+            let a = clamp_pixel(sum[3].max(0.0).round() as u32);
+            let r = clamp_pixel(sum[0].max(0.0).round() as u32).min(a);
+            let g = clamp_pixel(sum[1].max(0.0).round() as u32).min(a);
+            let b = clamp_pixel(sum[2].max(0.0).round() as u32).min(a);
+            store_pixel(output, x, y, r, g, b, a);
+        }
+    }
+}
@@ -1,10 +1,17 @@
-use crate::{ColorChannel, Surface};
-use std::{cell::RefCell, f32::consts::PI, rc::Rc};
+#[cfg(not(any(feature = "std", test)))]
+use crate::math::FloatMath;
+use crate::{
+    ColorChannel, Surface, SurfaceView,
+    format::{Argb8, Bgra8, PixelAccess, PixelFormat, PixelSource, Rgba8},
+};
+use core::f32::consts::PI;
 
 #[allow(clippy::excessive_precision)]
 const KERNEL_FACTOR: f32 = 1.8799712059732503;
+pub(crate) const MAX_KERNEL_SIZE: u32 = 512;
 
 /// Helper function to find file in `res` folder in root directory
+#[cfg(feature = "std")]
 pub fn get_resource_path(dirs: &[&str], filename: &str) -> std::path::PathBuf {
     let mut manifest_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     manifest_dir = manifest_dir.join("res");
@@ -29,14 +36,9 @@ pub(crate) fn alpha(pixel: &u32, channel: ColorChannel) -> u32 {
 #[inline(always)]
 pub(crate) fn unpack_pixel(pixel: &u32, channel: ColorChannel) -> [u32; 4] {
     match channel {
-        ColorChannel::ARGB32 => {
-            let [a, r, g, b] = pixel.to_le_bytes();
-            [r as u32, g as u32, b as u32, a as u32]
-        }
-        ColorChannel::RGBA32 => {
-            let [r, g, b, a] = pixel.to_le_bytes();
-            [r as u32, g as u32, b as u32, a as u32]
-        }
+        ColorChannel::ARGB32 => Argb8::load(&pixel.to_le_bytes()),
+        ColorChannel::RGBA32 => Rgba8::load(&pixel.to_le_bytes()),
+        ColorChannel::BGRA32 => Bgra8::load(&pixel.to_le_bytes()),
     }
 }
 
@@ -49,7 +51,7 @@ pub(crate) fn get_pixel_mut<'a>(surface: &'a mut Surface, x: u32, y: u32) -> &'a
 }
 
 #[inline(always)]
-pub(crate) fn get_pixel<'a>(surface: &'a Surface, x: u32, y: u32) -> &'a u32 {
+pub(crate) fn get_pixel<'a>(surface: &SurfaceView<'a>, x: u32, y: u32) -> &'a u32 {
     // let surface_stride = surface.stride;
     &surface.pixels[(y * surface.stride + x) as usize]
     // .get(y * surface.stride + x)
@@ -57,14 +59,14 @@ pub(crate) fn get_pixel<'a>(surface: &'a Surface, x: u32, y: u32) -> &'a u32 {
 }
 
 #[inline(always)]
-pub(crate) fn load_pixel(input: &mut Surface, x: u32, y: u32, channel: ColorChannel) -> [u32; 4] {
+pub(crate) fn load_pixel(input: &SurfaceView, x: u32, y: u32, channel: ColorChannel) -> [u32; 4] {
     let pixel = get_pixel(input, x, y);
     unpack_pixel(pixel, channel)
 }
 
 #[inline(always)]
 pub(crate) fn init_load_pixel(
-    input: &mut Surface,
+    input: &SurfaceView,
     x: u32,
     y: u32,
     channel: ColorChannel,
@@ -72,18 +74,20 @@ pub(crate) fn init_load_pixel(
     load_pixel(input, x, y, channel)
 }
 #[inline(always)]
-pub(crate) fn pack_pixel(r: u32, g: u32, b: u32, a: u32) -> u32 {
-    u32::from_le_bytes([r as u8, g as u8, b as u8, a as u8])
-    // (r << 24) | (g << 16) | (b << 8) | (a)
+pub(crate) fn pack_pixel(r: u32, g: u32, b: u32, a: u32, channel: ColorChannel) -> u32 {
+    let mut bytes = [0; 4];
+    match channel {
+        ColorChannel::ARGB32 => Argb8::store(&mut bytes, [r, g, b, a]),
+        ColorChannel::RGBA32 => Rgba8::store(&mut bytes, [r, g, b, a]),
+        ColorChannel::BGRA32 => Bgra8::store(&mut bytes, [r, g, b, a]),
+    }
+    u32::from_le_bytes(bytes)
 }
 
-// #[inline(always)]
-// pub(crate) fn pack_pixel(r: u32, g: u32, b: u32, a: u32) -> u32 {
-//     (a << 24) | (r << 16) | (g << 8) | (b)
-// }
 #[inline(always)]
 pub(crate) fn store_pixel(output: &mut Surface, x: u32, y: u32, r: u32, g: u32, b: u32, a: u32) {
-    *get_pixel_mut(output, x, y) = pack_pixel(r, g, b, a)
+    let channel = output.channel;
+    *get_pixel_mut(output, x, y) = pack_pixel(r, g, b, a, channel)
 }
 
 #[inline(always)]
@@ -94,6 +98,7 @@ pub(crate) fn premultiply_pixel(r: &mut u32, g: &mut u32, b: &mut u32, a: &mut u
 }
 
 #[inline(always)]
+#[allow(clippy::manual_checked_ops)]
 pub(crate) fn unpremultiply_pixel(r: &mut u32, g: &mut u32, b: &mut u32, a: &mut u32) {
     if *a != 0 {
         *r = (255 * *r) / *a;
@@ -123,7 +128,7 @@ pub(crate) fn clamp_pixel(pixel: u32) -> u32 {
 }
 
 #[inline(always)]
-pub(crate) fn overlap_surface3(a: &mut Surface, b: &mut Surface, c: &mut Surface) {
+pub(crate) fn overlap_surface3(a: &mut SurfaceView, b: &mut SurfaceView, c: &mut Surface) {
     let mut _width = a.width;
     let mut _height = a.height;
     if b.width < _width {
@@ -147,7 +152,7 @@ pub(crate) fn overlap_surface3(a: &mut Surface, b: &mut Surface, c: &mut Surface
     c.height = _height;
 }
 
-pub(crate) fn overlap_surface(a: &mut Surface, b: &mut Surface) {
+pub(crate) fn overlap_surface(a: &mut SurfaceView, b: &mut Surface) {
     let mut _width = a.width;
     let mut _height = a.height;
     if b.width < _width {
@@ -166,6 +171,228 @@ pub(crate) fn deg2rad(angle: f32) -> f32 {
     angle * (PI / 180.0)
 }
 
+pub(crate) fn opacity_matrix(amount: f32) -> [f32; 20] {
+    [
+        1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        amount, 0.0,
+    ]
+}
+
+pub(crate) fn brightness_matrix(amount: f32) -> [f32; 20] {
+    [
+        amount, 0.0, 0.0, 0.0, 0.0, 0.0, amount, 0.0, 0.0, 0.0, 0.0, 0.0, amount, 0.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+    ]
+}
+
+pub(crate) fn invert_matrix(amount: f32) -> [f32; 20] {
+    let scale = 1.0 - 2.0 * amount;
+    [
+        scale, 0.0, 0.0, 0.0, amount, 0.0, scale, 0.0, 0.0, amount, 0.0, 0.0, scale, 0.0, amount,
+        0.0, 0.0, 0.0, 1.0, 0.0,
+    ]
+}
+
+pub(crate) fn contrast_matrix(amount: f32) -> [f32; 20] {
+    let offset = (1.0 - amount) * 0.5;
+    [
+        amount, 0.0, 0.0, 0.0, offset, 0.0, amount, 0.0, 0.0, offset, 0.0, 0.0, amount, 0.0,
+        offset, 0.0, 0.0, 0.0, 1.0, 0.0,
+    ]
+}
+
+pub(crate) fn saturate_matrix(amount: f32) -> [f32; 20] {
+    [
+        0.213 + 0.787 * amount,
+        0.715 - 0.715 * amount,
+        0.072 - 0.072 * amount,
+        0.0,
+        0.0,
+        0.213 - 0.213 * amount,
+        0.715 + 0.285 * amount,
+        0.072 - 0.072 * amount,
+        0.0,
+        0.0,
+        0.213 - 0.213 * amount,
+        0.715 - 0.715 * amount,
+        0.072 + 0.928 * amount,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
+pub(crate) fn grayscale_matrix(amount: f32) -> [f32; 20] {
+    let inv_amount = 1.0 - amount;
+    [
+        inv_amount + amount * 0.2126,
+        amount * 0.7152,
+        amount * 0.0722,
+        0.0,
+        0.0,
+        amount * 0.2126,
+        inv_amount + amount * 0.7152,
+        amount * 0.0722,
+        0.0,
+        0.0,
+        amount * 0.2126,
+        amount * 0.7152,
+        inv_amount + amount * 0.0722,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
+pub(crate) fn sepia_matrix(amount: f32) -> [f32; 20] {
+    let inv_amount = 1.0 - amount;
+    [
+        0.393 + 0.607 * inv_amount,
+        0.769 - 0.769 * inv_amount,
+        0.189 - 0.189 * inv_amount,
+        0.0,
+        0.0,
+        0.349 - 0.349 * inv_amount,
+        0.686 + 0.314 * inv_amount,
+        0.168 - 0.168 * inv_amount,
+        0.0,
+        0.0,
+        0.272 - 0.272 * inv_amount,
+        0.534 - 0.534 * inv_amount,
+        0.131 + 0.869 * inv_amount,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
+pub(crate) fn hue_rotate_matrix(angle: f32) -> [f32; 20] {
+    let a1 = f32::cos(deg2rad(angle));
+    let a2 = f32::sin(deg2rad(angle));
+    [
+        0.213 + a1 * 0.787 - a2 * 0.213,
+        0.715 - a1 * 0.715 - a2 * 0.715,
+        0.072 - a1 * 0.072 + a2 * 0.928,
+        0.0,
+        0.0,
+        0.213 - a1 * 0.213 + a2 * 0.143,
+        0.715 + a1 * 0.285 + a2 * 0.140,
+        0.072 - a1 * 0.072 - a2 * 0.283,
+        0.0,
+        0.0,
+        0.213 - a1 * 0.213 - a2 * 0.787,
+        0.715 - a1 * 0.715 + a2 * 0.715,
+        0.072 + a1 * 0.928 + a2 * 0.072,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
+/// Saturate matrix for arbitrary luminance coefficients `[lr, lg, lb]`.
+pub(crate) fn luminance_saturate_matrix(amount: f32, [lr, lg, lb]: [f32; 3]) -> [f32; 20] {
+    [
+        lr + (1.0 - lr) * amount,
+        lg - lg * amount,
+        lb - lb * amount,
+        0.0,
+        0.0,
+        lr - lr * amount,
+        lg + (1.0 - lg) * amount,
+        lb - lb * amount,
+        0.0,
+        0.0,
+        lr - lr * amount,
+        lg - lg * amount,
+        lb + (1.0 - lb) * amount,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
+/// Grayscale matrix for arbitrary luminance coefficients `[lr, lg, lb]`.
+pub(crate) fn luminance_grayscale_matrix(amount: f32, [lr, lg, lb]: [f32; 3]) -> [f32; 20] {
+    let inv_amount = 1.0 - amount;
+    [
+        inv_amount + amount * lr,
+        amount * lg,
+        amount * lb,
+        0.0,
+        0.0,
+        amount * lr,
+        inv_amount + amount * lg,
+        amount * lb,
+        0.0,
+        0.0,
+        amount * lr,
+        amount * lg,
+        inv_amount + amount * lb,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
+/// Hue rotate matrix for arbitrary luminance coefficients `[lr, lg, lb]`.
+///
+/// Rotates around the gray axis while keeping luminance constant. The sine terms of the first and
+/// last rows follow the filter effects specification, and the middle row is solved so that every
+/// column has zero luminance-weighted sum.
+pub(crate) fn luminance_hue_rotate_matrix(angle: f32, [lr, lg, lb]: [f32; 3]) -> [f32; 20] {
+    let a1 = f32::cos(deg2rad(angle));
+    let a2 = f32::sin(deg2rad(angle));
+    let top = [-lr, -lg, 1.0 - lb];
+    let bottom = [-(1.0 - lr), lg, lb];
+    let middle = [0, 1, 2].map(|i| -(lr * top[i] + lb * bottom[i]) / lg);
+    [
+        lr + a1 * (1.0 - lr) + a2 * top[0],
+        lg - a1 * lg + a2 * top[1],
+        lb - a1 * lb + a2 * top[2],
+        0.0,
+        0.0,
+        lr - a1 * lr + a2 * middle[0],
+        lg + a1 * (1.0 - lg) + a2 * middle[1],
+        lb - a1 * lb + a2 * middle[2],
+        0.0,
+        0.0,
+        lr - a1 * lr + a2 * bottom[0],
+        lg - a1 * lg + a2 * bottom[1],
+        lb + a1 * (1.0 - lb) + a2 * bottom[2],
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    ]
+}
+
 pub(crate) fn srgb_to_linear_rgb(r: &mut u32, g: &mut u32, b: &mut u32) {
     *r = SRGB_TO_LINEAR_RGB_TABLE[*r as usize];
     *g = SRGB_TO_LINEAR_RGB_TABLE[*g as usize];
@@ -217,199 +444,200 @@ const LINEAR_RGB_TO_SRGB_TABLE: [u32;256] = [
     248, 249, 249, 250, 250, 251, 251, 251, 252, 252, 253, 253, 254, 254, 255, 255,
 ];
 
-pub(crate) fn blur_store_pixel(
-    output: &mut Surface,
-    (x, y): (u32, u32),
-    (r, g, b, a): (u32, u32, u32, u32),
-    k: u32,
-) {
-    store_pixel(output, x, y, r / k, g / k, b / k, a / k);
-}
-
 pub(crate) fn calc_kernel_size(std_deviation: f32) -> u32 {
     f32::floor(std_deviation * KERNEL_FACTOR + 0.5) as u32
 }
 
-pub(crate) fn box_blur(
-    input: Rc<RefCell<&mut Surface>>,
-    output: Rc<RefCell<&mut Surface>>,
-    intermediate: &mut [u32],
-    mut kernel_width: u32,
-    mut kernel_height: u32,
+/// Applies a 5x4 color matrix to every pixel, loading and storing through the pixel formats
+/// of the input and output.
+pub(crate) fn color_transform_pixels<I: PixelSource, O: PixelAccess>(
+    input: &I,
+    output: &mut O,
+    matrix: &[f32; 20],
 ) {
-    let (mut offset, mut pixel);
-    let (mut sum_r, mut sum_g, mut sum_b, mut sum_a);
-
-    let channel = input.borrow().channel;
-    let output_width = output.borrow().width;
-    let output_height = output.borrow().height;
-    if kernel_width > 0 {
-        kernel_width = kernel_width.min(output_width);
-        for y in 0..output_height {
-            sum_r = 0;
-            sum_g = 0;
-            sum_b = 0;
-            sum_a = 0;
-
-            for x in 0..kernel_width {
-                let index = (x % kernel_width) as usize;
-
-                intermediate[index] = *get_pixel(*input.borrow_mut(), x, y);
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r += r;
-                sum_g += g;
-                sum_b += b;
-                sum_a += a;
-
-                // offset = x - kernel_width / 2;
-                offset = x.wrapping_sub(kernel_width.wrapping_div(2));
-                // if offset >= 0 && offset < output.borrow().width {
-                if offset < output_width {
-                    blur_store_pixel(
-                        *output.borrow_mut(),
-                        (offset, y),
-                        (sum_r, sum_g, sum_b, sum_a),
-                        kernel_width,
-                    );
-                }
-            }
+    for y in 0..output.height() {
+        for x in 0..output.width() {
+            let [mut r, mut g, mut b, mut a] = input.load(x, y);
+            unpremultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+
+            let rr = r as f32 * matrix[0]
+                + g as f32 * matrix[1]
+                + b as f32 * matrix[2]
+                + a as f32 * matrix[3]
+                + matrix[4] * 255.0;
+            let gg = r as f32 * matrix[5]
+                + g as f32 * matrix[6]
+                + b as f32 * matrix[7]
+                + a as f32 * matrix[8]
+                + matrix[9] * 255.0;
+            let bb = r as f32 * matrix[10]
+                + g as f32 * matrix[11]
+                + b as f32 * matrix[12]
+                + a as f32 * matrix[13]
+                + matrix[14] * 255.0;
+            let aa = r as f32 * matrix[15]
+                + g as f32 * matrix[16]
+                + b as f32 * matrix[17]
+                + a as f32 * matrix[18]
+                + matrix[19] * 255.0;
+            r = clamp_pixel(rr as u32);
+            g = clamp_pixel(gg as u32);
+            b = clamp_pixel(bb as u32);
+            a = clamp_pixel(aa as u32);
+
+            premultiply_pixel(&mut r, &mut g, &mut b, &mut a);
+            output.store(x, y, [r, g, b, a]);
+        }
+    }
+}
 
-            for x in kernel_width..output_width {
-                let index = (x % kernel_width) as usize;
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r -= r;
-                sum_g -= g;
-                sum_b -= b;
-                sum_a -= a;
-                intermediate[index] = *get_pixel(*input.borrow(), x, y);
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r += r;
-                sum_g += g;
-                sum_b += b;
-                sum_a += a;
-
-                offset = x - kernel_width / 2;
-                blur_store_pixel(
-                    *output.borrow_mut(),
-                    (offset, y),
-                    (sum_r, sum_g, sum_b, sum_a),
-                    kernel_width,
-                );
-            }
+/// The direction of a box blur pass.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BlurAxis {
+    Horizontal,
+    Vertical,
+}
 
-            for x in output_width..(output_width + kernel_width) {
-                let index = (x % kernel_width) as usize;
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r -= r;
-                sum_g -= g;
-                sum_b -= b;
-                sum_a -= a;
-
-                offset = x - kernel_width / 2;
-                // if offset >= 0 && offset < output.borrow().width {
-                if offset < output_width {
-                    blur_store_pixel(
-                        *output.borrow_mut(),
-                        (offset, y),
-                        (sum_r, sum_g, sum_b, sum_a),
-                        kernel_width,
-                    );
-                }
-            }
-        }
+/// Approximates a Gaussian blur with three box blurs along each axis with a non-zero kernel.
+///
+///The first pass reads `input` and the others filter `output` in place, so at most one of the
+///kernels may be zero.
+pub(crate) fn gaussian_blur_pixels<I: PixelSource, O: PixelAccess>(
+    input: &I,
+    output: &mut O,
+    kernel_width: u32,
+    kernel_height: u32,
+) {
+    let mut intermediate = [[0; 4]; MAX_KERNEL_SIZE as usize];
+    let mut passes = [
+        (BlurAxis::Horizontal, kernel_width),
+        (BlurAxis::Vertical, kernel_height),
+    ]
+    .into_iter()
+    .filter(|&(_, kernel)| kernel > 0)
+    .flat_map(|pass| [pass; 3]);
+    if let Some((axis, kernel)) = passes.next() {
+        box_blur(input, output, &mut intermediate, axis, kernel);
+    }
+    for (axis, kernel) in passes {
+        box_blur_in_place(output, &mut intermediate, axis, kernel);
     }
+}
 
-    if kernel_height > 0 {
-        kernel_height = kernel_height.min(output_height);
-        for x in 0..output_width {
-            sum_r = 0;
-            sum_g = 0;
-            sum_b = 0;
-            sum_a = 0;
-
-            for y in 0..kernel_height {
-                let index = (y % kernel_height) as usize;
-                intermediate[index] = *get_pixel(*input.borrow(), x, y);
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r += r;
-                sum_g += g;
-                sum_b += b;
-                sum_a += a;
-
-                // offset = y - kernel_height / 2;
-                offset = y.wrapping_sub(kernel_height.wrapping_div(2));
-                // if offset >= 0 && offset < output.borrow().height {
-                if offset < output_height {
-                    blur_store_pixel(
-                        *output.borrow_mut(),
-                        (x, offset),
-                        (sum_r, sum_g, sum_b, sum_a),
-                        kernel_height,
-                    );
-                }
-            }
+/// Box blurs every row or column of `input` into `output` with a window of `kernel` pixels.
+pub(crate) fn box_blur<I: PixelSource, O: PixelAccess>(
+    input: &I,
+    output: &mut O,
+    intermediate: &mut [[u32; 4]],
+    axis: BlurAxis,
+    kernel: u32,
+) {
+    let size = (output.width(), output.height());
+    box_blur_lines(
+        &mut (input, output),
+        size,
+        intermediate,
+        axis,
+        kernel,
+        |(input, _), x, y| input.load(x, y),
+        |(_, output), x, y, rgba| output.store(x, y, rgba),
+    );
+}
 
-            for y in kernel_height..output_height {
-                let index = (y % kernel_height) as usize;
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r -= r;
-                sum_g -= g;
-                sum_b -= b;
-                sum_a -= a;
-                intermediate[index] = *get_pixel(*input.borrow(), x, y);
-                pixel = intermediate[index];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r += r;
-                sum_g += g;
-                sum_b += b;
-                sum_a += a;
-
-                offset = y - kernel_height / 2;
-                blur_store_pixel(
-                    *output.borrow_mut(),
-                    (x, offset),
-                    (sum_r, sum_g, sum_b, sum_a),
-                    kernel_height,
-                );
-            }
+/// Box blurs every row or column of `surface` into itself.
+pub(crate) fn box_blur_in_place<O: PixelAccess>(
+    surface: &mut O,
+    intermediate: &mut [[u32; 4]],
+    axis: BlurAxis,
+    kernel: u32,
+) {
+    let size = (surface.width(), surface.height());
+    box_blur_lines(
+        surface,
+        size,
+        intermediate,
+        axis,
+        kernel,
+        |surface, x, y| surface.load(x, y),
+        |surface, x, y, rgba| surface.store(x, y, rgba),
+    );
+}
 
-            for y in output_height..(output_height + kernel_height) {
-                pixel = intermediate[(y % kernel_height) as usize];
-                let [r, g, b, a] = unpack_pixel(&pixel, channel);
-
-                sum_r -= r;
-                sum_g -= g;
-                sum_b -= b;
-                sum_a -= a;
-
-                offset = y - kernel_height / 2;
-                // if offset >= 0 && offset < output.borrow().height {
-                if offset < output_height {
-                    blur_store_pixel(
-                        *output.borrow_mut(),
-                        (x, offset),
-                        (sum_r, sum_g, sum_b, sum_a),
-                        kernel_height,
-                    );
-                }
+/// Slides a window of `kernel` pixels along every line of `axis`, keeping the pixels inside it
+/// in `intermediate`.
+///
+///Every pixel is loaded before the window centered on it is stored, and stores trail the loads
+///by half a window, so `load` and `store` may access the same surface through `state`.
+fn box_blur_lines<S>(
+    state: &mut S,
+    (width, height): (u32, u32),
+    intermediate: &mut [[u32; 4]],
+    axis: BlurAxis,
+    kernel: u32,
+    load: impl Fn(&S, u32, u32) -> [u32; 4],
+    store: impl Fn(&mut S, u32, u32, [u32; 4]),
+) {
+    let (length, lines) = match axis {
+        BlurAxis::Horizontal => (width, height),
+        BlurAxis::Vertical => (height, width),
+    };
+    let position = |line, offset| match axis {
+        BlurAxis::Horizontal => (offset, line),
+        BlurAxis::Vertical => (line, offset),
+    };
+    let kernel = kernel.min(length);
+    if kernel == 0 {
+        return;
+    }
+    for line in 0..lines {
+        let mut sum = [0u32; 4];
+        for offset in 0..length + kernel {
+            let load = || {
+                let (x, y) = position(line, offset);
+                load(state, x, y)
+            };
+            if let Some((center, rgba)) =
+                box_blur_step(&mut sum, intermediate, kernel, offset, length, load)
+            {
+                let (x, y) = position(line, center);
+                store(state, x, y, rgba);
             }
         }
     }
 }
 
+/// Moves a box blur window of `kernel` pixels one pixel along a line of `length` pixels, so it
+/// ends at `offset`.
+///
+///`ring` holds the pixels inside the window and `sum` their total. `load` reads the pixel at
+///`offset` and is only called inside the line. Returns the position the window is centered on
+///and the average of the window, once the center is inside the line.
+#[inline(always)]
+pub(crate) fn box_blur_step(
+    sum: &mut [u32; 4],
+    ring: &mut [[u32; 4]],
+    kernel: u32,
+    offset: u32,
+    length: u32,
+    load: impl FnOnce() -> [u32; 4],
+) -> Option<(u32, [u32; 4])> {
+    let index = (offset % kernel) as usize;
+    if offset >= kernel {
+        for (sum, channel) in sum.iter_mut().zip(ring[index]) {
+            *sum -= channel;
+        }
+    }
+    if offset < length {
+        ring[index] = load();
+        for (sum, channel) in sum.iter_mut().zip(ring[index]) {
+            *sum += channel;
+        }
+    }
+    // offset - kernel / 2, skipped while the window starts before the line
+    let center = offset.wrapping_sub(kernel / 2);
+    (center < length).then(|| (center, sum.map(|channel| channel / kernel)))
+}
+
 #[inline(always)]
 pub(crate) fn clamp_and_store_pixel(
     output: &mut Surface,
@@ -436,7 +664,7 @@ pub fn blend_normal_op(s: u32, d: u32, sa: u32, _da: u32) -> u32 {
     s + div255(d * (255 - sa))
 }
 
-pub(crate) fn blend_normal(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_normal(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -454,7 +682,7 @@ pub fn blend_multiply_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
     div255(s * d + s * (255 - da) + d * (255 - sa))
 }
 
-pub(crate) fn blend_multiply(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_multiply(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -472,7 +700,7 @@ pub fn blend_screen_op(s: u32, d: u32, _sa: u32, _da: u32) -> u32 {
     s + d - div255(s * d)
 }
 
-pub(crate) fn blend_screen(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_screen(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -495,7 +723,7 @@ pub fn blend_overlay_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
     }
 }
 
-pub(crate) fn blend_overlay(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_overlay(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -518,7 +746,7 @@ pub fn blend_darken_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
         d + s - div255(sda)
     }
 }
-pub(crate) fn blend_darken(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_darken(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -542,7 +770,7 @@ pub fn blend_lighten_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
     }
 }
 
-pub(crate) fn blend_lighten(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_lighten(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -566,7 +794,7 @@ pub fn blend_color_dodge_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
     }
 }
 
-pub(crate) fn blend_color_dodge(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_color_dodge(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -591,7 +819,7 @@ pub fn blend_color_burn_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
         div255(sa * (da - ((da - d) * sa / s)) + s * (255 - da) + d * (255 - sa))
     }
 }
-pub(crate) fn blend_color_burn(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_color_burn(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -614,7 +842,7 @@ pub fn blend_hard_light_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
     }
 }
 
-pub(crate) fn blend_hard_light(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_hard_light(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -660,7 +888,7 @@ pub fn blend_soft_light_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
             / 65025
     }
 }
-pub(crate) fn blend_soft_light(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_soft_light(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -684,7 +912,7 @@ pub fn blend_difference_op(s: u32, d: u32, sa: u32, da: u32) -> u32 {
         s + d - 2 * div255(dsa)
     }
 }
-pub(crate) fn blend_difference(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_difference(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -702,7 +930,7 @@ pub fn blend_exclusion_op(s: u32, d: u32, _sa: u32, _da: u32) -> u32 {
     div255(255 * (s + d) - 2 * s * d)
 }
 
-pub(crate) fn blend_exclusion(input1: &mut Surface, input2: &mut Surface, output: &mut Surface) {
+pub(crate) fn blend_exclusion(input1: &SurfaceView, input2: &SurfaceView, output: &mut Surface) {
     for y in 0..output.height {
         for x in 0..output.width {
             let [sr, sg, sb, sa] = init_load_pixel(input1, x, y, input1.channel);
@@ -716,7 +944,7 @@ pub(crate) fn blend_exclusion(input1: &mut Surface, input2: &mut Surface, output
     }
 }
 
-pub(crate) fn composite_over(in1: &mut Surface, in2: &mut Surface, out: &mut Surface) {
+pub(crate) fn composite_over(in1: &SurfaceView, in2: &SurfaceView, out: &mut Surface) {
     for y in 0..out.height {
         for x in 0..out.width {
             let [sr, sg, sb, sa] = init_load_pixel(in1, x, y, in1.channel);
@@ -734,7 +962,7 @@ pub(crate) fn composite_over(in1: &mut Surface, in2: &mut Surface, out: &mut Sur
     }
 }
 
-pub(crate) fn composite_in(in1: &mut Surface, in2: &mut Surface, out: &mut Surface) {
+pub(crate) fn composite_in(in1: &SurfaceView, in2: &SurfaceView, out: &mut Surface) {
     for y in 0..out.height {
         for x in 0..out.width {
             let [sr, sg, sb, sa] = init_load_pixel(in1, x, y, in1.channel);
@@ -751,7 +979,7 @@ pub(crate) fn composite_in(in1: &mut Surface, in2: &mut Surface, out: &mut Surfa
     }
 }
 
-pub(crate) fn composite_out(in1: &mut Surface, in2: &mut Surface, out: &mut Surface) {
+pub(crate) fn composite_out(in1: &SurfaceView, in2: &SurfaceView, out: &mut Surface) {
     for y in 0..out.height {
         for x in 0..out.width {
             let [sr, sg, sb, sa] = init_load_pixel(in1, x, y, in1.channel);
@@ -768,7 +996,7 @@ pub(crate) fn composite_out(in1: &mut Surface, in2: &mut Surface, out: &mut Surf
     }
 }
 
-pub(crate) fn composite_atop(in1: &mut Surface, in2: &mut Surface, out: &mut Surface) {
+pub(crate) fn composite_atop(in1: &SurfaceView, in2: &SurfaceView, out: &mut Surface) {
     for y in 0..out.height {
         for x in 0..out.width {
             let [sr, sg, sb, sa] = init_load_pixel(in1, x, y, in1.channel);
@@ -785,7 +1013,7 @@ pub(crate) fn composite_atop(in1: &mut Surface, in2: &mut Surface, out: &mut Sur
     }
 }
 
-pub(crate) fn composite_xor(in1: &mut Surface, in2: &mut Surface, out: &mut Surface) {
+pub(crate) fn composite_xor(in1: &SurfaceView, in2: &SurfaceView, out: &mut Surface) {
     for y in 0..out.height {
         for x in 0..out.width {
             let [sr, sg, sb, sa] = init_load_pixel(in1, x, y, in1.channel);
@@ -0,0 +1,222 @@
+//! WebAssembly bindings over the RGBA bytes of `ImageData`.
+//!
+//! Every function takes `width * height * 4` bytes in RGBA order and filters them in place. The
+//! bytes are read as they are, the same way `Surface::try_from_image` reads an image, so a
+//! preview in the browser matches the output of the same filters run natively.
+
+use crate::{
+    BlendMode, ColorChannel, CompositeOperator, CssFilter, Surface, SurfaceError, SurfaceView,
+};
+use wasm_bindgen::prelude::*;
+
+/// Runs `filter` on the bytes, copying them into aligned pixels when wasm-bindgen passed an
+/// unaligned buffer.
+fn with_surface(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    filter: impl FnOnce(&mut Surface),
+) -> Result<(), SurfaceError> {
+    let stride = width as usize * 4;
+    if data.as_ptr().align_offset(align_of::<u32>()) == 0 {
+        let mut surface = Surface::from_bytes(data, width, height, stride, ColorChannel::RGBA32)?;
+        filter(&mut surface);
+        return Ok(());
+    }
+    let mut pixels = pixels(data);
+    let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut pixels);
+    let mut surface = Surface::from_bytes(bytes, width, height, stride, ColorChannel::RGBA32)?;
+    filter(&mut surface);
+    let required = stride * height as usize;
+    data[..required].copy_from_slice(&bytes[..required]);
+    Ok(())
+}
+
+/// Reads RGBA bytes into `u32` pixels.
+fn pixels(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// Applies a CSS `filter` property value, such as `"blur(2px) grayscale(50%)"`.
+#[wasm_bindgen(js_name = cssFilter)]
+pub fn css_filter(data: &mut [u8], width: u32, height: u32, filter: &str) -> Result<(), JsError> {
+    let filter = CssFilter::parse(filter)?;
+    with_surface(data, width, height, |surface| filter.apply(surface))?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_grayscale].
+#[wasm_bindgen]
+pub fn grayscale(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_grayscale(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_sepia].
+#[wasm_bindgen]
+pub fn sepia(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_sepia(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_saturate].
+#[wasm_bindgen]
+pub fn saturate(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_saturate(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_brightness].
+#[wasm_bindgen]
+pub fn brightness(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_brightness(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_contrast].
+#[wasm_bindgen]
+pub fn contrast(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_contrast(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_opacity].
+#[wasm_bindgen]
+pub fn opacity(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_opacity(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_invert].
+#[wasm_bindgen]
+pub fn invert(data: &mut [u8], width: u32, height: u32, amount: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_invert(input, output, amount)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::color_transform_hue_rotate].
+#[wasm_bindgen(js_name = hueRotate)]
+pub fn hue_rotate(data: &mut [u8], width: u32, height: u32, angle: f32) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::color_transform_hue_rotate(input, output, angle)
+        })
+    })?;
+    Ok(())
+}
+
+/// See [Surface::gaussian_blur].
+#[wasm_bindgen(js_name = gaussianBlur)]
+pub fn gaussian_blur(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    std_deviation_x: f32,
+    std_deviation_y: f32,
+) -> Result<(), JsError> {
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |input, output| {
+            Surface::gaussian_blur(input, output, std_deviation_x, std_deviation_y)
+        })
+    })?;
+    Ok(())
+}
+
+/// Blends `source` over the backdrop in `data`, see [Surface::blend].
+///
+///`mode` is a CSS `mix-blend-mode` keyword such as `"multiply"` or `"color-dodge"`.
+#[wasm_bindgen]
+pub fn blend(
+    data: &mut [u8],
+    source: &[u8],
+    width: u32,
+    height: u32,
+    mode: &str,
+) -> Result<(), JsError> {
+    let mode = match mode {
+        "normal" => BlendMode::Normal,
+        "multiply" => BlendMode::Multiply,
+        "screen" => BlendMode::Screen,
+        "overlay" => BlendMode::Overlay,
+        "darken" => BlendMode::Darken,
+        "lighten" => BlendMode::Lighten,
+        "color-dodge" => BlendMode::ColorDodge,
+        "color-burn" => BlendMode::ColorBurn,
+        "hard-light" => BlendMode::HardLight,
+        "soft-light" => BlendMode::SoftLight,
+        "difference" => BlendMode::Difference,
+        "exclusion" => BlendMode::Exclusion,
+        _ => return Err(JsError::new(&format!("Unknown blend mode {mode}"))),
+    };
+    let source = pixels(source);
+    let source = SurfaceView::make(&source, width, height, width, Some(ColorChannel::RGBA32))?;
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |backdrop, output| {
+            Surface::blend(source, backdrop, output, mode)
+        })
+    })?;
+    Ok(())
+}
+
+/// Composites `source` with the backdrop in `data`, see [Surface::composite].
+///
+///`operator` is a canvas `globalCompositeOperation` such as `"source-over"` or `"xor"`.
+#[wasm_bindgen]
+pub fn composite(
+    data: &mut [u8],
+    source: &[u8],
+    width: u32,
+    height: u32,
+    operator: &str,
+) -> Result<(), JsError> {
+    let operator = match operator {
+        "source-over" => CompositeOperator::Over,
+        "source-in" => CompositeOperator::In,
+        "source-out" => CompositeOperator::Out,
+        "source-atop" => CompositeOperator::Atop,
+        "xor" => CompositeOperator::Xor,
+        _ => {
+            return Err(JsError::new(&format!(
+                "Unknown composite operator {operator}"
+            )));
+        }
+    };
+    let source = pixels(source);
+    let source = SurfaceView::make(&source, width, height, width, Some(ColorChannel::RGBA32))?;
+    with_surface(data, width, height, |surface| {
+        Surface::in_place(surface, |backdrop, output| {
+            Surface::composite(source, backdrop, output, operator)
+        })
+    })?;
+    Ok(())
+}
@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod adjustments_test {
+    use plutofilter_rs::{ColorChannel, HslAdjustment, HueRange, Surface};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn opaque([r, g, b]: [u8; 3]) -> u32 {
+        u32::from_le_bytes([r, g, b, 255])
+    }
+
+    fn rgb(pixel: u32) -> [u8; 3] {
+        let [r, g, b, _] = pixel.to_le_bytes();
+        [r, g, b]
+    }
+
+    /// Runs `filter` over the given opaque colors and returns the resulting colors
+    fn apply(
+        colors: &[[u8; 3]],
+        filter: impl for<'a> Fn(&mut Surface<'a>, &mut Surface<'a>),
+    ) -> Result<Vec<[u8; 3]>> {
+        let width = colors.len() as u32;
+        let mut input_pixels: Vec<u32> = colors.iter().copied().map(opaque).collect();
+        let mut output_pixels = vec![0u32; colors.len()];
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, width, 1, width, channel)?;
+        let mut output = Surface::make(&mut output_pixels, width, 1, width, channel)?;
+        filter(&mut input, &mut output);
+        Ok(output_pixels.into_iter().map(rgb).collect())
+    }
+
+    fn gray_ramp() -> Vec<[u8; 3]> {
+        (0..=255u8).map(|v| [v, v, v]).collect()
+    }
+
+    /// Levels clip below the black point and above the white point and stretch the values between
+    #[test]
+    fn levels() -> Result<()> {
+        let output = apply(&[[51, 128, 204], [0, 30, 255]], |input, output| {
+            Surface::levels(input, output, 0.2, 0.8, 1.0)
+        })?;
+        assert_eq!(output, [[0, 128, 255], [0, 0, 255]]);
+
+        let output = apply(&[[128, 128, 128]], |input, output| {
+            Surface::levels(input, output, 0.0, 1.0, 2.0)
+        })?;
+        assert_eq!(output, [[181, 181, 181]]);
+        Ok(())
+    }
+
+    /// A straight curve keeps every value, an S curve is monotonic and passes through its points
+    #[test]
+    fn curves() -> Result<()> {
+        let ramp = gray_ramp();
+        let output = apply(&ramp, |input, output| {
+            Surface::curves(input, output, &[[0.0, 0.0], [1.0, 1.0]])
+        })?;
+        assert_eq!(output, ramp);
+
+        let points = [[0.0, 0.0], [0.25, 0.15], [0.75, 0.85], [1.0, 1.0]];
+        let output = apply(&ramp, |input, output| {
+            Surface::curves(input, output, &points)
+        })?;
+        assert!(output.windows(2).all(|pair| pair[0][0] <= pair[1][0]));
+        assert_eq!(output[0][0], 0);
+        assert_eq!(output[255][0], 255);
+        assert!(output[64][0].abs_diff(38) <= 1 && output[191][0].abs_diff(217) <= 1);
+        Ok(())
+    }
+
+    /// Per-channel curves only change their own channel
+    #[test]
+    fn curves_rgb() -> Result<()> {
+        let identity = [[0.0, 0.0], [1.0, 1.0]];
+        let output = apply(&[[10, 100, 200]], |input, output| {
+            Surface::curves_rgb(
+                input,
+                output,
+                &[[0.0, 1.0], [1.0, 0.0]],
+                &identity,
+                &identity,
+            )
+        })?;
+        assert_eq!(output, [[245, 100, 200]]);
+        Ok(())
+    }
+
+    /// Vibrance leaves grays alone and boosts muted colors more than vivid ones
+    #[test]
+    fn vibrance() -> Result<()> {
+        let colors = [[128, 128, 128], [140, 120, 120], [255, 0, 0]];
+        let output = apply(&colors, |input, output| {
+            Surface::vibrance(input, output, 0.5)
+        })?;
+        assert_eq!(output[0], [128, 128, 128]);
+
+        let spread = |[r, g, _]: [u8; 3]| r as f32 - g as f32;
+        assert!(spread(output[1]) / spread(colors[1]) > 1.3);
+        assert!(output[2][0] >= 250 && output[2][1] <= 5);
+        Ok(())
+    }
+
+    /// A warm temperature turns white yellow, a magenta tint lowers green, and black stays neutral
+    #[test]
+    fn temperature_tint() -> Result<()> {
+        let colors = [[255, 255, 255], [0, 0, 0]];
+        let warm = apply(&colors, |input, output| {
+            Surface::temperature_tint(input, output, 0.5, 0.0)
+        })?;
+        assert!(warm[0][0] as u32 > warm[0][2] as u32 + 30);
+        assert_eq!(warm[1], [0, 0, 0]);
+
+        let magenta = apply(&colors, |input, output| {
+            Surface::temperature_tint(input, output, 0.0, 0.5)
+        })?;
+        assert!(magenta[0][1] < magenta[0][0] - 10 && magenta[0][1] < magenta[0][2] - 10);
+        Ok(())
+    }
+
+    /// Lifting shadows brightens dark tones more than light ones and keeps the tonal order
+    #[test]
+    fn shadows_highlights() -> Result<()> {
+        let ramp = gray_ramp();
+        let output = apply(&ramp, |input, output| {
+            Surface::shadows_highlights(input, output, 1.0, -1.0)
+        })?;
+        assert_eq!(output[0], [0, 0, 0]);
+        assert_eq!(output[255], [255, 255, 255]);
+        assert!(output.windows(2).all(|pair| pair[0][0] <= pair[1][0]));
+        assert!(output[50][0] > 60);
+        assert!(output[210][0] < 200);
+        Ok(())
+    }
+
+    /// Adjusting the red range rotates reds, leaves blues alone and never tints grays
+    #[test]
+    fn hsl_adjust() -> Result<()> {
+        let adjustments = [(
+            HueRange::Red,
+            HslAdjustment {
+                hue: 120.0,
+                saturation: 0.0,
+                lightness: 0.0,
+            },
+        )];
+        let colors = [[255, 0, 0], [0, 0, 255], [90, 90, 90]];
+        let output = apply(&colors, |input, output| {
+            Surface::hsl_adjust(input, output, &adjustments)
+        })?;
+        assert_eq!(output, [[0, 255, 0], [0, 0, 255], [90, 90, 90]]);
+
+        let adjustments = [(
+            HueRange::Blue,
+            HslAdjustment {
+                hue: 0.0,
+                saturation: -1.0,
+                lightness: 0.0,
+            },
+        )];
+        let output = apply(&[[0, 0, 255]], |input, output| {
+            Surface::hsl_adjust(input, output, &adjustments)
+        })?;
+        assert_eq!(output, [[128, 128, 128]]);
+        Ok(())
+    }
+
+    /// Chain photo adjustments through the editor pipeline
+    #[test]
+    #[cfg(feature = "image")]
+    fn editor_adjustments() -> Result<()> {
+        use plutofilter_rs::{ImageEditor, get_resource_path};
+
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let output_path = get_resource_path(
+            &["test_output_images", "adjustments"],
+            "photo-adjustments.png",
+        );
+        ImageEditor::open(base_file)
+            .levels(0.05, 0.95, 1.1)
+            .shadows_highlights(0.4, -0.3)
+            .temperature_tint(0.15, 0.0)
+            .curves(&[[0.0, 0.0], [0.25, 0.22], [0.75, 0.8], [1.0, 1.0]])
+            .vibrance(0.4)
+            .save_to(output_path)?;
+        Ok(())
+    }
+}
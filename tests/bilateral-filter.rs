@@ -0,0 +1,103 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod bilateral_filter_test {
+    use plutofilter_rs::{ImageEditor, get_resource_path};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Test Bilateral Filter with zero sigma, which leaves the image unchanged
+    #[test]
+    fn test_bilateral_filter_0x0() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.bilateral_filter(0.0, 0.0);
+
+        let output_path = get_resource_path(
+            &["test_output_images", "bilateral_filter"],
+            "bilateral_filter-0x0.png",
+        );
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test Bilateral Filter with 8px spatial sigma and 0.1 range sigma
+    #[test]
+    fn test_bilateral_filter_8x10() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.bilateral_filter(8.0, 0.1);
+
+        let output_path = get_resource_path(
+            &["test_output_images", "bilateral_filter"],
+            "bilateral_filter-8x10.png",
+        );
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test Bilateral Filter with 16px spatial sigma and 0.25 range sigma
+    #[test]
+    fn test_bilateral_filter_16x25() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.bilateral_filter(16.0, 0.25);
+
+        let output_path = get_resource_path(
+            &["test_output_images", "bilateral_filter"],
+            "bilateral_filter-16x25.png",
+        );
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// A strong edge survives while the noise on either side of it is smoothed
+    #[test]
+    fn test_bilateral_filter_keeps_edges() {
+        use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf};
+
+        let (width, height) = (64, 48);
+        let level = |x: u32, y: u32| {
+            let noise = ((x * 7 + y * 13) % 5) as u8 * 6;
+            if x < width / 2 {
+                40 + noise
+            } else {
+                200 + noise
+            }
+        };
+        let pixels = (0..width * height)
+            .map(|i| {
+                let v = level(i % width, i / width);
+                u32::from_le_bytes([v, v, v, 255])
+            })
+            .collect();
+        let image = SurfaceBuf::from_pixels(pixels, width, height, ColorChannel::RGBA32).unwrap();
+        let mut output = SurfaceBuf::new(width, height, ColorChannel::RGBA32);
+        Surface::bilateral_filter(&image, &mut output.as_surface(), 4.0, 0.1);
+
+        let red = |image: &SurfaceBuf, x: u32, y: u32| {
+            image.pixels()[(y * width + x) as usize].to_le_bytes()[0] as i32
+        };
+        let spread = |image: &SurfaceBuf, xs: std::ops::Range<u32>| {
+            let values: Vec<i32> = (8..40)
+                .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                .map(|(x, y)| red(image, x, y))
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        for xs in [4..24, 40..60] {
+            assert!(
+                spread(&output, xs.clone()) < spread(&image, xs.clone()) / 2,
+                "noise in {xs:?} is not smoothed"
+            );
+        }
+        for y in 8..40 {
+            let (dark, light) = (red(&output, width / 2 - 1, y), red(&output, width / 2, y));
+            assert!(
+                dark < 70 && light > 190,
+                "edge blurred to {dark} and {light}"
+            );
+        }
+    }
+}
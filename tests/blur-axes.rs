@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod blur_axes_test {
+    use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf};
+
+    fn stripes(vertical: bool) -> SurfaceBuf {
+        let pixels = (0..32 * 24)
+            .map(|i| {
+                let (x, y) = (i % 32, i / 32);
+                let on = if vertical { x % 6 < 3 } else { y % 6 < 3 };
+                let value = if on { 255 } else { 0 };
+                u32::from_le_bytes([value, value / 2, 64, 255])
+            })
+            .collect();
+        SurfaceBuf::from_pixels(pixels, 32, 24, ColorChannel::RGBA32).unwrap()
+    }
+
+    fn blur(input: &SurfaceBuf, filter: impl FnOnce(&SurfaceBuf, &mut Surface)) -> SurfaceBuf {
+        let mut output = SurfaceBuf::new(input.width(), input.height(), ColorChannel::RGBA32);
+        filter(input, &mut output.as_surface());
+        output
+    }
+
+    /// A horizontal pass followed by a vertical pass matches blurring both axes at once
+    #[test]
+    fn separate_passes_match_gaussian_blur() {
+        let image = stripes(true);
+        let horizontal = blur(&image, |i, o| Surface::blur_horizontal(i, o, 2.5));
+        let both = blur(&horizontal, |i, o| Surface::blur_vertical(i, o, 1.5));
+        let expected = blur(&image, |i, o| Surface::gaussian_blur(i, o, 2.5, 1.5));
+        assert_eq!(both, expected);
+    }
+
+    /// A one-axis blur leaves stripes running along that axis untouched away from the edges
+    #[test]
+    fn single_axis() {
+        let center = |image: &SurfaceBuf| -> Vec<u32> {
+            let (width, height) = (image.width() as usize, image.height() as usize);
+            let row = &image.pixels()[height / 2 * width..][..width];
+            let column = (0..height).map(|y| image.pixels()[y * width + width / 2]);
+            row.iter().copied().chain(column).collect()
+        };
+        let rows = stripes(false);
+        let blurred = blur(&rows, |i, o| Surface::blur_horizontal(i, o, 2.0));
+        assert_eq!(center(&blurred)[32..], center(&rows)[32..]);
+        let blurred = blur(&rows, |i, o| Surface::blur_vertical(i, o, 2.0));
+        assert_ne!(center(&blurred)[32..], center(&rows)[32..]);
+
+        let columns = stripes(true);
+        let blurred = blur(&columns, |i, o| Surface::blur_vertical(i, o, 2.0));
+        assert_eq!(center(&blurred)[..32], center(&columns)[..32]);
+        let blurred = blur(&columns, |i, o| Surface::blur_horizontal(i, o, 2.0));
+        assert_ne!(center(&blurred)[..32], center(&columns)[..32]);
+    }
+
+    /// Blurring in place matches blurring into a separate surface
+    #[test]
+    fn in_place() {
+        let image = stripes(true);
+        let expected = blur(&image, |i, o| Surface::gaussian_blur(i, o, 4.0, 2.0));
+        let mut actual = image.clone();
+        Surface::in_place(&mut actual.as_surface(), |i, o| {
+            Surface::gaussian_blur(i, o, 4.0, 2.0)
+        });
+        assert_eq!(actual, expected);
+    }
+
+    /// Surfaces no longer share state through reference counting
+    #[test]
+    fn surface_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Surface<'static>>();
+        assert_send_sync::<SurfaceBuf>();
+    }
+}
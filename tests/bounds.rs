@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod bounds_test {
+    use plutofilter_rs::{
+        Bounds, ColorChannel, ColorMatrix, EdgeMode, FilterPrimitive, Rect, ResampleFilter,
+        Surface, SurfaceBuf, SurfaceView, TiledPipeline,
+    };
+
+    fn noise(width: u32, height: u32) -> SurfaceBuf {
+        let pixels = (0..width * height)
+            .map(|i| {
+                let v = i.wrapping_mul(2654435761) >> 8;
+                let a = 128 + (v >> 24) / 2;
+                u32::from_le_bytes([v as u8 % a as u8, (v >> 8) as u8 % a as u8, 30, a as u8])
+            })
+            .collect();
+        SurfaceBuf::from_pixels(pixels, width, height, ColorChannel::RGBA32).unwrap()
+    }
+
+    /// Returns a copy of `image` with the pixels for which `inside` returns true replaced
+    fn altered(image: &SurfaceBuf, inside: impl Fn(u32, u32) -> bool) -> SurfaceBuf {
+        let width = image.width();
+        let pixels = image
+            .pixels()
+            .iter()
+            .enumerate()
+            .map(|(i, &pixel)| {
+                let (x, y) = (i as u32 % width, i as u32 / width);
+                match inside(x, y) {
+                    true => !pixel | 0xff000000,
+                    false => pixel,
+                }
+            })
+            .collect();
+        SurfaceBuf::from_pixels(pixels, width, image.height(), ColorChannel::RGBA32).unwrap()
+    }
+
+    fn contains(rect: Rect, x: u32, y: u32) -> bool {
+        rect.intersect(Rect::new(x, y, 1, 1)) == Rect::new(x, y, 1, 1)
+    }
+
+    /// Checks that changing the pixels of `rect` only changes the output inside its bounds, and
+    /// that changing every pixel outside the required input of `rect` leaves it unchanged
+    fn check(
+        primitive: FilterPrimitive,
+        output_size: (u32, u32),
+        rect: Rect,
+        filter: impl Fn(SurfaceView<'_>, &mut Surface<'_>),
+    ) {
+        let image = noise(48, 40);
+        let run = |input: &SurfaceBuf| {
+            let mut output = SurfaceBuf::new(output_size.0, output_size.1, ColorChannel::RGBA32);
+            filter(input.as_view(), &mut output.as_surface());
+            output
+        };
+        let expected = run(&image);
+
+        let bounds = primitive.bounds(rect);
+        let changed = run(&altered(&image, |x, y| contains(rect, x, y)));
+        let required = primitive.required_input_rect(rect);
+        let unchanged = run(&altered(&image, |x, y| !contains(required, x, y)));
+        let mut reached = false;
+        for y in 0..output_size.1 {
+            for x in 0..output_size.0 {
+                let i = (y * output_size.0 + x) as usize;
+                if changed.pixels()[i] != expected.pixels()[i] {
+                    reached = true;
+                    assert!(contains(bounds, x, y), "{primitive:?} changed ({x}, {y})");
+                }
+                if contains(rect, x, y) {
+                    assert_eq!(
+                        unchanged.pixels()[i],
+                        expected.pixels()[i],
+                        "{primitive:?} read outside {required:?} for ({x}, {y})"
+                    );
+                }
+            }
+        }
+        assert!(reached, "{primitive:?} did not change");
+    }
+
+    /// Neighborhood filters grow rectangles by what they read around each pixel
+    #[test]
+    fn neighborhood() {
+        for rect in [Rect::new(20, 15, 6, 5), Rect::new(0, 0, 3, 3)] {
+            check(FilterPrimitive::Pointwise, (48, 40), rect, |i, o| {
+                Surface::color_transform(i, o, ColorMatrix::sepia(1.0))
+            });
+            check(
+                FilterPrimitive::GaussianBlur(2.0, 3.0),
+                (48, 40),
+                rect,
+                |i, o| Surface::gaussian_blur(i, o, 2.0, 3.0),
+            );
+            check(
+                FilterPrimitive::BilateralFilter(3.0),
+                (48, 40),
+                rect,
+                |i, o| Surface::bilateral_filter(i, o, 3.0, 0.5),
+            );
+            check(FilterPrimitive::RankFilter(2, 1), (48, 40), rect, |i, o| {
+                Surface::median_filter(i, o, 2, 1)
+            });
+        }
+        assert_eq!(
+            FilterPrimitive::RankFilter(3, 1).bounds(Rect::new(10, 10, 4, 4)),
+            Rect::new(7, 9, 10, 6)
+        );
+        assert!(
+            FilterPrimitive::GaussianBlur(4.0, 4.0)
+                .bounds(Rect::default())
+                .is_empty()
+        );
+    }
+
+    /// Transforms map rectangles through their matrix and resampling kernel
+    #[test]
+    fn transform() {
+        let cases = [
+            (
+                [1.0, 0.0, 0.0, 1.0, 7.0, -3.0],
+                ResampleFilter::Nearest,
+                EdgeMode::None,
+            ),
+            (
+                [0.8, 0.5, -0.5, 0.8, 12.0, 2.0],
+                ResampleFilter::Bilinear,
+                EdgeMode::None,
+            ),
+            (
+                [1.3, 0.0, 0.2, 1.1, -4.0, 1.0],
+                ResampleFilter::Bicubic,
+                EdgeMode::Duplicate,
+            ),
+            (
+                [0.4, 0.0, 0.0, 0.5, 3.0, 2.0],
+                ResampleFilter::Lanczos,
+                EdgeMode::None,
+            ),
+            (
+                [1.0, 0.0, 0.0, 1.0, 5.0, 5.0],
+                ResampleFilter::Bilinear,
+                EdgeMode::Wrap,
+            ),
+        ];
+        for (matrix, filter, edge_mode) in cases {
+            let primitive = FilterPrimitive::Transform {
+                matrix,
+                filter,
+                edge_mode,
+                input_width: 48,
+                input_height: 40,
+            };
+            for rect in [Rect::new(18, 14, 6, 5), Rect::new(0, 30, 8, 10)] {
+                check(primitive, (48, 40), rect, |i, o| {
+                    Surface::transform(i, o, matrix, filter, edge_mode)
+                });
+            }
+        }
+
+        let translate = |edge_mode| FilterPrimitive::Transform {
+            matrix: [1.0, 0.0, 0.0, 1.0, 7.0, -3.0],
+            filter: ResampleFilter::Nearest,
+            edge_mode,
+            input_width: 48,
+            input_height: 40,
+        };
+        let rect = Rect::new(10, 10, 4, 4);
+        assert_eq!(
+            translate(EdgeMode::None).bounds(rect),
+            Rect::new(17, 7, 4, 4)
+        );
+        assert_eq!(
+            translate(EdgeMode::None).required_input_rect(Rect::new(17, 7, 4, 4)),
+            rect
+        );
+        // Output pixels left of the input repeat its first column.
+        assert_eq!(
+            translate(EdgeMode::Duplicate).required_input_rect(Rect::new(0, 0, 4, 4)),
+            Rect::new(0, 3, 1, 4)
+        );
+        assert!(
+            translate(EdgeMode::None)
+                .required_input_rect(Rect::new(0, 0, 4, 4))
+                .is_empty()
+        );
+    }
+
+    /// Resizing maps rectangles between the input and output sizes
+    #[test]
+    fn resize() {
+        let primitive = FilterPrimitive::resize(48, 40, 30, 20, ResampleFilter::Bilinear);
+        for rect in [Rect::new(10, 8, 4, 3), Rect::new(26, 17, 4, 3)] {
+            check(primitive, (30, 20), rect, |i, o| {
+                Surface::transform(
+                    i,
+                    o,
+                    [30.0 / 48.0, 0.0, 0.0, 0.5, 0.0, 0.0],
+                    ResampleFilter::Bilinear,
+                    EdgeMode::Duplicate,
+                )
+            });
+        }
+    }
+
+    /// Chains apply their primitives in order, and backwards for the required input
+    #[test]
+    fn chain() {
+        let translate = FilterPrimitive::Transform {
+            matrix: [1.0, 0.0, 0.0, 1.0, 20.0, 0.0],
+            filter: ResampleFilter::Nearest,
+            edge_mode: EdgeMode::None,
+            input_width: 100,
+            input_height: 100,
+        };
+        let chain = [FilterPrimitive::RankFilter(2, 0), translate];
+        let rect = Rect::new(10, 10, 4, 4);
+        assert_eq!(chain.bounds(rect), Rect::new(28, 10, 8, 4));
+        assert!(chain.required_input_rect(rect).is_empty());
+        assert_eq!(
+            chain.required_input_rect(Rect::new(30, 10, 4, 4)),
+            Rect::new(8, 10, 8, 4)
+        );
+
+        let pipeline = TiledPipeline::new()
+            .gaussian_blur(2.0, 0.0)
+            .color_transform_sepia(1.0)
+            .median_filter(1, 2);
+        let (x, y) = pipeline.halo();
+        assert_eq!(pipeline.bounds(rect), rect.inflate(x, y));
+        assert_eq!(pipeline.required_input_rect(rect), rect.inflate(x, y));
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod byte_surface_test {
+    use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf, SurfaceError};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Filters write into padded rows without touching the padding
+    #[test]
+    fn padded_rows() -> Result<()> {
+        let (width, height, stride_bytes) = (5, 3, 28);
+        let mut frame = vec![0xAAu32; 7 * 3];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut frame);
+        let mut input = SurfaceBuf::new(width, height, ColorChannel::ARGB32);
+        input.fill([0, 0, 0, 255]);
+        {
+            let mut output =
+                Surface::from_bytes(bytes, width, height, stride_bytes, ColorChannel::ARGB32)?;
+            Surface::color_transform_invert(&input, &mut output, 1.0);
+        }
+        for (index, pixel) in frame.iter().enumerate() {
+            let expected = match index % 7 < 5 {
+                true => u32::from_le_bytes([255; 4]),
+                false => 0xAA,
+            };
+            assert_eq!(*pixel, expected, "pixel {index}");
+        }
+        Ok(())
+    }
+
+    /// The last row only needs the bytes of its pixels
+    #[test]
+    fn short_last_row() -> Result<()> {
+        let mut frame = vec![0u32; 8 + 6];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut frame);
+        let surface = Surface::from_bytes(bytes, 6, 2, 32, ColorChannel::RGBA32)?;
+        assert_eq!((surface.width(), surface.height()), (6, 2));
+        Ok(())
+    }
+
+    /// Layouts that can not be read as `u32` pixels are rejected
+    #[test]
+    fn invalid_layouts() {
+        let mut frame = vec![0u32; 64];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut frame);
+        assert!(matches!(
+            Surface::from_bytes(bytes, 4, 4, 12, ColorChannel::RGBA32),
+            Err(SurfaceError::StrideLessThanWidth)
+        ));
+        assert!(matches!(
+            Surface::from_bytes(bytes, 4, 4, 18, ColorChannel::RGBA32),
+            Err(SurfaceError::UnalignedStride)
+        ));
+        assert!(matches!(
+            Surface::from_bytes(bytes, 4, 5, 64, ColorChannel::RGBA32),
+            Err(SurfaceError::BufferTooShort {
+                required: 272,
+                found: 256
+            })
+        ));
+        assert!(matches!(
+            Surface::from_bytes(&mut bytes[1..], 4, 4, 16, ColorChannel::RGBA32),
+            Err(SurfaceError::MisalignedPixels)
+        ));
+    }
+}
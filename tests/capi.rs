@@ -0,0 +1,143 @@
+#[cfg(test)]
+#[cfg(feature = "capi")]
+mod capi_test {
+    use std::ffi::c_int;
+
+    use plutofilter_rs::capi::*;
+    use plutofilter_rs::{BlendMode, ColorChannel, Surface, SurfaceBuf};
+
+    fn gradient() -> Vec<u32> {
+        (0..16 * 16)
+            .map(|i| u32::from_be_bytes([255, i as u8, (i / 2) as u8, 64]))
+            .collect()
+    }
+
+    fn native(pixels: Vec<u32>) -> SurfaceBuf {
+        let channel = match cfg!(target_endian = "little") {
+            true => ColorChannel::BGRA32,
+            false => ColorChannel::ARGB32,
+        };
+        SurfaceBuf::from_pixels(pixels, 16, 16, channel).unwrap()
+    }
+
+    /// Filtering a surface into itself matches the Rust API with separate outputs
+    #[test]
+    fn aliased_surfaces() {
+        let mut pixels = gradient();
+        unsafe {
+            let surface = plutofilter_surface_make(pixels.as_mut_ptr(), 16, 16, 16);
+            plutofilter_gaussian_blur(surface, surface, 2.0, 2.0);
+            plutofilter_blend(
+                surface,
+                surface,
+                surface,
+                plutofilter_blend_mode_t::PLUTOFILTER_BLEND_MODE_MULTIPLY as c_int,
+            );
+        }
+
+        let image = native(gradient());
+        let mut blurred = SurfaceBuf::new(16, 16, image.channel());
+        Surface::gaussian_blur(&image, &mut blurred.as_surface(), 2.0, 2.0);
+        let mut expected = SurfaceBuf::new(16, 16, image.channel());
+        Surface::blend(
+            &blurred,
+            &blurred,
+            &mut expected.as_surface(),
+            BlendMode::Multiply,
+        );
+        assert_eq!(native(pixels), expected);
+    }
+
+    /// Subregions are clipped to the surface and only their pixels are written
+    #[test]
+    fn subregion() {
+        let mut pixels = gradient();
+        unsafe {
+            let surface = plutofilter_surface_make(pixels.as_mut_ptr(), 16, 16, 16);
+            let region = plutofilter_surface_make_sub(surface, 12, 12, 8, 8);
+            assert_eq!((region.width, region.height, region.stride), (4, 4, 16));
+            plutofilter_color_transform_invert(region, region, 1.0);
+        }
+        for (index, (before, after)) in gradient().iter().zip(&pixels).enumerate() {
+            let (x, y) = (index % 16, index / 16);
+            assert_eq!(x >= 12 && y >= 12, before != after, "pixel ({x}, {y})");
+        }
+    }
+
+    /// Blend modes and compositing operators outside the enums leave the output unchanged
+    #[test]
+    fn unknown_modes() {
+        let mut pixels = gradient();
+        unsafe {
+            let surface = plutofilter_surface_make(pixels.as_mut_ptr(), 16, 16, 16);
+            for value in [-1, 12, c_int::MAX] {
+                plutofilter_blend(surface, surface, surface, value);
+            }
+            for value in [-1, 5, c_int::MIN] {
+                plutofilter_composite(surface, surface, surface, value);
+            }
+        }
+        assert_eq!(pixels, gradient());
+
+        unsafe {
+            let surface = plutofilter_surface_make(pixels.as_mut_ptr(), 16, 16, 16);
+            let op = plutofilter_composite_operator_t::PLUTOFILTER_COMPOSITE_OPERATOR_XOR;
+            plutofilter_composite(surface, surface, surface, op as c_int);
+        }
+        assert!(pixels.iter().all(|&pixel| pixel == 0));
+    }
+
+    /// Out-of-range arguments never panic across the C boundary, and the ones the filters reject
+    /// leave the output unchanged
+    #[test]
+    fn out_of_range_arguments() {
+        let mut pixels = gradient();
+        unsafe {
+            let surface = plutofilter_surface_make(pixels.as_mut_ptr(), 16, 16, 16);
+            plutofilter_gaussian_blur(surface, surface, -1.0, 2.0);
+            plutofilter_gaussian_blur(surface, surface, 2.0, f32::NEG_INFINITY);
+            plutofilter_color_transform(surface, surface, std::ptr::null());
+        }
+        assert_eq!(pixels, gradient());
+
+        let amounts = [-1.0, 2.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY];
+        unsafe {
+            let surface = plutofilter_surface_make(pixels.as_mut_ptr(), 16, 16, 16);
+            let region = plutofilter_surface_make_sub(surface, u16::MAX, 20, u16::MAX, 8);
+            assert_eq!((region.width, region.height), (0, 0));
+            plutofilter_gaussian_blur(surface, region, 2.0, 2.0);
+            plutofilter_gaussian_blur(surface, surface, f32::NAN, f32::INFINITY);
+            plutofilter_color_transform(surface, surface, [f32::NAN; 20].as_ptr());
+            for amount in amounts {
+                plutofilter_color_transform_opacity(surface, surface, amount);
+                plutofilter_color_transform_brightness(surface, surface, amount);
+                plutofilter_color_transform_invert(surface, surface, amount);
+                plutofilter_color_transform_contrast(surface, surface, amount);
+                plutofilter_color_transform_saturate(surface, surface, amount);
+                plutofilter_color_transform_grayscale(surface, surface, amount);
+                plutofilter_color_transform_sepia(surface, surface, amount);
+                plutofilter_color_transform_hue_rotate(surface, surface, amount);
+                plutofilter_composite_arithmetic(
+                    surface, surface, surface, amount, amount, amount, amount,
+                );
+            }
+        }
+    }
+
+    /// Every exported function is declared in the generated header
+    #[test]
+    fn header_is_current() {
+        let source = include_str!("../src/capi.rs");
+        let header = include_str!("../include/plutofilter.h");
+        for name in source
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub unsafe extern \"C\" fn "))
+            .filter_map(|line| line.split('(').next())
+        {
+            assert!(
+                header.contains(&format!(" {name}(")),
+                "{name} is missing from include/plutofilter.h, regenerate it with cbindgen"
+            );
+        }
+    }
+}
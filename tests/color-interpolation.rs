@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod color_interpolation_test {
+    use plutofilter_rs::{ColorChannel, ColorInterpolation, Surface};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    const BLACK: u32 = 0xFF000000;
+    const WHITE: u32 = 0xFFFFFFFF;
+
+    fn red(pixel: u32) -> u8 {
+        pixel.to_le_bytes()[0]
+    }
+
+    /// Averaging black and white gives 50% linear light, which is 188 in sRGB
+    #[test]
+    fn arithmetic_average() -> Result<()> {
+        let (mut black, mut white) = (vec![BLACK], vec![WHITE]);
+        let (mut srgb_output, mut linear_output) = (vec![0u32], vec![0u32]);
+        let channel = Some(ColorChannel::RGBA32);
+        let mut in1 = Surface::make(&mut black, 1, 1, 1, channel)?;
+        let mut in2 = Surface::make(&mut white, 1, 1, 1, channel)?;
+
+        let mut out = Surface::make(&mut srgb_output, 1, 1, 1, channel)?;
+        Surface::composite_arithmetic(&mut in1, &mut in2, &mut out, 0.0, 0.5, 0.5, 0.0);
+
+        let mut out = Surface::make(&mut linear_output, 1, 1, 1, channel)?
+            .with_color_interpolation(ColorInterpolation::LinearRGB);
+        Surface::composite_arithmetic(&mut in1, &mut in2, &mut out, 0.0, 0.5, 0.5, 0.0);
+
+        assert!((127..=128).contains(&red(srgb_output[0])));
+        assert_eq!(red(linear_output[0]), 188);
+        Ok(())
+    }
+
+    /// Blurring across a black to white edge is brighter in linear light
+    #[test]
+    fn blur_edge() -> Result<()> {
+        let width = 32;
+        let mut input_pixels: Vec<u32> = (0..width)
+            .map(|x| if x < width / 2 { BLACK } else { WHITE })
+            .collect();
+        let mut srgb_pixels = vec![0u32; width as usize];
+        let mut linear_pixels = vec![0u32; width as usize];
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, width, 1, width, channel)?;
+
+        let mut output = Surface::make(&mut srgb_pixels, width, 1, width, channel)?;
+        Surface::gaussian_blur(&mut input, &mut output, 3.0, 0.0);
+        let mut output = Surface::make(&mut linear_pixels, width, 1, width, channel)?
+            .with_color_interpolation(ColorInterpolation::LinearRGB);
+        Surface::gaussian_blur(&mut input, &mut output, 3.0, 0.0);
+
+        let middle = (width / 2) as usize;
+        assert!(red(linear_pixels[middle]) > red(srgb_pixels[middle]) + 20);
+        Ok(())
+    }
+
+    /// An identity matrix in linear light returns every 8-bit value unchanged
+    #[test]
+    fn identity_round_trip() -> Result<()> {
+        let mut input_pixels: Vec<u32> = (0..=255u32)
+            .map(|v| u32::from_le_bytes([v as u8, v as u8, v as u8, 255]))
+            .collect();
+        let mut output_pixels = vec![0u32; 256];
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, 256, 1, 256, channel)?;
+        let mut output = Surface::make(&mut output_pixels, 256, 1, 256, channel)?
+            .with_color_interpolation(ColorInterpolation::LinearRGB);
+        Surface::color_transform_opacity(&mut input, &mut output, 1.0);
+
+        assert_eq!(input_pixels, output_pixels);
+        Ok(())
+    }
+
+    /// Blur the test image in linear light through the editor pipeline
+    #[test]
+    #[cfg(feature = "image")]
+    fn editor_linear_blur() -> Result<()> {
+        use plutofilter_rs::{ImageEditor, get_resource_path};
+
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor
+            .color_interpolation(ColorInterpolation::LinearRGB)
+            .gaussian_blur(10.0, 10.0);
+
+        let output_path = get_resource_path(
+            &["test_output_images", "color_interpolation"],
+            "gaussian_blur-linear-10x10.png",
+        );
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+}
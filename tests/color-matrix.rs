@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod color_matrix_test {
+    use plutofilter_rs::{ColorChannel, ColorMatrix, Surface};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn test_pixels() -> Vec<u32> {
+        (0..256u32)
+            .map(|i| u32::from_le_bytes([i as u8, (i * 7) as u8, (255 - i) as u8, 255]))
+            .collect()
+    }
+
+    fn assert_close(a: ColorMatrix, b: ColorMatrix) {
+        for (x, y) in a.to_array().iter().zip(b.to_array()) {
+            assert!((x - y).abs() < 1e-5, "{a:?} != {b:?}");
+        }
+    }
+
+    /// Preset matrices match the corresponding `color_transform_*` helpers exactly
+    #[test]
+    fn presets_match_helpers() -> Result<()> {
+        let mut input_pixels = test_pixels();
+        let (mut expected, mut actual) = (vec![0u32; 256], vec![0u32; 256]);
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, 16, 16, 16, channel)?;
+
+        let mut output = Surface::make(&mut expected, 16, 16, 16, channel)?;
+        Surface::color_transform_hue_rotate(&mut input, &mut output, 75.0);
+        let mut output = Surface::make(&mut actual, 16, 16, 16, channel)?;
+        Surface::color_transform(&mut input, &mut output, ColorMatrix::hue_rotate(75.0));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    /// Without clipping in between, a fused chain matches separate passes up to 8-bit rounding
+    #[test]
+    fn fused_chain() -> Result<()> {
+        let mut input_pixels: Vec<u32> = (0..256u32)
+            .map(|i| {
+                u32::from_le_bytes([
+                    (64 + i / 2) as u8,
+                    (64 + i * 7 % 128) as u8,
+                    (192 - i / 2) as u8,
+                    255,
+                ])
+            })
+            .collect();
+        let (mut first, mut second, mut separate) =
+            (vec![0u32; 256], vec![0u32; 256], vec![0u32; 256]);
+        let mut fused = vec![0u32; 256];
+        let channel = Some(ColorChannel::RGBA32);
+        {
+            let mut input = Surface::make(&mut input_pixels, 16, 16, 16, channel)?;
+            let mut output = Surface::make(&mut first, 16, 16, 16, channel)?;
+            Surface::color_transform_contrast(&mut input, &mut output, 0.97);
+        }
+        {
+            let mut input = Surface::make(&mut first, 16, 16, 16, channel)?;
+            let mut output = Surface::make(&mut second, 16, 16, 16, channel)?;
+            Surface::color_transform_sepia(&mut input, &mut output, 0.4);
+        }
+        {
+            let mut input = Surface::make(&mut second, 16, 16, 16, channel)?;
+            let mut output = Surface::make(&mut separate, 16, 16, 16, channel)?;
+            Surface::color_transform_brightness(&mut input, &mut output, 0.9);
+        }
+
+        let matrix = ColorMatrix::contrast(0.97)
+            .then(ColorMatrix::sepia(0.4))
+            .then(ColorMatrix::brightness(0.9));
+        let mut input = Surface::make(&mut input_pixels, 16, 16, 16, channel)?;
+        let mut output = Surface::make(&mut fused, 16, 16, 16, channel)?;
+        Surface::color_transform(&mut input, &mut output, matrix);
+
+        for (a, b) in separate.iter().zip(&fused) {
+            for (x, y) in a.to_le_bytes().iter().zip(b.to_le_bytes()) {
+                assert!(x.abs_diff(y) <= 2, "{a:08x} != {b:08x}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Multiplication order, inverse and identity follow the usual matrix algebra
+    #[test]
+    fn algebra() {
+        let a = ColorMatrix::saturate(1.4);
+        let b = ColorMatrix::contrast(1.2);
+        assert_close(a.then(b), b * a);
+        assert_close(a * ColorMatrix::identity(), a);
+
+        let inverse = (a * b).inverse().expect("invertible");
+        assert_close((a * b) * inverse, ColorMatrix::IDENTITY);
+        assert_close(inverse, b.inverse().unwrap() * a.inverse().unwrap());
+
+        assert!(ColorMatrix::grayscale(1.0).inverse().is_none());
+        assert!(ColorMatrix::opacity(0.0).inverse().is_none());
+    }
+
+    /// Presets that are linear in their amount interpolate to intermediate amounts
+    #[test]
+    fn lerp() {
+        let half = ColorMatrix::identity().lerp(ColorMatrix::sepia(1.0), 0.5);
+        assert_close(half, ColorMatrix::sepia(0.5));
+        let quarter = ColorMatrix::invert(0.0).lerp(ColorMatrix::invert(1.0), 0.25);
+        assert_close(quarter, ColorMatrix::invert(0.25));
+    }
+
+    /// Apply the README chain in a single pass through the editor pipeline
+    #[test]
+    #[cfg(feature = "image")]
+    fn editor_fused() -> Result<()> {
+        use plutofilter_rs::{ImageEditor, get_resource_path};
+
+        let base_file = get_resource_path(&["original_images"], "test-image.jpg");
+        let output_path = get_resource_path(&["test_output_images", "color_matrix"], "fused.jpg");
+        let matrix = ColorMatrix::contrast(0.97)
+            .then(ColorMatrix::hue_rotate(330.0))
+            .then(ColorMatrix::saturate(1.11));
+        ImageEditor::open(base_file)
+            .color_transform(matrix)
+            .save_to(output_path)?;
+        Ok(())
+    }
+}
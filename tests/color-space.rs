@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod color_space_test {
+    use plutofilter_rs::{ColorChannel, ColorSpace, Surface};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn rgba(pixel: u32) -> [u8; 4] {
+        pixel.to_le_bytes()
+    }
+
+    /// Luminance coefficients derived from the primaries match the published values
+    #[test]
+    fn luminance_coefficients() {
+        let close = |a: [f32; 3], b: [f32; 3]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-3);
+        assert!(close(
+            ColorSpace::Srgb.luminance(),
+            [0.2126, 0.7152, 0.0722]
+        ));
+        assert!(close(
+            ColorSpace::DisplayP3.luminance(),
+            [0.2290, 0.6917, 0.0793]
+        ));
+        assert!(close(
+            ColorSpace::AdobeRgb.luminance(),
+            [0.2974, 0.6273, 0.0753]
+        ));
+    }
+
+    /// sRGB red lies inside Display P3, where it is less saturated
+    #[test]
+    fn srgb_red_to_display_p3() -> Result<()> {
+        let (mut input_pixels, mut output_pixels) = (vec![0xFF0000FFu32], vec![0u32]);
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, 1, 1, 1, channel)?;
+        let mut output = Surface::make(&mut output_pixels, 1, 1, 1, channel)?
+            .with_color_space(ColorSpace::DisplayP3);
+        Surface::convert_color_space(&mut input, &mut output);
+
+        let [r, g, b, a] = rgba(output_pixels[0]);
+        assert!(r.abs_diff(234) <= 1 && g.abs_diff(51) <= 1 && b.abs_diff(35) <= 1);
+        assert_eq!(a, 255);
+        Ok(())
+    }
+
+    /// Converting to a wider gamut and back only loses 8-bit quantization precision
+    #[test]
+    fn round_trip() -> Result<()> {
+        let mut input_pixels: Vec<u32> = (0..64u32)
+            .map(|i| {
+                u32::from_le_bytes([
+                    (32 + i * 3) as u8,
+                    (224 - i * 3) as u8,
+                    (64 + i * 2) as u8,
+                    255,
+                ])
+            })
+            .collect();
+        let mut wide_pixels = vec![0u32; 64];
+        let mut output_pixels = vec![0u32; 64];
+        let channel = Some(ColorChannel::RGBA32);
+        for space in [
+            ColorSpace::DisplayP3,
+            ColorSpace::Rec2020,
+            ColorSpace::AdobeRgb,
+        ] {
+            let mut input = Surface::make(&mut input_pixels, 64, 1, 64, channel)?;
+            let mut wide =
+                Surface::make(&mut wide_pixels, 64, 1, 64, channel)?.with_color_space(space);
+            let mut output = Surface::make(&mut output_pixels, 64, 1, 64, channel)?;
+            Surface::convert_color_space(&mut input, &mut wide);
+            Surface::convert_color_space(&mut wide, &mut output);
+
+            for (before, after) in input_pixels.iter().zip(&output_pixels) {
+                for (b, a) in rgba(*before).iter().zip(rgba(*after)) {
+                    assert!(b.abs_diff(a) <= 4, "{space:?}: {before:08x} -> {after:08x}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Desaturating pure green uses the luminance of the surface color space
+    #[test]
+    fn saturate_uses_space_luminance() -> Result<()> {
+        let green = u32::from_le_bytes([0, 255, 0, 255]);
+        let (mut srgb_input, mut rec2020_input) = (vec![green], vec![green]);
+        let (mut srgb_output, mut rec2020_output) = (vec![0u32], vec![0u32]);
+        let channel = Some(ColorChannel::RGBA32);
+
+        let mut input = Surface::make(&mut srgb_input, 1, 1, 1, channel)?;
+        let mut output = Surface::make(&mut srgb_output, 1, 1, 1, channel)?;
+        Surface::color_transform_saturate(&mut input, &mut output, 0.0);
+
+        let mut input = Surface::make(&mut rec2020_input, 1, 1, 1, channel)?
+            .with_color_space(ColorSpace::Rec2020);
+        let mut output = Surface::make(&mut rec2020_output, 1, 1, 1, channel)?
+            .with_color_space(ColorSpace::Rec2020);
+        Surface::color_transform_saturate(&mut input, &mut output, 0.0);
+
+        assert_eq!(rgba(srgb_output[0])[1], 182);
+        assert_eq!(rgba(rec2020_output[0])[1], 172);
+        Ok(())
+    }
+
+    /// Converted images are saved with an ICC profile that identifies their color space on load
+    #[test]
+    #[cfg(feature = "image")]
+    fn editor_icc_round_trip() -> Result<()> {
+        use plutofilter_rs::{ImageEditor, get_resource_path};
+
+        for (space, name) in [
+            (ColorSpace::DisplayP3, "display-p3.png"),
+            (ColorSpace::Rec2020, "rec2020.png"),
+            (ColorSpace::AdobeRgb, "adobe-rgb.png"),
+        ] {
+            let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+            let output_path = get_resource_path(&["test_output_images", "color_space"], name);
+            ImageEditor::open(base_file)
+                .convert_color_space(space)
+                .color_transform_saturate(1.2)
+                .save_to(&output_path)?;
+
+            assert_eq!(ImageEditor::open(&output_path).color_space(), space);
+        }
+        Ok(())
+    }
+}
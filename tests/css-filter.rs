@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod css_filter_test {
+    use plutofilter_rs::{
+        ColorChannel, CssFilter, CssFilterError, CssFunction, Surface, SurfaceBuf,
+    };
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn gradient() -> SurfaceBuf {
+        let pixels = (0..16 * 16)
+            .map(|i| u32::from_le_bytes([i as u8, (i / 2) as u8, 64, 255]))
+            .collect();
+        SurfaceBuf::from_pixels(pixels, 16, 16, ColorChannel::RGBA32).unwrap()
+    }
+
+    /// Amounts, lengths and angles are normalized and defaults follow the CSS spec
+    #[test]
+    fn parse_functions() -> Result<()> {
+        let filter: CssFilter = "Blur(2px) brightness(150%) contrast() grayscale(2) \
+            hue-rotate(0.5turn) invert(50%) opacity(0.25) saturate(3) sepia( 100% ) hue-rotate(0)"
+            .parse()?;
+        assert_eq!(
+            filter.functions(),
+            [
+                CssFunction::Blur(2.0),
+                CssFunction::Brightness(1.5),
+                CssFunction::Contrast(1.0),
+                CssFunction::Grayscale(1.0),
+                CssFunction::HueRotate(180.0),
+                CssFunction::Invert(0.5),
+                CssFunction::Opacity(0.25),
+                CssFunction::Saturate(3.0),
+                CssFunction::Sepia(1.0),
+                CssFunction::HueRotate(0.0),
+            ]
+        );
+        assert!(CssFilter::parse(" none ")?.functions().is_empty());
+        assert!(CssFilter::parse("")?.functions().is_empty());
+        Ok(())
+    }
+
+    /// Invalid values, unknown functions and broken lists are rejected
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            CssFilter::parse("blur(2)"),
+            Err(CssFilterError::InvalidArgument(name)) if name == "blur"
+        ));
+        assert!(matches!(
+            CssFilter::parse("brightness(-1)"),
+            Err(CssFilterError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            CssFilter::parse("hue-rotate(90)"),
+            Err(CssFilterError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            CssFilter::parse("saturate(inf)"),
+            Err(CssFilterError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            CssFilter::parse("drop-shadow(1px 1px black)"),
+            Err(CssFilterError::Unsupported(_))
+        ));
+        assert!(matches!(
+            CssFilter::parse("sharpen(1)"),
+            Err(CssFilterError::UnknownFunction(_))
+        ));
+        assert!(matches!(
+            CssFilter::parse("blur(2px"),
+            Err(CssFilterError::Syntax)
+        ));
+    }
+
+    /// Applying a filter matches running the same surface operations one after another
+    #[test]
+    fn apply_matches_operations() -> Result<()> {
+        let mut filtered = gradient();
+        CssFilter::parse("sepia(60%) blur(1.5px) hue-rotate(90deg)")?
+            .apply(&mut filtered.as_surface());
+
+        let image = gradient();
+        let mut sepia = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::color_transform_sepia(&image, &mut sepia.as_surface(), 0.6);
+        let mut blurred = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::gaussian_blur(&sepia, &mut blurred.as_surface(), 1.5, 1.5);
+        let mut expected = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::color_transform_hue_rotate(&blurred, &mut expected.as_surface(), 90.0);
+        assert_eq!(filtered, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod filter_region_test {
+    use plutofilter_rs::{ColorChannel, ColorInterpolation, Rect, Surface, SurfaceBuf};
+
+    fn noise(width: u32, height: u32) -> SurfaceBuf {
+        let pixels = (0..width * height)
+            .map(|i| {
+                let v = i.wrapping_mul(2654435761) >> 8;
+                let a = 128 + (v >> 24) / 2;
+                u32::from_le_bytes([v as u8 % a as u8, (v >> 8) as u8 % a as u8, 0, a as u8])
+            })
+            .collect();
+        SurfaceBuf::from_pixels(pixels, width, height, ColorChannel::RGBA32).unwrap()
+    }
+
+    fn pixel(image: &SurfaceBuf, x: u32, y: u32) -> u32 {
+        image.pixels()[(y * image.width() + x) as usize]
+    }
+
+    /// Asserts that `actual` matches `expected` inside `region` and `original` outside it
+    fn assert_region(
+        actual: &SurfaceBuf,
+        expected: &SurfaceBuf,
+        original: &SurfaceBuf,
+        region: Rect,
+    ) {
+        for y in 0..actual.height() {
+            for x in 0..actual.width() {
+                let inside = region.intersect(Rect::new(x, y, 1, 1)) == Rect::new(x, y, 1, 1);
+                let want = if inside { expected } else { original };
+                assert_eq!(pixel(actual, x, y), pixel(want, x, y), "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    fn filter_both(
+        image: &SurfaceBuf,
+        region: Rect,
+        padding: (u32, u32),
+        filter: impl Fn(plutofilter_rs::SurfaceView<'_>, &mut Surface<'_>),
+    ) {
+        let mut expected = SurfaceBuf::new(image.width(), image.height(), ColorChannel::RGBA32);
+        filter(image.as_view(), &mut expected.as_surface());
+        let mut actual = image.clone();
+        Surface::filter_region(image, &mut actual.as_surface(), region, padding, &filter);
+        assert_region(&actual, &expected, image, region);
+    }
+
+    /// A blurred region matches the same pixels of a full blur, including next to the edges
+    #[test]
+    fn gaussian_blur_has_no_seams() {
+        let image = noise(48, 40);
+        for (sx, sy) in [(2.0, 2.0), (5.0, 1.0), (0.0, 3.0)] {
+            let padding = Surface::gaussian_blur_padding(sx, sy);
+            for region in [
+                Rect::new(12, 10, 16, 12),
+                Rect::new(0, 0, 9, 7),
+                Rect::new(30, 25, 100, 100),
+            ] {
+                filter_both(&image, region, padding, |input, output| {
+                    Surface::gaussian_blur(input, output, sx, sy)
+                });
+            }
+        }
+    }
+
+    /// Blurs in linear RGB read the same padding
+    #[test]
+    fn linear_rgb() {
+        let image = noise(32, 32).with_color_interpolation(ColorInterpolation::LinearRGB);
+        let padding = Surface::gaussian_blur_padding(2.0, 2.0);
+        let mut expected = image.clone();
+        Surface::in_place(&mut expected.as_surface(), |input, output| {
+            Surface::gaussian_blur(input, output, 2.0, 2.0)
+        });
+        let mut actual = image.clone();
+        let region = Rect::new(8, 4, 10, 20);
+        Surface::filter_region(
+            &image,
+            &mut actual.as_surface(),
+            region,
+            padding,
+            |input, output| Surface::gaussian_blur(input, output, 2.0, 2.0),
+        );
+        assert_region(&actual, &expected, &image, region);
+    }
+
+    /// Rank filters need their radius as padding
+    #[test]
+    fn rank_filter() {
+        let image = noise(40, 30);
+        filter_both(&image, Rect::new(5, 6, 20, 10), (3, 2), |input, output| {
+            Surface::median_filter(input, output, 3, 2)
+        });
+    }
+
+    /// Regions outside the surfaces leave the output untouched
+    #[test]
+    fn empty_region() {
+        let image = noise(16, 16);
+        let mut output = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        let before = output.clone();
+        Surface::filter_region(
+            &image,
+            &mut output.as_surface(),
+            Rect::new(16, 4, 8, 8),
+            (2, 2),
+            |_, _| panic!("the filter should not run"),
+        );
+        assert_eq!(output, before);
+    }
+
+    /// Rectangles clip and grow without overflowing
+    #[test]
+    fn rect() {
+        let bounds = Rect::from_size(32, 32);
+        assert_eq!(
+            Rect::new(20, 20, 20, 20).intersect(bounds),
+            Rect::new(20, 20, 12, 12)
+        );
+        assert!(Rect::new(40, 0, 4, 4).intersect(bounds).is_empty());
+        assert_eq!(Rect::new(2, 3, 4, 4).inflate(3, 3), Rect::new(0, 0, 9, 10));
+        assert_eq!(
+            Rect::new(u32::MAX - 1, 0, 1, 1).inflate(4, 0),
+            Rect::new(u32::MAX - 5, 0, 5, 1)
+        );
+    }
+
+    /// Subregions of a view read the same pixels as the view
+    #[test]
+    fn view_make_sub() {
+        let image = noise(16, 12);
+        let view = image.as_view();
+        let sub = view.make_sub(4, 5, 100, 3);
+        assert_eq!((sub.width(), sub.height()), (12, 3));
+        let expected: Vec<u32> = (5..8)
+            .flat_map(|y| (4..16).map(move |x| (x, y)))
+            .map(|(x, y)| pixel(&image, x, y))
+            .collect();
+        let mut copy = SurfaceBuf::new(12, 3, ColorChannel::RGBA32);
+        Surface::gaussian_blur(sub, &mut copy.as_surface(), 0.0, 0.0);
+        assert_eq!(copy.pixels(), expected);
+        let outside = view.make_sub(20, 20, 4, 4);
+        assert_eq!((outside.width(), outside.height()), (0, 0));
+    }
+}
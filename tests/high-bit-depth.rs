@@ -0,0 +1,122 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod high_bit_depth_test {
+    use image::{DynamicImage, ImageReader};
+    use plutofilter_rs::{BlendMode, Surface16, SurfaceF32, get_resource_path};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Darkening and brightening again keeps 16-bit precision instead of banding
+    #[test]
+    fn surface16_brightness_round_trip() -> Result<()> {
+        let width = 256;
+        let mut input_pixels: Vec<[u16; 4]> = (0..width)
+            .map(|x| {
+                let v = (x * 257) as u16;
+                [v, v, v, u16::MAX]
+            })
+            .collect();
+        let mut temp_pixels = vec![[0u16; 4]; width as usize];
+        let mut input = Surface16::make(&mut input_pixels, width, 1, width)?;
+        let mut temp = Surface16::make(&mut temp_pixels, width, 1, width)?;
+        Surface16::color_transform_brightness(&mut input, &mut temp, 0.1);
+        Surface16::color_transform_brightness(&mut temp, &mut input, 10.0);
+
+        for (x, pixel) in input_pixels.iter().enumerate() {
+            let expected = (x * 257) as i32;
+            assert!((pixel[0] as i32 - expected).abs() <= 8, "{x}: {pixel:?}");
+        }
+        Ok(())
+    }
+
+    /// Blurring a flat surface leaves it unchanged away from the edges
+    #[test]
+    fn surface_f32_blur_flat() -> Result<()> {
+        let (width, height) = (64, 64);
+        let mut input_pixels = vec![[0.25f32, 0.5, 0.75, 1.0]; (width * height) as usize];
+        let mut output_pixels = vec![[0.0f32; 4]; (width * height) as usize];
+        let mut input = SurfaceF32::make(&mut input_pixels, width, height, width)?;
+        let mut output = SurfaceF32::make(&mut output_pixels, width, height, width)?;
+        SurfaceF32::gaussian_blur(&mut input, &mut output, 2.0, 2.0);
+
+        let center = output_pixels[(32 * width + 32) as usize];
+        for (value, expected) in center.iter().zip([0.25, 0.5, 0.75, 1.0]) {
+            assert!((value - expected).abs() < 1e-4);
+        }
+        Ok(())
+    }
+
+    /// Multiply blend of two opaque colors multiplies their channels
+    #[test]
+    fn surface_f32_blend_multiply() -> Result<()> {
+        let mut source_pixels = vec![[0.5f32, 0.25, 1.0, 1.0]];
+        let mut backdrop_pixels = vec![[0.5f32, 1.0, 0.5, 1.0]];
+        let mut output_pixels = vec![[0.0f32; 4]];
+        let mut source = SurfaceF32::make(&mut source_pixels, 1, 1, 1)?;
+        let mut backdrop = SurfaceF32::make(&mut backdrop_pixels, 1, 1, 1)?;
+        let mut output = SurfaceF32::make(&mut output_pixels, 1, 1, 1)?;
+        SurfaceF32::blend(&mut source, &mut backdrop, &mut output, BlendMode::Multiply);
+
+        assert_eq!(output_pixels[0], [0.25, 0.25, 0.5, 1.0]);
+        Ok(())
+    }
+
+    /// Arithmetic composite keeps HDR values above 1.0 and clamps integer formats
+    #[test]
+    fn composite_arithmetic_hdr() -> Result<()> {
+        let mut source_pixels = vec![[2.0f32, 0.5, 0.0, 1.0]];
+        let mut backdrop_pixels = vec![[1.0f32, 0.5, 0.0, 1.0]];
+        let mut output_pixels = vec![[0.0f32; 4]];
+        let mut source = SurfaceF32::make(&mut source_pixels, 1, 1, 1)?;
+        let mut backdrop = SurfaceF32::make(&mut backdrop_pixels, 1, 1, 1)?;
+        let mut output = SurfaceF32::make(&mut output_pixels, 1, 1, 1)?;
+        SurfaceF32::composite_arithmetic(
+            &mut source,
+            &mut backdrop,
+            &mut output,
+            0.0,
+            1.0,
+            1.0,
+            0.0,
+        );
+        assert_eq!(output_pixels[0], [3.0, 1.0, 0.0, 1.0]);
+
+        let mut source_pixels = vec![[65535u16, 32768, 0, 65535]];
+        let mut backdrop_pixels = vec![[65535u16, 32768, 0, 65535]];
+        let mut output_pixels = vec![[0u16; 4]];
+        let mut source = Surface16::make(&mut source_pixels, 1, 1, 1)?;
+        let mut backdrop = Surface16::make(&mut backdrop_pixels, 1, 1, 1)?;
+        let mut output = Surface16::make(&mut output_pixels, 1, 1, 1)?;
+        Surface16::composite_arithmetic(
+            &mut source,
+            &mut backdrop,
+            &mut output,
+            0.0,
+            1.0,
+            1.0,
+            0.0,
+        );
+        assert_eq!(output_pixels[0], [65535, 65535, 0, 65535]);
+        Ok(())
+    }
+
+    /// Filter an image at 16 bits per channel and save it as a 16-bit PNG
+    #[test]
+    fn surface16_from_image() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut input_image = ImageReader::open(base_file)?.decode()?;
+        let (width, height) = (input_image.width(), input_image.height());
+        let mut output_image = DynamicImage::new_rgba16(width, height);
+        {
+            let mut input = Surface16::from_image(&mut input_image);
+            let mut output = Surface16::from_image(&mut output_image);
+            Surface16::color_transform_contrast(&mut input, &mut output, 1.5);
+            Surface16::color_transform_sepia(&mut output, &mut input, 0.5);
+            Surface16::gaussian_blur(&mut input, &mut output, 2.0, 2.0);
+        }
+
+        let output_path =
+            get_resource_path(&["test_output_images", "high_bit_depth"], "surface16.png");
+        output_image.save(output_path)?;
+        Ok(())
+    }
+}
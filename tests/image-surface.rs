@@ -0,0 +1,64 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod image_surface_test {
+    use image::{DynamicImage, Rgb, RgbImage};
+    use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Every image variant is converted to 8-bit RGBA before it is borrowed
+    #[test]
+    fn converts_variants() -> Result<()> {
+        let rgb = RgbImage::from_pixel(4, 3, Rgb([10, 20, 30]));
+        let images = [
+            DynamicImage::ImageRgb8(rgb.clone()),
+            DynamicImage::ImageRgba16(DynamicImage::ImageRgb8(rgb.clone()).to_rgba16()),
+            DynamicImage::ImageRgb32F(DynamicImage::ImageRgb8(rgb).to_rgb32f()),
+        ];
+        for mut image in images {
+            let surface = Surface::try_from_image(&mut image)?;
+            assert_eq!((surface.width(), surface.height()), (4, 3));
+            surface.finish();
+            assert_eq!(
+                image.as_rgba8().map(|image| image.get_pixel(3, 2).0),
+                Some([10, 20, 30, 255])
+            );
+        }
+        Ok(())
+    }
+
+    /// Filters write through the surface into the image
+    #[test]
+    fn writes_into_image() -> Result<()> {
+        let mut input = SurfaceBuf::new(4, 3, ColorChannel::RGBA32);
+        input.fill([255, 255, 255, 255]);
+        let mut image = DynamicImage::new_rgba8(4, 3);
+        {
+            let mut output = Surface::try_from_image(&mut image)?;
+            Surface::color_transform_invert(&input, &mut output, 1.0);
+        }
+        assert!(
+            image
+                .to_rgba8()
+                .pixels()
+                .all(|pixel| pixel.0 == [0, 0, 0, 255])
+        );
+        Ok(())
+    }
+
+    /// The deprecated constructor still borrows 8-bit RGBA images as a plain surface
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_from_image() {
+        let mut input = SurfaceBuf::new(4, 3, ColorChannel::RGBA32);
+        input.fill([255, 255, 255, 255]);
+        let mut image = DynamicImage::new_rgba8(4, 3);
+        let mut output: Surface = Surface::from_image(&mut image);
+        Surface::color_transform_invert(&input, &mut output, 1.0);
+        assert!(
+            image
+                .to_rgba8()
+                .pixels()
+                .all(|pixel| pixel.0 == [0, 0, 0, 255])
+        );
+    }
+}
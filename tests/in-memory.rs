@@ -0,0 +1,118 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod in_memory_test {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageFormat, RgbaImage};
+    use plutofilter_rs::{
+        BlendMode, CompositeOperator, FilterInput, ImageEditor, get_resource_path,
+    };
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn test_image_bytes() -> Result<Vec<u8>> {
+        Ok(std::fs::read(get_resource_path(
+            &["original_images"],
+            "test-image.jpg",
+        ))?)
+    }
+
+    /// Decoding bytes gives the same result as opening the file
+    #[test]
+    fn from_bytes_matches_open() -> Result<()> {
+        let from_file =
+            ImageEditor::open(get_resource_path(&["original_images"], "test-image.jpg"))
+                .color_transform_sepia(0.6)
+                .into_dynamic_image();
+        let from_bytes = ImageEditor::from_bytes(&test_image_bytes()?)?
+            .color_transform_sepia(0.6)
+            .into_dynamic_image();
+        assert_eq!(from_file, from_bytes);
+        assert!(ImageEditor::from_bytes(b"not an image").is_err());
+        Ok(())
+    }
+
+    /// Raw pixels round trip through the editor and must match the given size
+    #[test]
+    fn from_rgba8() -> Result<()> {
+        let pixels: Vec<u8> = (0..16 * 8 * 4).map(|i| i as u8).collect();
+        let image = ImageEditor::from_rgba8(16, 8, pixels.clone())?.into_dynamic_image();
+        assert_eq!(image.into_rgba8().into_raw(), pixels);
+        assert!(ImageEditor::from_rgba8(16, 9, pixels).is_err());
+        Ok(())
+    }
+
+    /// Encoded output decodes back to the same pixels for lossless formats
+    #[test]
+    fn encode_to() -> Result<()> {
+        let editor = || -> Result<ImageEditor> {
+            Ok(ImageEditor::from_bytes(&test_image_bytes()?)?.color_transform_invert(1.0))
+        };
+        let expected = editor()?.into_dynamic_image().into_rgba8();
+
+        let mut png = Vec::new();
+        editor()?.encode_to(&mut png, ImageFormat::Png, 100)?;
+        let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png)?;
+        assert_eq!(decoded.into_rgba8(), expected);
+
+        let mut low = Vec::new();
+        let mut high = Vec::new();
+        editor()?.encode_to(&mut low, ImageFormat::Jpeg, 20)?;
+        editor()?.encode_to(&mut high, ImageFormat::Jpeg, 95)?;
+        assert!(low.len() < high.len());
+
+        let mut bmp = Cursor::new(Vec::new());
+        editor()?.encode_to(&mut bmp, ImageFormat::Bmp, 100)?;
+        assert_eq!(image::guess_format(bmp.get_ref())?, ImageFormat::Bmp);
+        Ok(())
+    }
+
+    /// Blend with in-memory images instead of files
+    #[test]
+    fn blend_in_memory() -> Result<()> {
+        let blend_path = get_resource_path(&["original_images"], "royal-purple.png");
+        let blend_bytes = std::fs::read(&blend_path)?;
+        let blend_image = image::open(&blend_path)?;
+        let base = || -> Result<ImageEditor> { Ok(ImageEditor::from_bytes(&test_image_bytes()?)?) };
+
+        let from_path = base()?
+            .blend(&blend_path, BlendMode::Multiply)
+            .into_dynamic_image();
+        let from_bytes = base()?
+            .blend(FilterInput::try_from(blend_bytes)?, BlendMode::Multiply)
+            .into_dynamic_image();
+        let from_image = base()?
+            .blend(blend_image, BlendMode::Multiply)
+            .into_dynamic_image();
+        assert_eq!(from_path, from_bytes);
+        assert_eq!(from_path, from_image);
+
+        let output_path = get_resource_path(&["test_output_images", "in_memory"], "blend.png");
+        let solid = DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            512,
+            382,
+            image::Rgba([255, 128, 0, 255]),
+        ));
+        ImageEditor::from_dynamic_image(from_image)
+            .composite(FilterInput::Image(solid), CompositeOperator::Atop)
+            .save_to(output_path)?;
+        Ok(())
+    }
+
+    /// Bytes that are not an image fail to convert instead of panicking in the blend
+    #[test]
+    fn blend_corrupt_bytes() -> Result<()> {
+        assert!(FilterInput::try_from(&b"not an image"[..]).is_err());
+        let mut truncated = test_image_bytes()?;
+        truncated.truncate(64);
+        assert!(FilterInput::try_from(truncated).is_err());
+        Ok(())
+    }
+
+    /// Editors created from memory have no path to save to
+    #[test]
+    fn save_without_path() -> Result<()> {
+        let editor = ImageEditor::from_rgba8(1, 1, vec![0, 0, 0, 255])?;
+        assert!(editor.save().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod lazy_pipeline_test {
+    use image::RgbaImage;
+    use plutofilter_rs::{ColorInterpolation, ImageEditor, ResampleFilter, get_resource_path};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Runs the same chain eagerly and lazily and returns both saved outputs
+    fn render(
+        name: &str,
+        chain: impl Fn(ImageEditor) -> ImageEditor,
+    ) -> Result<(RgbaImage, RgbaImage)> {
+        let base_file = get_resource_path(&["original_images"], "test-image.jpg");
+        let eager_path = get_resource_path(
+            &["test_output_images", "lazy_pipeline"],
+            &format!("{name}-eager.png"),
+        );
+        let lazy_path = get_resource_path(
+            &["test_output_images", "lazy_pipeline"],
+            &format!("{name}-lazy.png"),
+        );
+        chain(ImageEditor::open(&base_file)).save_to(&eager_path)?;
+        chain(ImageEditor::open(&base_file).lazy()).save_to(&lazy_path)?;
+        Ok((
+            image::open(eager_path)?.into_rgba8(),
+            image::open(lazy_path)?.into_rgba8(),
+        ))
+    }
+
+    fn max_difference(a: &RgbaImage, b: &RgbaImage) -> u8 {
+        assert_eq!(a.dimensions(), b.dimensions());
+        a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| x.abs_diff(*y))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Merged color matrices only differ from separate passes by 8-bit rounding
+    #[test]
+    fn merged_color_matrices() -> Result<()> {
+        let (eager, lazy) = render("color-matrices", |editor| {
+            editor
+                .color_transform_contrast(0.97)
+                .color_transform_hue_rotate(330.0)
+                .color_transform_saturate(1.11)
+        })?;
+        assert!(max_difference(&eager, &lazy) <= 3);
+        Ok(())
+    }
+
+    /// Identity operations are skipped, so the image is not rounded through them
+    #[test]
+    fn skipped_identities() -> Result<()> {
+        let (eager, lazy) = render("identities", |editor| {
+            editor
+                .gaussian_blur(0.0, 0.0)
+                .color_transform_hue_rotate(360.0)
+                .median_filter(0, 0)
+                .levels(0.0, 1.0, 1.0)
+        })?;
+        let original =
+            image::open(get_resource_path(&["original_images"], "test-image.jpg"))?.into_rgba8();
+        assert_eq!(lazy, original);
+        assert!(max_difference(&eager, &original) <= 1);
+        Ok(())
+    }
+
+    /// Spatial filters, size changes and non-inplace steps run in the same order as eagerly
+    #[test]
+    fn mixed_chain() -> Result<()> {
+        let (eager, lazy) = render("mixed", |editor| {
+            editor
+                .color_transform_sepia(0.8)
+                .resize(256, 191, ResampleFilter::Bilinear)
+                .gaussian_blur(1.5, 1.5)
+                .color_interpolation(ColorInterpolation::LinearRGB)
+                .color_transform_brightness(1.2)
+                .color_interpolation(ColorInterpolation::SRGB)
+                .color_transform_brightness(0.9)
+                .max_filter(1, 1)
+        })?;
+        assert_eq!(eager.dimensions(), (256, 191));
+        assert_eq!(eager, lazy);
+        Ok(())
+    }
+
+    /// Matrices that push colors out of range are not merged, so their clamp is kept
+    #[test]
+    fn out_of_range_matrices() -> Result<()> {
+        let (eager, lazy) = render("out-of-range", |editor| {
+            editor
+                .color_transform_brightness(2.0)
+                .color_transform_brightness(0.5)
+                .color_transform_grayscale(0.5)
+                .color_transform_invert(1.0)
+        })?;
+        assert!(max_difference(&eager, &lazy) <= 2);
+        Ok(())
+    }
+
+    /// Pixels outside a smaller blend layer keep the result of the previous step
+    #[test]
+    fn smaller_blend_layer() -> Result<()> {
+        use image::{DynamicImage, Rgba};
+        use plutofilter_rs::BlendMode;
+
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([200, 0, 0, 255])));
+        let layer = DynamicImage::ImageRgba8(RgbaImage::new(2, 2));
+        let output = ImageEditor::from_dynamic_image(image)
+            .lazy()
+            .color_transform_invert(1.0)
+            .blend(layer, BlendMode::Normal)
+            .into_dynamic_image()
+            .into_rgba8();
+        assert!(
+            output
+                .pixels()
+                .all(|pixel| *pixel == Rgba([55, 255, 255, 255]))
+        );
+        Ok(())
+    }
+
+    /// Steps with negative sigmas are not skipped as identities, so lazy chains reject them like
+    /// eager ones
+    #[test]
+    fn negative_sigma() {
+        for lazy in [false, true] {
+            let result = std::panic::catch_unwind(|| {
+                let editor = ImageEditor::from_rgba8(2, 2, vec![255; 16]).unwrap();
+                let editor = if lazy { editor.lazy() } else { editor };
+                editor.bilateral_filter(-1.0, 0.0).into_dynamic_image()
+            });
+            assert!(result.is_err(), "lazy: {lazy}");
+        }
+    }
+}
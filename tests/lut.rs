@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod lut_test {
+    use plutofilter_rs::{ColorChannel, Lut3d, LutError, LutInterpolation, Surface};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    const SWAP_RED_BLUE: &str = "TITLE \"swap\"
+# red and blue swapped
+LUT_3D_SIZE 2
+0 0 0
+0 0 1
+0 1 0
+0 1 1
+1 0 0
+1 0 1
+1 1 0
+1 1 1
+";
+
+    fn test_pixels() -> Vec<u32> {
+        (0..256u32)
+            .map(|i| u32::from_le_bytes([i as u8, (i * 7) as u8, (255 - i) as u8, 255]))
+            .collect()
+    }
+
+    /// Identity tables of any size return every pixel unchanged with both interpolations
+    #[test]
+    fn identity() -> Result<()> {
+        for size in [2, 17, 33] {
+            for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+                let mut input_pixels = test_pixels();
+                let mut output_pixels = vec![0u32; 256];
+                let channel = Some(ColorChannel::RGBA32);
+                let mut input = Surface::make(&mut input_pixels, 16, 16, 16, channel)?;
+                let mut output = Surface::make(&mut output_pixels, 16, 16, 16, channel)?;
+                Surface::apply_lut(
+                    &mut input,
+                    &mut output,
+                    &Lut3d::identity(size),
+                    interpolation,
+                );
+                assert_eq!(input_pixels, output_pixels);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parsed entries are ordered with red changing fastest
+    #[test]
+    fn parse_cube() -> Result<()> {
+        let lut = Lut3d::parse_cube(SWAP_RED_BLUE)?;
+        assert_eq!(lut.size(), 2);
+
+        let mut input_pixels = vec![u32::from_le_bytes([200, 100, 50, 255])];
+        let mut output_pixels = vec![0u32];
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, 1, 1, 1, channel)?;
+        let mut output = Surface::make(&mut output_pixels, 1, 1, 1, channel)?;
+        Surface::apply_lut(&mut input, &mut output, &lut, LutInterpolation::Tetrahedral);
+        assert_eq!(output_pixels[0].to_le_bytes(), [50, 100, 200, 255]);
+        Ok(())
+    }
+
+    /// Input domains rescale colors before the lookup
+    #[test]
+    fn parse_domain() -> Result<()> {
+        let cube = SWAP_RED_BLUE.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MAX 2 2 2");
+        let lut = Lut3d::parse_cube(&cube)?;
+        let [r, g, b] = lut.lookup([1.0, 0.5, 0.0], LutInterpolation::Trilinear);
+        assert_eq!([r, g, b], [0.0, 0.25, 0.5]);
+        Ok(())
+    }
+
+    /// Malformed files are rejected with a descriptive error
+    #[test]
+    fn parse_errors() {
+        let missing_entry = SWAP_RED_BLUE.replace("1 1 1\n", "");
+        assert!(matches!(
+            Lut3d::parse_cube(&missing_entry),
+            Err(LutError::EntryCount {
+                expected: 8,
+                found: 7
+            })
+        ));
+        assert!(matches!(
+            Lut3d::parse_cube(&SWAP_RED_BLUE.replace("0 1 1", "0 x 1")),
+            Err(LutError::InvalidLine(7))
+        ));
+        assert!(matches!(
+            Lut3d::parse_cube(&SWAP_RED_BLUE.replace("LUT_3D_SIZE 2", "")),
+            Err(LutError::MissingSize)
+        ));
+        assert!(matches!(
+            Lut3d::parse_cube("LUT_1D_SIZE 4\n"),
+            Err(LutError::Unsupported1d)
+        ));
+    }
+
+    /// Both interpolations agree on the table entries and stay close between them
+    #[test]
+    #[cfg(feature = "std")]
+    fn interpolations_agree() -> Result<()> {
+        let lut = Lut3d::from_cube_file(plutofilter_rs::get_resource_path(
+            &["luts"],
+            "teal-orange.cube",
+        ))?;
+        for i in 0..=64 {
+            let color = [i as f32 / 64.0, (i * 3 % 65) as f32 / 64.0, 0.25];
+            let trilinear = lut.lookup(color, LutInterpolation::Trilinear);
+            let tetrahedral = lut.lookup(color, LutInterpolation::Tetrahedral);
+            for (a, b) in trilinear.iter().zip(tetrahedral) {
+                assert!(
+                    (a - b).abs() < 0.02,
+                    "{color:?}: {trilinear:?} {tetrahedral:?}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// A Hald CLUT of an identity table maps colors to themselves
+    #[test]
+    #[cfg(feature = "image")]
+    fn hald_identity() -> Result<()> {
+        use image::{DynamicImage, RgbImage};
+
+        let level = 4u32;
+        let size = level * level;
+        let side = level * level * level;
+        let hald = RgbImage::from_fn(side, side, |x, y| {
+            let i = x + y * side;
+            let scale = |c: u32| (c * 255 / (size - 1)) as u8;
+            image::Rgb([
+                scale(i % size),
+                scale(i / size % size),
+                scale(i / (size * size)),
+            ])
+        });
+        let lut = Lut3d::from_hald_image(&DynamicImage::ImageRgb8(hald))?;
+        assert_eq!(lut.size(), 16);
+
+        let [r, g, b] = lut.lookup([0.2, 0.6, 1.0], LutInterpolation::Tetrahedral);
+        assert!((r - 0.2).abs() < 0.01 && (g - 0.6).abs() < 0.01 && (b - 1.0).abs() < 0.01);
+
+        let not_hald = DynamicImage::ImageRgb8(RgbImage::new(60, 60));
+        assert!(matches!(
+            Lut3d::from_hald_image(&not_hald),
+            Err(LutError::InvalidHaldSize)
+        ));
+        Ok(())
+    }
+
+    /// Grade the test image with a .cube file through the editor pipeline
+    #[test]
+    #[cfg(feature = "image")]
+    fn editor_cube_grade() -> Result<()> {
+        use plutofilter_rs::{ImageEditor, get_resource_path};
+
+        let lut = Lut3d::from_cube_file(get_resource_path(&["luts"], "teal-orange.cube"))?;
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let output_path = get_resource_path(&["test_output_images", "lut"], "teal-orange.png");
+        ImageEditor::open(base_file)
+            .apply_lut(&lut, LutInterpolation::Tetrahedral)
+            .save_to(output_path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod misaligned_image_test {
+    use std::alloc::{GlobalAlloc, Layout, System};
+
+    use image::{DynamicImage, Rgba, RgbaImage};
+    use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Offsets every byte buffer by one byte, so image buffers are never aligned for `u32`
+    struct Misaligned;
+
+    unsafe impl GlobalAlloc for Misaligned {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            match layout.align() {
+                1 => {
+                    // SAFETY: The size is non-zero and 4 is a power of two.
+                    let layout = unsafe { Layout::from_size_align_unchecked(layout.size() + 4, 4) };
+                    // SAFETY: The layout has a non-zero size.
+                    let base = unsafe { System.alloc(layout) };
+                    match base.is_null() {
+                        true => base,
+                        false => base.wrapping_add(1),
+                    }
+                }
+                // SAFETY: Forwarded from the caller.
+                _ => unsafe { System.alloc(layout) },
+            }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            match layout.align() {
+                1 => {
+                    // SAFETY: Allocated by `alloc` with the same layout.
+                    let layout = unsafe { Layout::from_size_align_unchecked(layout.size() + 4, 4) };
+                    // SAFETY: `alloc` returned the allocation offset by one byte.
+                    unsafe { System.dealloc(ptr.wrapping_sub(1), layout) }
+                }
+                // SAFETY: Forwarded from the caller.
+                _ => unsafe { System.dealloc(ptr, layout) },
+            }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Misaligned = Misaligned;
+
+    /// Images whose bytes are not aligned for `u32` are filtered through a copy that is written
+    /// back into the image
+    #[test]
+    fn filters_misaligned_image() -> Result<()> {
+        let mut image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(5, 3, Rgba([0, 0, 0, 255])));
+        let bytes = image.as_bytes().as_ptr();
+        assert_ne!(bytes.align_offset(align_of::<u32>()), 0);
+
+        let mut input = SurfaceBuf::new(5, 3, ColorChannel::RGBA32);
+        input.fill([10, 20, 30, 255]);
+        let mut output = Surface::try_from_image(&mut image)?;
+        assert!(output.is_copy());
+        Surface::color_transform_invert(&input, &mut output, 1.0);
+        output.finish();
+        assert_eq!(image.as_bytes().as_ptr(), bytes);
+        assert!(
+            image
+                .to_rgba8()
+                .pixels()
+                .all(|pixel| pixel.0 == [245, 235, 225, 255])
+        );
+        Ok(())
+    }
+}
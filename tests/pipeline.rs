@@ -0,0 +1,137 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod pipeline_test {
+    use image::RgbaImage;
+    use plutofilter_rs::{BlendMode, FilterInput, ImageEditor, get_resource_path};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn save(editor: ImageEditor, name: &str) -> Result<RgbaImage> {
+        let output_path = get_resource_path(&["test_output_images", "pipeline"], name);
+        editor.save_to(&output_path)?;
+        Ok(image::open(output_path)?.into_rgba8())
+    }
+
+    fn open() -> ImageEditor {
+        ImageEditor::open(get_resource_path(&["original_images"], "test-image.jpg"))
+    }
+
+    /// Every operation continues from the result of the previous one
+    #[test]
+    fn operations_chain() -> Result<()> {
+        let chained = save(
+            open().gaussian_blur(4.0, 4.0).color_transform_contrast(1.5),
+            "chained.png",
+        )?;
+        let contrast_only = save(open().color_transform_contrast(1.5), "contrast-only.png")?;
+        let blurred = save(
+            open()
+                .color_transform_contrast(1.5)
+                .input(FilterInput::SourceGraphic)
+                .gaussian_blur(4.0, 4.0)
+                .color_transform_contrast(1.5),
+            "chained-after-branch.png",
+        )?;
+        assert_ne!(chained, contrast_only);
+        assert_eq!(chained, blurred);
+        Ok(())
+    }
+
+    /// The deprecated `_inplace` names behave like the plain operations
+    #[test]
+    #[allow(deprecated)]
+    fn inplace_aliases() -> Result<()> {
+        let aliased = save(
+            open()
+                .color_transform_sepia_inplace(0.8)
+                .gaussian_blur_inplace(2.0, 2.0),
+            "aliased.png",
+        )?;
+        let plain = save(
+            open().color_transform_sepia(0.8).gaussian_blur(2.0, 2.0),
+            "plain.png",
+        )?;
+        assert_eq!(aliased, plain);
+        Ok(())
+    }
+
+    /// Blend a blurred copy over the source graphic, eagerly and lazily
+    #[test]
+    fn source_graphic_glow() -> Result<()> {
+        let glow = |editor: ImageEditor| {
+            editor
+                .gaussian_blur(6.0, 6.0)
+                .color_transform_brightness(0.6)
+                .blend(FilterInput::SourceGraphic, BlendMode::Screen)
+        };
+        let eager = save(glow(open()), "glow.png")?;
+        let lazy = save(glow(open().lazy()), "glow-lazy.png")?;
+        assert_eq!(eager, lazy);
+        Ok(())
+    }
+
+    /// Named results can be restored and blended after other branches ran
+    #[test]
+    fn named_results() -> Result<()> {
+        let branched = save(
+            open()
+                .color_transform_grayscale(1.0)
+                .result("gray")
+                .input(FilterInput::SourceGraphic)
+                .color_transform_sepia(1.0)
+                .blend(FilterInput::result("gray"), BlendMode::Multiply),
+            "named-results.png",
+        )?;
+        let gray = save(open().color_transform_grayscale(1.0), "gray.png")?;
+        let restored = save(
+            open()
+                .color_transform_grayscale(1.0)
+                .result("gray")
+                .color_transform_invert(1.0)
+                .input(FilterInput::result("gray")),
+            "restored.png",
+        )?;
+        assert_eq!(gray, restored);
+        assert_ne!(branched, gray);
+        Ok(())
+    }
+
+    /// Reading a result that was never stored panics
+    #[test]
+    #[should_panic(expected = "No result named `missing`.")]
+    fn missing_result() {
+        let _ = open().input(FilterInput::result("missing"));
+    }
+
+    /// Blends and composites with a smaller layer keep the previous result outside the layer
+    #[test]
+    fn smaller_layers() {
+        use image::{DynamicImage, Rgba};
+        use plutofilter_rs::CompositeOperator;
+
+        let image =
+            || DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([200, 0, 0, 255])));
+        let layer = || DynamicImage::ImageRgba8(RgbaImage::new(2, 2));
+        let inverted = Rgba([55, 255, 255, 255]);
+
+        let blended = ImageEditor::from_dynamic_image(image())
+            .color_transform_invert(1.0)
+            .blend(layer(), BlendMode::Normal)
+            .into_dynamic_image()
+            .into_rgba8();
+        assert_eq!(*blended.get_pixel(0, 0), inverted);
+        assert_eq!(*blended.get_pixel(3, 3), inverted);
+
+        let composited = ImageEditor::from_dynamic_image(image())
+            .color_transform_invert(1.0)
+            .composite(layer(), CompositeOperator::Over)
+            .color_transform_invert(1.0)
+            .composite_arithmetic(layer(), 0.0, 1.0, 0.0, 0.0)
+            .into_dynamic_image()
+            .into_rgba8();
+        assert!(
+            composited
+                .pixels()
+                .all(|pixel| *pixel == Rgba([200, 0, 0, 255]))
+        );
+    }
+}
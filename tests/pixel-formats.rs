@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod pixel_formats_test {
+    use plutofilter_rs::{
+        Argb8, Bgra8, ColorChannel, FormatSurface, Gray8, GrayAlpha8, PixelFormat, Rgb8, Rgb565,
+        Rgba8, Surface,
+    };
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Storing and loading a pixel returns the same channels for every lossless format
+    #[test]
+    fn store_load_round_trip() {
+        fn round_trip<F: PixelFormat>(rgba: [u32; 4]) -> [u32; 4] {
+            let mut bytes = [0u8; 4];
+            F::store(&mut bytes, rgba);
+            F::load(&bytes)
+        }
+        let color = [10, 20, 30, 40];
+        assert_eq!(round_trip::<Rgba8>(color), color);
+        assert_eq!(round_trip::<Bgra8>(color), color);
+        assert_eq!(round_trip::<Argb8>(color), color);
+        assert_eq!(round_trip::<Rgb8>([10, 20, 30, 255]), [10, 20, 30, 255]);
+        assert_eq!(round_trip::<Gray8>([90, 90, 90, 255]), [90, 90, 90, 255]);
+        assert_eq!(
+            round_trip::<GrayAlpha8>([90, 90, 90, 128]),
+            [90, 90, 90, 128]
+        );
+        assert_eq!(round_trip::<Rgb565>([255, 0, 255, 255]), [255, 0, 255, 255]);
+    }
+
+    /// Byte order of the 32-bit formats
+    #[test]
+    fn byte_order() {
+        let mut bytes = [0u8; 4];
+        Bgra8::store(&mut bytes, [1, 2, 3, 4]);
+        assert_eq!(bytes, [3, 2, 1, 4]);
+        Argb8::store(&mut bytes, [1, 2, 3, 4]);
+        assert_eq!(bytes, [4, 1, 2, 3]);
+        Rgb565::store(&mut bytes, [255, 0, 0, 255]);
+        assert_eq!(&bytes[..2], &0xF800u16.to_le_bytes());
+    }
+
+    /// Inverting an RGB565 framebuffer in place of a copy
+    #[test]
+    fn rgb565_invert() -> Result<()> {
+        let (width, height) = (4, 2);
+        let mut input_bytes = [0x00u8, 0xF8].repeat(width * height);
+        let mut output_bytes = vec![0u8; width * height * 2];
+        let input = FormatSurface::<Rgb565>::make(
+            &mut input_bytes,
+            width as u32,
+            height as u32,
+            width * 2,
+        )?;
+        let mut output = FormatSurface::<Rgb565>::make(
+            &mut output_bytes,
+            width as u32,
+            height as u32,
+            width * 2,
+        )?;
+        FormatSurface::color_transform_invert(&input, &mut output, 1.0);
+
+        for pixel in output_bytes.chunks(2) {
+            assert_eq!(Rgb565::load(pixel), [0, 255, 255, 255]);
+        }
+        Ok(())
+    }
+
+    /// Converting while transforming, from a padded RGB8 frame into a BGRA8 buffer
+    #[test]
+    fn rgb8_to_bgra8() -> Result<()> {
+        let mut input_bytes = vec![200, 100, 50, 0, 0, 0, 200, 100, 50, 0, 0, 0];
+        let mut output_bytes = vec![0u8; 2 * 2 * 4];
+        let input = FormatSurface::<Rgb8>::make(&mut input_bytes, 1, 2, 6)?;
+        let mut output = FormatSurface::<Bgra8>::make(&mut output_bytes, 2, 2, 8)?;
+        FormatSurface::convert(&input, &mut output);
+
+        assert_eq!(&output_bytes[..4], &[50, 100, 200, 255]);
+        assert_eq!(&output_bytes[8..12], &[50, 100, 200, 255]);
+        assert_eq!(&output_bytes[4..8], &[0, 0, 0, 0]);
+        Ok(())
+    }
+
+    /// Blurring a flat gray frame leaves its interior unchanged
+    #[test]
+    fn gray8_blur_flat() -> Result<()> {
+        let (width, height) = (32, 32);
+        let mut input_bytes = vec![128u8; width * height];
+        let mut output_bytes = vec![0u8; width * height];
+        let input =
+            FormatSurface::<Gray8>::make(&mut input_bytes, width as u32, height as u32, width)?;
+        let mut output =
+            FormatSurface::<Gray8>::make(&mut output_bytes, width as u32, height as u32, width)?;
+        FormatSurface::gaussian_blur(&input, &mut output, 2.0, 2.0);
+
+        assert_eq!(output_bytes[16 * width + 16], 128);
+        Ok(())
+    }
+
+    /// One input is read by several filters through a shared borrow
+    #[test]
+    fn gray8_shared_input() -> Result<()> {
+        let (width, height) = (16, 8);
+        let mut input_bytes: Vec<u8> = (0..width * height)
+            .map(|i| (i % width * 16) as u8)
+            .collect();
+        let mut horizontal = vec![0u8; width * height];
+        let mut vertical = vec![0u8; width * height];
+        let make = |bytes| FormatSurface::<Gray8>::make(bytes, width as u32, height as u32, width);
+
+        let input = make(&mut input_bytes)?;
+        FormatSurface::blur_horizontal(&input, &mut make(&mut horizontal)?, 1.5);
+        FormatSurface::blur_vertical(&input, &mut make(&mut vertical)?, 1.5);
+
+        // Columns are flat, so only the horizontal blur changes the rows away from the edges.
+        let middle = 3 * width..5 * width;
+        assert_eq!(vertical[middle.clone()], input_bytes[middle.clone()]);
+        assert_ne!(horizontal[middle.clone()], input_bytes[middle]);
+        Ok(())
+    }
+
+    /// Buffers too small for the requested size are rejected
+    #[test]
+    fn make_rejects_short_buffer() {
+        let mut bytes = vec![0u8; 10];
+        assert!(FormatSurface::<Rgb8>::make(&mut bytes, 4, 1, 12).is_err());
+        assert!(FormatSurface::<Rgb8>::make(&mut bytes, 4, 1, 8).is_err());
+        assert!(FormatSurface::<Rgb8>::make(&mut bytes, 3, 1, 9).is_ok());
+    }
+
+    /// An identity transform on an ARGB32 surface keeps the channel order
+    #[test]
+    fn argb32_identity_transform() -> Result<()> {
+        let pixel = u32::from_le_bytes([255, 10, 20, 30]);
+        let mut input_pixels = vec![pixel; 4];
+        let mut output_pixels = vec![0u32; 4];
+        let mut input = Surface::make(&mut input_pixels, 2, 2, 2, Some(ColorChannel::ARGB32))?;
+        let mut output = Surface::make(&mut output_pixels, 2, 2, 2, Some(ColorChannel::ARGB32))?;
+        Surface::color_transform_opacity(&mut input, &mut output, 1.0);
+
+        assert_eq!(output_pixels, vec![pixel; 4]);
+        Ok(())
+    }
+
+    /// Every filter stores ARGB32 and BGRA32 outputs in their own order, giving the same pixels as
+    /// on RGBA32
+    #[test]
+    fn argb32_outputs_match_rgba32() {
+        use plutofilter_rs::{
+            BlendMode, CompositeOperator, EdgeMode, ResampleFilter, SurfaceBuf, SurfaceView,
+        };
+
+        let (width, height) = (12u32, 9u32);
+        let rgba: Vec<u32> = (0..width * height)
+            .map(|i| {
+                let v = i.wrapping_mul(2654435761) >> 8;
+                let a = 128 + (v >> 24) / 2;
+                u32::from_le_bytes([v as u8 % a as u8, (v >> 8) as u8 % a as u8, 30, a as u8])
+            })
+            .collect();
+        let convert = |channel, pixel: &u32| {
+            let [r, g, b, a] = pixel.to_le_bytes();
+            u32::from_le_bytes(match channel {
+                ColorChannel::RGBA32 => [r, g, b, a],
+                ColorChannel::ARGB32 => [a, r, g, b],
+                ColorChannel::BGRA32 => [b, g, r, a],
+            })
+        };
+        let surfaces = |channel| {
+            let pixels = rgba.iter().map(|pixel| convert(channel, pixel)).collect();
+            let input = SurfaceBuf::from_pixels(pixels, width, height, channel).unwrap();
+            (input, SurfaceBuf::new(width, height, channel))
+        };
+
+        type Filter = fn(SurfaceView<'_>, &mut Surface<'_>);
+        let filters: [(&str, Filter); 7] = [
+            ("sepia", |i, o| Surface::color_transform_sepia(i, o, 0.7)),
+            ("blur", |i, o| Surface::gaussian_blur(i, o, 1.5, 1.0)),
+            ("median", |i, o| Surface::median_filter(i, o, 1, 1)),
+            ("bilateral", |i, o| {
+                Surface::bilateral_filter(i, o, 2.0, 0.2)
+            }),
+            ("blend", |i, o| Surface::blend(i, i, o, BlendMode::Screen)),
+            ("composite", |i, o| {
+                Surface::composite(i, i, o, CompositeOperator::Xor)
+            }),
+            ("transform", |i, o| {
+                Surface::transform(
+                    i,
+                    o,
+                    [0.9, 0.2, -0.2, 0.9, 1.0, 0.5],
+                    ResampleFilter::Bilinear,
+                    EdgeMode::Duplicate,
+                )
+            }),
+        ];
+        for (name, filter) in filters {
+            let (input, mut expected) = surfaces(ColorChannel::RGBA32);
+            filter(input.as_view(), &mut expected.as_surface());
+            for channel in [ColorChannel::ARGB32, ColorChannel::BGRA32] {
+                let (input, mut actual) = surfaces(channel);
+                filter(input.as_view(), &mut actual.as_surface());
+                let expected: Vec<u32> = expected
+                    .pixels()
+                    .iter()
+                    .map(|pixel| convert(channel, pixel))
+                    .collect();
+                assert_eq!(actual.pixels(), expected, "{name} in {channel:?}");
+            }
+        }
+    }
+}
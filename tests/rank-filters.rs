@@ -0,0 +1,62 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod rank_filters_test {
+    use plutofilter_rs::{ColorChannel, ImageEditor, Surface, get_resource_path};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Test Median Filter 2x2
+    #[test]
+    fn test_median_filter_2x2() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.median_filter(2, 2);
+
+        let output_path =
+            get_resource_path(&["test_output_images", "rank_filter"], "median-2x2.png");
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test Minimum Filter 3x3
+    #[test]
+    fn test_min_filter_3x3() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.min_filter(3, 3);
+
+        let output_path = get_resource_path(&["test_output_images", "rank_filter"], "min-3x3.png");
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test Maximum Filter 3x3
+    #[test]
+    fn test_max_filter_3x3() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.max_filter(3, 3);
+
+        let output_path = get_resource_path(&["test_output_images", "rank_filter"], "max-3x3.png");
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Median filter removes an isolated noise pixel
+    #[test]
+    fn test_median_filter_removes_salt() -> Result<()> {
+        let (width, height) = (5, 5);
+        let mut input_pixels = vec![0xFF000000; (width * height) as usize];
+        input_pixels[12] = 0xFFFFFFFF;
+        let mut output_pixels = vec![0; (width * height) as usize];
+        let channel = Some(ColorChannel::RGBA32);
+        let mut input = Surface::make(&mut input_pixels, width, height, width, channel)?;
+        let mut output = Surface::make(&mut output_pixels, width, height, width, channel)?;
+        Surface::median_filter(&mut input, &mut output, 1, 1);
+
+        assert!(output_pixels.iter().all(|&pixel| pixel == 0xFF000000));
+        Ok(())
+    }
+}
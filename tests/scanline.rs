@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod scanline_test {
+    use plutofilter_rs::{
+        BlendMode, BlendRows, BlurRows, ColorChannel, ColorInterpolation, ColorMatrix,
+        ColorMatrixRows, CompositeOperator, CompositeRows, RowFilter, RowWriter, Surface,
+        SurfaceBuf,
+    };
+
+    fn noise(width: u32, height: u32, seed: u32) -> SurfaceBuf {
+        let pixels = (0..width * height)
+            .map(|i| {
+                let v = (i ^ seed).wrapping_mul(2654435761) >> 8;
+                let a = 128 + (v >> 24) / 2;
+                u32::from_le_bytes([v as u8 % a as u8, (v >> 8) as u8 % a as u8, 30, a as u8])
+            })
+            .collect();
+        SurfaceBuf::from_pixels(pixels, width, height, ColorChannel::RGBA32).unwrap()
+    }
+
+    fn rows(image: &SurfaceBuf) -> impl Iterator<Item = &[u32]> {
+        image.pixels().chunks_exact(image.width() as usize)
+    }
+
+    fn collect(image: &SurfaceBuf, rows: Vec<Vec<u32>>) -> SurfaceBuf {
+        let pixels = rows.concat();
+        SurfaceBuf::from_pixels(pixels, image.width(), image.height(), ColorChannel::RGBA32)
+            .unwrap()
+    }
+
+    fn stream(image: &SurfaceBuf, filter: &mut impl RowFilter) -> SurfaceBuf {
+        let mut output = Vec::new();
+        let mut filtered = filter.rows(&mut output);
+        for row in rows(image) {
+            filtered.write_row(row).unwrap();
+        }
+        filtered.finish().unwrap();
+        collect(image, output)
+    }
+
+    /// Streamed blurs match blurring the whole image, also when the kernel is taller than the image
+    #[test]
+    fn blur_matches_whole_image() {
+        for (width, height, x, y) in [(37, 29, 2.0, 3.0), (20, 4, 1.0, 6.0), (16, 16, 0.0, 2.0)] {
+            let image = noise(width, height, 0);
+            let mut expected = SurfaceBuf::new(width, height, ColorChannel::RGBA32);
+            Surface::gaussian_blur(&image, &mut expected.as_surface(), x, y);
+            let actual = stream(&image, &mut BlurRows::new(width, height, x, y));
+            assert_eq!(actual, expected, "{width}x{height} blurred by {x}x{y}");
+        }
+    }
+
+    /// Rows come out of a blur once the rows below them are pushed
+    #[test]
+    fn blur_latency() {
+        let image = noise(24, 40, 0);
+        let mut blur = BlurRows::new(24, 40, 1.0, 1.0);
+        let mut output = Vec::new();
+        for row in rows(&image).take(20) {
+            blur.push_row(row, &mut output).unwrap();
+        }
+        assert!((10..20).contains(&output.len()));
+    }
+
+    /// Filters chain by writing into each other
+    #[test]
+    fn chained() {
+        let image = noise(33, 21, 0);
+        let mut expected = image.clone();
+        Surface::in_place(&mut expected.as_surface(), |input, output| {
+            Surface::color_transform(input, output, ColorMatrix::sepia(0.8))
+        });
+        Surface::in_place(&mut expected.as_surface(), |input, output| {
+            Surface::gaussian_blur(input, output, 1.5, 1.5)
+        });
+
+        let mut sepia = ColorMatrixRows::new(ColorMatrix::sepia(0.8));
+        let mut blur = BlurRows::new(33, 21, 1.5, 1.5);
+        let mut output = Vec::new();
+        let mut blurred = blur.rows(&mut output);
+        let mut filtered = sepia.rows(&mut blurred);
+        for row in rows(&image) {
+            filtered.write_row(row).unwrap();
+        }
+        filtered.finish().unwrap();
+        blurred.finish().unwrap();
+        assert_eq!(collect(&image, output), expected);
+    }
+
+    /// Color matrices in linear RGB match the whole image
+    #[test]
+    fn color_matrix_linear_rgb() {
+        let image = noise(19, 11, 0);
+        let mut expected = SurfaceBuf::new(19, 11, ColorChannel::RGBA32)
+            .with_color_interpolation(ColorInterpolation::LinearRGB);
+        Surface::color_transform(
+            &image,
+            &mut expected.as_surface(),
+            ColorMatrix::saturate(0.3),
+        );
+        let mut filter = ColorMatrixRows::new(ColorMatrix::saturate(0.3))
+            .with_color_interpolation(ColorInterpolation::LinearRGB);
+        assert_eq!(
+            stream(&image, &mut filter),
+            expected.with_color_interpolation(ColorInterpolation::SRGB)
+        );
+    }
+
+    /// Blends and composites write every row right away
+    #[test]
+    fn blend_and_composite() {
+        let (source, backdrop) = (noise(25, 9, 0), noise(25, 9, 7));
+        let pairs = || rows(&source).zip(rows(&backdrop));
+
+        let mut expected = SurfaceBuf::new(25, 9, ColorChannel::RGBA32);
+        Surface::blend(
+            &source,
+            &backdrop,
+            &mut expected.as_surface(),
+            BlendMode::Overlay,
+        );
+        let mut blend = BlendRows::new(BlendMode::Overlay);
+        let mut output = Vec::new();
+        for (y, (source, backdrop)) in pairs().enumerate() {
+            blend.push_rows(source, backdrop, &mut output).unwrap();
+            assert_eq!(output.len(), y + 1);
+        }
+        assert_eq!(collect(&source, output), expected);
+
+        Surface::composite(
+            &source,
+            &backdrop,
+            &mut expected.as_surface(),
+            CompositeOperator::Xor,
+        );
+        let mut composite = CompositeRows::new(CompositeOperator::Xor);
+        let mut output = Vec::new();
+        for (source, backdrop) in pairs() {
+            composite.push_rows(source, backdrop, &mut output).unwrap();
+        }
+        assert_eq!(collect(&source, output), expected);
+
+        let k = [0.5, 0.25, 0.75, 0.0];
+        Surface::composite_arithmetic(
+            &source,
+            &backdrop,
+            &mut expected.as_surface(),
+            k[0],
+            k[1],
+            k[2],
+            k[3],
+        );
+        let mut arithmetic = CompositeRows::arithmetic(k[0], k[1], k[2], k[3]);
+        let mut output = Vec::new();
+        for (source, backdrop) in pairs() {
+            arithmetic.push_rows(source, backdrop, &mut output).unwrap();
+        }
+        assert_eq!(collect(&source, output), expected);
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod surface_buf_test {
+    use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf, SurfaceError};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Filters run between owned buffers held in a struct
+    #[test]
+    fn filter_between_buffers() {
+        struct Layers {
+            input: SurfaceBuf,
+            faded: SurfaceBuf,
+        }
+        let mut layers = Layers {
+            input: SurfaceBuf::new(32, 32, ColorChannel::RGBA32),
+            faded: SurfaceBuf::new(32, 32, ColorChannel::RGBA32),
+        };
+        layers.input.fill([200, 100, 50, 255]);
+        Surface::color_transform_opacity(
+            &mut layers.input.as_surface(),
+            &mut layers.faded.as_surface(),
+            0.5,
+        );
+        let [_, _, _, a] = layers.faded.pixels()[0].to_le_bytes();
+        assert_eq!(a, 127);
+    }
+
+    /// Fill premultiplies the color in the byte order of the buffer
+    #[test]
+    fn fill() {
+        let mut rgba = SurfaceBuf::new(2, 2, ColorChannel::RGBA32);
+        rgba.fill([255, 0, 0, 128]);
+        assert_eq!(rgba.pixels()[3].to_le_bytes(), [128, 0, 0, 128]);
+
+        let mut argb = SurfaceBuf::new(2, 2, ColorChannel::ARGB32);
+        argb.fill([255, 0, 0, 128]);
+        assert_eq!(argb.pixels()[3].to_le_bytes(), [128, 128, 0, 0]);
+    }
+
+    /// Resizing keeps the overlapping pixels in place and clears new ones
+    #[test]
+    fn resize() -> Result<()> {
+        let mut buffer = SurfaceBuf::from_pixels((0..6).collect(), 3, 2, ColorChannel::RGBA32)?;
+        buffer.resize(2, 3);
+        assert_eq!(buffer.pixels(), &[0, 1, 3, 4, 0, 0]);
+        buffer.resize(4, 1);
+        assert_eq!((buffer.width(), buffer.height()), (4, 1));
+        assert_eq!(buffer.pixels(), &[0, 1, 0, 0]);
+
+        let copy = buffer.clone();
+        buffer.fill([0, 0, 0, 0]);
+        assert_ne!(copy, buffer);
+
+        assert!(matches!(
+            SurfaceBuf::from_pixels(vec![0; 5], 3, 2, ColorChannel::RGBA32),
+            Err(SurfaceError::InvalidPixelLength)
+        ));
+        Ok(())
+    }
+
+    /// Converting opaque images to and from `RgbaImage` keeps the bytes, whatever the channel
+    /// order
+    #[test]
+    #[cfg(feature = "image")]
+    fn rgba_image_round_trip() -> Result<()> {
+        use image::RgbaImage;
+
+        let image = RgbaImage::from_fn(8, 4, |x, y| image::Rgba([x as u8, y as u8, 7, 255]));
+        let buffer = SurfaceBuf::from(&image);
+        assert_eq!(RgbaImage::from(&buffer), image);
+
+        let argb: Vec<u32> = buffer
+            .pixels()
+            .iter()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.to_le_bytes();
+                u32::from_le_bytes([a, r, g, b])
+            })
+            .collect();
+        let argb = SurfaceBuf::from_pixels(argb, 8, 4, ColorChannel::ARGB32)?;
+        assert_eq!(RgbaImage::from(argb), image);
+        Ok(())
+    }
+
+    /// Semi-transparent images are premultiplied like `fill`, and unpremultiplied on the way back
+    #[test]
+    #[cfg(feature = "image")]
+    fn rgba_image_premultiplied() {
+        use image::{Rgba, RgbaImage};
+
+        let color = [200, 100, 50, 128];
+        let image = RgbaImage::from_pixel(4, 3, Rgba(color));
+        let mut filled = SurfaceBuf::new(4, 3, ColorChannel::RGBA32);
+        filled.fill(color);
+        assert_eq!(SurfaceBuf::from(&image), filled);
+
+        let image = RgbaImage::from_fn(16, 16, |x, y| {
+            Rgba([(x * 16) as u8, (y * 16) as u8, 255, (x * 16 + y) as u8 | 1])
+        });
+        let round_trip = RgbaImage::from(SurfaceBuf::from(&image));
+        for (before, after) in image.pixels().zip(round_trip.pixels()) {
+            let alpha = before.0[3];
+            assert_eq!(after.0[3], alpha);
+            for channel in 0..3 {
+                let difference = before.0[channel].abs_diff(after.0[channel]);
+                assert!(
+                    u32::from(difference) * u32::from(alpha) <= 2 * 255,
+                    "{before:?} came back as {after:?}"
+                );
+            }
+        }
+    }
+}
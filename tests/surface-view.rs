@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod surface_view_test {
+    use plutofilter_rs::{
+        BlendMode, ColorChannel, CompositeOperator, Surface, SurfaceBuf, SurfaceError, SurfaceView,
+    };
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn gradient() -> SurfaceBuf {
+        let pixels = (0..16 * 16)
+            .map(|i| u32::from_le_bytes([i as u8, (i / 2) as u8, 64, 255]))
+            .collect();
+        SurfaceBuf::from_pixels(pixels, 16, 16, ColorChannel::RGBA32).unwrap()
+    }
+
+    /// The same surface can be used as both inputs of a blend
+    #[test]
+    fn self_blend() {
+        let mut image = gradient();
+        let mut squared = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::blend(
+            &image,
+            &image,
+            &mut squared.as_surface(),
+            BlendMode::Multiply,
+        );
+
+        let mut copy = image.clone();
+        let mut expected = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::blend(
+            &mut image.as_surface(),
+            &mut copy.as_surface(),
+            &mut expected.as_surface(),
+            BlendMode::Multiply,
+        );
+        assert_eq!(squared, expected);
+    }
+
+    /// Filtering in place matches filtering into a separate surface
+    #[test]
+    fn in_place_matches_separate_output() {
+        let image = gradient();
+        let mut expected = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::gaussian_blur(&image, &mut expected.as_surface(), 2.0, 2.0);
+
+        let mut blurred = image.clone();
+        Surface::in_place(&mut blurred.as_surface(), |input, output| {
+            Surface::gaussian_blur(input, output, 2.0, 2.0)
+        });
+        assert_eq!(blurred, expected);
+
+        let mut expected = SurfaceBuf::new(16, 16, ColorChannel::RGBA32);
+        Surface::composite(
+            &image,
+            &image,
+            &mut expected.as_surface(),
+            CompositeOperator::Xor,
+        );
+        let mut composited = image.clone();
+        Surface::in_place(&mut composited.as_surface(), |input, output| {
+            Surface::composite(input, input, output, CompositeOperator::Xor)
+        });
+        assert_eq!(composited, expected);
+    }
+
+    /// In-place filtering of a subregion leaves the pixels around it untouched
+    #[test]
+    fn in_place_subregion() -> Result<()> {
+        let image = gradient();
+        let mut inverted = image.clone();
+        {
+            let mut surface = inverted.as_surface();
+            let mut region = surface.make_sub(4, 4, 8, 8)?;
+            Surface::in_place(&mut region, |input, output| {
+                Surface::color_transform_invert(input, output, 1.0)
+            });
+        }
+        for (index, (before, after)) in image.pixels().iter().zip(inverted.pixels()).enumerate() {
+            let (x, y) = (index % 16, index / 16);
+            let inside = (4..12).contains(&x) && (4..12).contains(&y);
+            assert_eq!(inside, before != after, "pixel ({x}, {y})");
+        }
+        Ok(())
+    }
+
+    /// Views can be made over borrowed pixels and are checked like surfaces
+    #[test]
+    fn make_view() -> Result<()> {
+        let pixels = vec![u32::from_le_bytes([255, 0, 0, 0]); 4 * 4];
+        let view = SurfaceView::make(&pixels, 3, 4, 4, Some(ColorChannel::ARGB32))?;
+        let mut output = SurfaceBuf::new(3, 4, ColorChannel::ARGB32);
+        Surface::color_transform_invert(view, &mut output.as_surface(), 1.0);
+        assert!(
+            output
+                .pixels()
+                .iter()
+                .all(|pixel| pixel.to_le_bytes() == [255; 4])
+        );
+
+        assert!(matches!(
+            SurfaceView::make(&pixels, 5, 4, 4, None),
+            Err(SurfaceError::StrideLessThanWidth)
+        ));
+        assert!(matches!(
+            SurfaceView::make(&pixels, 4, 5, 4, None),
+            Err(SurfaceError::InvalidPixelLength)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tiled_test {
+    use plutofilter_rs::{
+        ColorChannel, ColorInterpolation, ColorMatrix, Surface, SurfaceBuf, SurfaceReader,
+        SurfaceWriter, TiledPipeline,
+    };
+
+    fn noise(width: u32, height: u32) -> SurfaceBuf {
+        let pixels = (0..width * height)
+            .map(|i| {
+                let v = i.wrapping_mul(2654435761) >> 8;
+                let a = 128 + (v >> 24) / 2;
+                u32::from_le_bytes([v as u8 % a as u8, (v >> 8) as u8 % a as u8, 30, a as u8])
+            })
+            .collect();
+        SurfaceBuf::from_pixels(pixels, width, height, ColorChannel::RGBA32).unwrap()
+    }
+
+    fn run_tiled(pipeline: &TiledPipeline, image: &SurfaceBuf) -> SurfaceBuf {
+        let mut output = SurfaceBuf::new(image.width(), image.height(), ColorChannel::RGBA32);
+        pipeline
+            .run(
+                &mut SurfaceReader::new(image),
+                &mut SurfaceWriter::new(&mut output.as_surface()),
+            )
+            .unwrap();
+        output
+    }
+
+    /// Tiles of any size give the same result as filtering the whole image
+    #[test]
+    fn matches_whole_image() {
+        let image = noise(61, 47);
+        let mut expected = image.clone();
+        {
+            let mut surface = expected.as_surface();
+            Surface::in_place(&mut surface, |i, o| Surface::gaussian_blur(i, o, 3.0, 2.0));
+            Surface::in_place(&mut surface, |i, o| Surface::median_filter(i, o, 2, 1));
+            Surface::in_place(&mut surface, |i, o| {
+                Surface::color_transform(i, o, ColorMatrix::sepia(0.7))
+            });
+            Surface::in_place(&mut surface, |i, o| Surface::max_filter(i, o, 1, 3));
+        }
+        for (width, height) in [(7, 5), (16, 8), (61, 1), (512, 512)] {
+            let pipeline = TiledPipeline::new()
+                .with_tile_size(width, height)
+                .gaussian_blur(3.0, 2.0)
+                .median_filter(2, 1)
+                .color_transform_sepia(0.7)
+                .max_filter(1, 3);
+            assert_eq!(
+                run_tiled(&pipeline, &image),
+                expected,
+                "{width}x{height} tiles"
+            );
+        }
+    }
+
+    /// Blurs in linear RGB are tiled with the same halo
+    #[test]
+    fn linear_rgb() {
+        let image = noise(40, 33).with_color_interpolation(ColorInterpolation::LinearRGB);
+        let mut expected = image.clone();
+        Surface::in_place(&mut expected.as_surface(), |i, o| {
+            Surface::gaussian_blur(i, o, 2.5, 2.5)
+        });
+        let pipeline = TiledPipeline::new()
+            .with_tile_size(9, 6)
+            .color_interpolation(ColorInterpolation::LinearRGB)
+            .gaussian_blur(2.5, 2.5);
+        let image = noise(40, 33);
+        assert_eq!(
+            run_tiled(&pipeline, &image),
+            expected.with_color_interpolation(ColorInterpolation::SRGB)
+        );
+    }
+
+    /// The halo adds up the footprints of the filters
+    #[test]
+    fn halo() {
+        let pipeline = TiledPipeline::new()
+            .color_transform_invert(1.0)
+            .median_filter(2, 3);
+        assert_eq!(pipeline.halo(), (2, 3));
+        let blur = Surface::gaussian_blur_padding(4.0, 0.0);
+        assert_eq!(pipeline.gaussian_blur(4.0, 0.0).halo(), (2 + blur.0, 3));
+    }
+
+    /// Files are filtered like the editor filters them, streaming PNG rows
+    #[cfg(feature = "image")]
+    #[test]
+    fn process_file() -> Result<(), Box<dyn std::error::Error>> {
+        use plutofilter_rs::{ImageEditor, get_resource_path};
+
+        let input = get_resource_path(&["original_images"], "firebrick-circle.png");
+        let expected = ImageEditor::open(&input)
+            .gaussian_blur(3.0, 3.0)
+            .color_transform_hue_rotate(90.0)
+            .min_filter(2, 2)
+            .into_dynamic_image()
+            .into_rgba8();
+        let pipeline = TiledPipeline::new()
+            .with_tile_size(64, 32)
+            .gaussian_blur(3.0, 3.0)
+            .color_transform_hue_rotate(90.0)
+            .min_filter(2, 2);
+
+        let png = get_resource_path(&["test_output_images", "tiled"], "tiled.png");
+        pipeline.process_file(&input, &png)?;
+        assert_eq!(image::open(&png)?.into_rgba8(), expected);
+
+        // Formats that can not be streamed are encoded in full.
+        let bmp = std::env::temp_dir().join("plutofilter-tiled.bmp");
+        pipeline.process_file(&input, &bmp)?;
+        assert_eq!(image::open(&bmp)?.into_rgba8(), expected);
+        std::fs::remove_file(bmp)?;
+        Ok(())
+    }
+}
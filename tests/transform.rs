@@ -0,0 +1,106 @@
+#[cfg(test)]
+#[cfg(feature = "image")]
+mod transform_test {
+    use plutofilter_rs::{EdgeMode, ImageEditor, ResampleFilter, get_resource_path};
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// Rotation by 30 degrees around the center of a 512x382 image
+    fn rotate_30() -> [f32; 6] {
+        let (cx, cy) = (256.0, 191.0);
+        let (sin, cos) = f32::sin_cos(30.0f32.to_radians());
+        [
+            cos,
+            sin,
+            -sin,
+            cos,
+            cx - cos * cx + sin * cy,
+            cy - sin * cx - cos * cy,
+        ]
+    }
+
+    /// Test rotation with bilinear filter
+    #[test]
+    fn test_transform_rotate_bilinear() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.transform(rotate_30(), ResampleFilter::Bilinear, EdgeMode::None);
+
+        let output_path =
+            get_resource_path(&["test_output_images", "transform"], "rotate-bilinear.png");
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test skew with bicubic filter and wrapping edges
+    #[test]
+    fn test_transform_skew_bicubic_wrap() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.transform(
+            [1.0, 0.0, 0.3, 1.0, 0.0, 0.0],
+            ResampleFilter::Bicubic,
+            EdgeMode::Wrap,
+        );
+
+        let output_path = get_resource_path(
+            &["test_output_images", "transform"],
+            "skew-bicubic-wrap.png",
+        );
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test 2x magnification with nearest filter
+    #[test]
+    fn test_transform_scale_nearest() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.transform(
+            [2.0, 0.0, 0.0, 2.0, -256.0, -191.0],
+            ResampleFilter::Nearest,
+            EdgeMode::Duplicate,
+        );
+
+        let output_path =
+            get_resource_path(&["test_output_images", "transform"], "scale-nearest.png");
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Test thumbnail generation with lanczos filter
+    #[test]
+    fn test_resize_lanczos() -> Result<()> {
+        let base_file = get_resource_path(&["original_images"], "zhang-hanyun.jpg");
+        let mut editor = ImageEditor::open(base_file);
+        editor = editor.resize(128, 96, ResampleFilter::Lanczos);
+
+        let output_path =
+            get_resource_path(&["test_output_images", "transform"], "resize-lanczos.png");
+        editor.save_to(output_path)?;
+
+        Ok(())
+    }
+
+    /// Strong downscales are not singular and sample the image with a bounded kernel
+    #[test]
+    fn test_transform_strong_downscale() {
+        use plutofilter_rs::{ColorChannel, Surface, SurfaceBuf};
+
+        let mut image = SurfaceBuf::new(64, 64, ColorChannel::RGBA32);
+        image.fill([200, 120, 40, 255]);
+        let mut output = SurfaceBuf::new(2, 2, ColorChannel::RGBA32);
+        for filter in [ResampleFilter::Bilinear, ResampleFilter::Lanczos] {
+            Surface::transform(
+                &image,
+                &mut output.as_surface(),
+                [2e-4, 0.0, 0.0, 2e-4, 0.0, 0.0],
+                filter,
+                EdgeMode::Duplicate,
+            );
+            assert!(output.pixels().iter().all(|&p| p == image.pixels()[0]));
+        }
+    }
+}
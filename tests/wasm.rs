@@ -0,0 +1,40 @@
+#[cfg(test)]
+#[cfg(feature = "wasm")]
+mod wasm_test {
+    use plutofilter_rs::{ColorChannel, CssFilter, SurfaceBuf, wasm};
+
+    fn gradient() -> Vec<u8> {
+        (0..16 * 16)
+            .flat_map(|i| [i as u8, (i / 2) as u8, 64, 255])
+            .collect()
+    }
+
+    /// ImageData bytes give the same result as the filter run on a native surface,
+    /// whether or not the bytes are aligned for `u32` pixels
+    #[test]
+    fn css_filter_matches_native() {
+        let pixels = gradient()
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        let mut expected = SurfaceBuf::from_pixels(pixels, 16, 16, ColorChannel::RGBA32).unwrap();
+        let filter = "contrast(97%) hue-rotate(330deg) saturate(1.11) blur(1.5px)";
+        CssFilter::parse(filter)
+            .unwrap()
+            .apply(&mut expected.as_surface());
+        let expected = expected
+            .pixels()
+            .iter()
+            .flat_map(|pixel| pixel.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let mut storage = vec![0u32; 16 * 16 + 1];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut storage);
+        for offset in [0, 1] {
+            let data = &mut bytes[offset..offset + 16 * 16 * 4];
+            data.copy_from_slice(&gradient());
+            wasm::css_filter(data, 16, 16, filter).unwrap();
+            assert_eq!(data, expected.as_slice(), "offset {offset}");
+        }
+    }
+}